@@ -18,36 +18,87 @@ License along with `gui-tools`. If not, see <https://www.gnu.org/licenses/>.
 */
 
 use super::piet::kurbo::{Point, Rect, Size};
+use super::window::WindowState;
+use super::{DisplayInner, DrawState, Error};
+
+use std::cell::RefMut;
+use std::rc::Rc;
 
 /// Rendering context for the window.
-pub struct RenderContext<'display, 'surface> {
-    inner: theo::RenderContext<'display, 'surface>,
+///
+/// The context keeps the display and the window's surface borrowed until it is dropped, so it
+/// can be held across calls without any lifetimes.
+pub struct RenderContext {
+    inner: RenderContextInner,
     text: Text,
 }
 
-impl<'d, 's> RenderContext<'d, 's> {
-    pub(crate) fn new(mut inner: theo::RenderContext<'d, 's>) -> Self {
-        let text = piet::RenderContext::text(&mut inner).clone();
-        Self {
+#[ouroboros::self_referencing]
+struct RenderContextInner {
+    /// The display being drawn with.
+    display: Rc<DisplayInner>,
+
+    /// The window being drawn to.
+    window: Rc<WindowState>,
+
+    /// The borrowed drawing state of the display.
+    #[borrows(display)]
+    #[covariant]
+    draw: RefMut<'this, DrawState>,
+
+    /// The borrowed surface of the window.
+    #[borrows(window)]
+    #[covariant]
+    surface: RefMut<'this, theo::Surface>,
+
+    /// The underlying render context.
+    #[borrows(mut draw, mut surface)]
+    #[not_covariant]
+    context: theo::RenderContext<'this, 'this>,
+}
+
+impl RenderContext {
+    pub(crate) fn new(
+        display: Rc<DisplayInner>,
+        window: Rc<WindowState>,
+        width: u32,
+        height: u32,
+    ) -> Result<Self, Error> {
+        let mut inner = RenderContextInnerTryBuilder {
+            display,
+            window,
+            draw_builder: |display| display.draw.try_borrow_mut().map_err(|_| Error::busy()),
+            surface_builder: |window| window.surface.try_borrow_mut().map_err(|_| Error::busy()),
+            context_builder: |draw, surface| {
+                theo::RenderContext::new(draw.ready_mut(), surface, width, height)
+                    .map_err(Error::piet)
+            },
+        }
+        .try_build()?;
+
+        let text = inner.with_context_mut(|inner| piet::RenderContext::text(inner).clone());
+        Ok(Self {
             inner,
             text: Text { inner: text },
-        }
+        })
     }
 }
 
-impl piet::RenderContext for RenderContext<'_, '_> {
+impl piet::RenderContext for RenderContext {
     type Brush = Brush;
     type Image = Image;
     type Text = Text;
     type TextLayout = TextLayout;
 
     fn status(&mut self) -> Result<(), piet::Error> {
-        self.inner.status()
+        self.inner.with_context_mut(|inner| inner.status())
     }
 
     fn solid_brush(&mut self, color: piet::Color) -> Self::Brush {
         Brush {
-            inner: self.inner.solid_brush(color),
+            inner: self
+                .inner
+                .with_context_mut(|inner| inner.solid_brush(color)),
         }
     }
 
@@ -55,16 +106,20 @@ impl piet::RenderContext for RenderContext<'_, '_> {
         &mut self,
         gradient: impl Into<piet::FixedGradient>,
     ) -> Result<Self::Brush, piet::Error> {
-        self.inner.gradient(gradient).map(|inner| Brush { inner })
+        self.inner
+            .with_context_mut(|inner| inner.gradient(gradient))
+            .map(|inner| Brush { inner })
     }
 
     fn clear(&mut self, region: impl Into<Option<Rect>>, color: piet::Color) {
-        self.inner.clear(region, color)
+        self.inner
+            .with_context_mut(|inner| inner.clear(region, color))
     }
 
     fn stroke(&mut self, shape: impl kurbo::Shape, brush: &impl piet::IntoBrush<Self>, width: f64) {
         let brush = brush.make_brush(self, || shape.bounding_box());
-        self.inner.stroke(shape, &brush.inner, width)
+        self.inner
+            .with_context_mut(|inner| inner.stroke(shape, &brush.inner, width))
     }
 
     fn stroke_styled(
@@ -75,21 +130,24 @@ impl piet::RenderContext for RenderContext<'_, '_> {
         style: &piet::StrokeStyle,
     ) {
         let brush = brush.make_brush(self, || shape.bounding_box());
-        self.inner.stroke_styled(shape, &brush.inner, width, style)
+        self.inner
+            .with_context_mut(|inner| inner.stroke_styled(shape, &brush.inner, width, style))
     }
 
     fn fill(&mut self, shape: impl kurbo::Shape, brush: &impl piet::IntoBrush<Self>) {
         let brush = brush.make_brush(self, || shape.bounding_box());
-        self.inner.fill(shape, &brush.inner)
+        self.inner
+            .with_context_mut(|inner| inner.fill(shape, &brush.inner))
     }
 
     fn fill_even_odd(&mut self, shape: impl kurbo::Shape, brush: &impl piet::IntoBrush<Self>) {
         let brush = brush.make_brush(self, || shape.bounding_box());
-        self.inner.fill_even_odd(shape, &brush.inner)
+        self.inner
+            .with_context_mut(|inner| inner.fill_even_odd(shape, &brush.inner))
     }
 
     fn clip(&mut self, shape: impl kurbo::Shape) {
-        self.inner.clip(shape)
+        self.inner.with_context_mut(|inner| inner.clip(shape))
     }
 
     fn text(&mut self) -> &mut Self::Text {
@@ -97,23 +155,25 @@ impl piet::RenderContext for RenderContext<'_, '_> {
     }
 
     fn draw_text(&mut self, layout: &Self::TextLayout, pos: impl Into<Point>) {
-        self.inner.draw_text(&layout.inner, pos.into())
+        self.inner
+            .with_context_mut(|inner| inner.draw_text(&layout.inner, pos.into()))
     }
 
     fn save(&mut self) -> Result<(), piet::Error> {
-        self.inner.save()
+        self.inner.with_context_mut(|inner| inner.save())
     }
 
     fn restore(&mut self) -> Result<(), piet::Error> {
-        self.inner.restore()
+        self.inner.with_context_mut(|inner| inner.restore())
     }
 
     fn finish(&mut self) -> Result<(), piet::Error> {
-        self.inner.finish()
+        self.inner.with_context_mut(|inner| inner.finish())
     }
 
     fn transform(&mut self, transform: kurbo::Affine) {
-        self.inner.transform(transform)
+        self.inner
+            .with_context_mut(|inner| inner.transform(transform))
     }
 
     fn make_image(
@@ -124,7 +184,7 @@ impl piet::RenderContext for RenderContext<'_, '_> {
         format: piet::ImageFormat,
     ) -> Result<Self::Image, piet::Error> {
         self.inner
-            .make_image(width, height, buf, format)
+            .with_context_mut(|inner| inner.make_image(width, height, buf, format))
             .map(|inner| Image { inner })
     }
 
@@ -134,7 +194,8 @@ impl piet::RenderContext for RenderContext<'_, '_> {
         dst_rect: impl Into<Rect>,
         interp: piet::InterpolationMode,
     ) {
-        self.inner.draw_image(&image.inner, dst_rect.into(), interp)
+        self.inner
+            .with_context_mut(|inner| inner.draw_image(&image.inner, dst_rect.into(), interp))
     }
 
     fn draw_image_area(
@@ -144,8 +205,9 @@ impl piet::RenderContext for RenderContext<'_, '_> {
         dst_rect: impl Into<Rect>,
         interp: piet::InterpolationMode,
     ) {
-        self.inner
-            .draw_image_area(&image.inner, src_rect.into(), dst_rect.into(), interp)
+        self.inner.with_context_mut(|inner| {
+            inner.draw_image_area(&image.inner, src_rect.into(), dst_rect.into(), interp)
+        })
     }
 
     fn capture_image_area(
@@ -153,17 +215,18 @@ impl piet::RenderContext for RenderContext<'_, '_> {
         src_rect: impl Into<Rect>,
     ) -> Result<Self::Image, piet::Error> {
         self.inner
-            .capture_image_area(src_rect.into())
+            .with_context_mut(|inner| inner.capture_image_area(src_rect.into()))
             .map(|inner| Image { inner })
     }
 
     fn blurred_rect(&mut self, rect: Rect, blur_radius: f64, brush: &impl piet::IntoBrush<Self>) {
         let brush = brush.make_brush(self, || rect);
-        self.inner.blurred_rect(rect, blur_radius, &brush.inner)
+        self.inner
+            .with_context_mut(|inner| inner.blurred_rect(rect, blur_radius, &brush.inner))
     }
 
     fn current_transform(&self) -> kurbo::Affine {
-        self.inner.current_transform()
+        self.inner.with_context(|inner| inner.current_transform())
     }
 }
 
@@ -297,10 +360,10 @@ pub struct Brush {
     inner: theo::Brush,
 }
 
-impl<'d, 's> piet::IntoBrush<RenderContext<'d, 's>> for Brush {
+impl piet::IntoBrush<RenderContext> for Brush {
     fn make_brush<'a>(
         &'a self,
-        _piet: &mut RenderContext<'d, 's>,
+        _piet: &mut RenderContext,
         _bbox: impl FnOnce() -> Rect,
     ) -> std::borrow::Cow<'a, <RenderContext as piet::RenderContext>::Brush> {
         std::borrow::Cow::Borrowed(self)
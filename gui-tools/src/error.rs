@@ -27,6 +27,7 @@ pub struct Error(Repr);
 enum Repr {
     OsError(async_winit::error::OsError),
     Piet(piet::Error),
+    Busy,
 }
 
 impl fmt::Display for Error {
@@ -34,6 +35,7 @@ impl fmt::Display for Error {
         match &self.0 {
             Repr::OsError(e) => write!(f, "OS error: {}", e),
             Repr::Piet(e) => write!(f, "Piet error: {}", e),
+            Repr::Busy => write!(f, "The display is already being drawn to"),
         }
     }
 }
@@ -55,6 +57,19 @@ impl Error {
     pub(crate) fn piet(e: piet::Error) -> Error {
         Error(Repr::Piet(e))
     }
+
+    pub(crate) fn busy() -> Error {
+        Error(Repr::Busy)
+    }
+
+    /// Whether this error was caused by the display already being drawn to.
+    ///
+    /// Drawing can be tried again once the other [`RenderContext`] has been dropped.
+    ///
+    /// [`RenderContext`]: crate::RenderContext
+    pub fn is_busy(&self) -> bool {
+        matches!(self.0, Repr::Busy)
+    }
 }
 
 impl From<piet::Error> for Error {
@@ -0,0 +1,179 @@
+/*
+
+`gui-tools` is free software: you can redistribute it and/or modify it under the terms of one of
+the following licenses:
+
+- The GNU Affero General Public License as published by the Free Software Foundation, either version
+  3 of the License, or (at your option) any later version.
+- The Patron License at https://github.com/notgull/gui-tools/blob/main/LICENSE-PATRON.md, for
+  sponsors and contributors, who can ignore the copyleft provisions of the GNU AGPL for this project.
+
+`gui-tools` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+Public License and the Patron License for more details.
+
+You should have received a copy of the GNU Affero General Public License and the corresponding Patron
+License along with `gui-tools`. If not, see <https://www.gnu.org/licenses/>.
+
+*/
+
+use crate::Point;
+
+/// A button on a mouse.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum MouseButton {
+    /// The left mouse button.
+    Left,
+
+    /// The right mouse button.
+    Right,
+
+    /// The middle mouse button.
+    Middle,
+
+    /// Some other mouse button.
+    Other(u16),
+}
+
+/// A mouse button being pressed or released.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MouseInput {
+    /// The button involved.
+    pub button: MouseButton,
+
+    /// Whether the button was pressed or released.
+    pub pressed: bool,
+}
+
+/// A key on the keyboard.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Key {
+    /// The tab key.
+    Tab,
+
+    /// The enter or return key.
+    Enter,
+
+    /// The escape key.
+    Escape,
+
+    /// The backspace key.
+    Backspace,
+
+    /// The delete key.
+    Delete,
+
+    /// The space bar.
+    Space,
+
+    /// The left arrow key.
+    Left,
+
+    /// The right arrow key.
+    Right,
+
+    /// The up arrow key.
+    Up,
+
+    /// The down arrow key.
+    Down,
+
+    /// The home key.
+    Home,
+
+    /// The end key.
+    End,
+
+    /// The page up key.
+    PageUp,
+
+    /// The page down key.
+    PageDown,
+
+    /// The insert key.
+    Insert,
+
+    /// A function key, numbered from 1.
+    Function(u8),
+
+    /// A letter or digit key, as the lowercase character printed on it.
+    Character(char),
+}
+
+/// A key being pressed or released.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeyInput {
+    /// The key involved, if it is known.
+    pub key: Option<Key>,
+
+    /// The platform-specific scan code of the physical key.
+    ///
+    /// Unlike `key`, this doesn't depend on the keyboard layout.
+    pub scancode: u32,
+
+    /// Whether the key was pressed or released.
+    pub pressed: bool,
+}
+
+/// The modifier keys that are held down.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Modifiers {
+    /// Whether shift is held down.
+    pub shift: bool,
+
+    /// Whether control is held down.
+    pub control: bool,
+
+    /// Whether alt is held down.
+    pub alt: bool,
+
+    /// Whether the logo key (Windows or Command) is held down.
+    pub logo: bool,
+}
+
+/// The distance that the mouse wheel or touchpad was scrolled.
+///
+/// Positive values scroll the content down and to the right, as when rolling the wheel towards
+/// the user. Platforms that use "natural" scrolling report the reverse.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScrollDelta {
+    /// The number of lines or rows to scroll by, from a mouse wheel.
+    Lines { x: f32, y: f32 },
+
+    /// The number of physical pixels to scroll by, from a touchpad.
+    Pixels { x: f64, y: f64 },
+}
+
+/// A finger touching the screen.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TouchInput {
+    /// An ID for this finger, which stays the same for as long as it touches the screen.
+    pub id: u64,
+
+    /// The stage of the touch.
+    pub phase: TouchPhase,
+
+    /// Where the finger is, in physical pixels.
+    pub position: Point,
+
+    /// How hard the finger is pressing, from 0 to 1, if the device can tell.
+    pub pressure: Option<f64>,
+}
+
+/// The stage of a touch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TouchPhase {
+    /// The finger touched the screen.
+    Started,
+
+    /// The finger moved.
+    Moved,
+
+    /// The finger was lifted from the screen.
+    Ended,
+
+    /// The system took the touch away, such as when it turned into a gesture.
+    Cancelled,
+}
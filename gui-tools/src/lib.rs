@@ -44,16 +44,22 @@ use std::rc::Rc;
 mod draw;
 mod error;
 mod handler;
+mod input;
 mod props;
+mod timer;
 mod window;
 
 pub use draw::{Brush, Image, RenderContext, Text, TextLayout, TextLayoutBuilder};
 pub use error::Error;
 pub use handler::{Event, Handler};
+pub use input::{
+    Key, KeyInput, Modifiers, MouseButton, MouseInput, ScrollDelta, TouchInput, TouchPhase,
+};
 pub use props::{
     Fullscreen, Icon, Monitor, Theme, VideoMode, WindowButtons, WindowLevel, WindowPosition,
     WindowSize,
 };
+pub use timer::{interval, now, sleep, Timer, VirtualClock};
 pub use window::{Window, WindowBuilder};
 
 // Use kurbo as a public dependency here, since piet is public as well.
@@ -0,0 +1,444 @@
+/*
+
+`gui-tools` is free software: you can redistribute it and/or modify it under the terms of one of
+the following licenses:
+
+- The GNU Affero General Public License as published by the Free Software Foundation, either version
+  3 of the License, or (at your option) any later version.
+- The Patron License at https://github.com/notgull/gui-tools/blob/main/LICENSE-PATRON.md, for
+  sponsors and contributors, who can ignore the copyleft provisions of the GNU AGPL for this project.
+
+`gui-tools` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+Public License and the Patron License for more details.
+
+You should have received a copy of the GNU Affero General Public License and the corresponding Patron
+License along with `gui-tools`. If not, see <https://www.gnu.org/licenses/>.
+
+*/
+
+//! Timers that wake up through the event loop.
+//!
+//! The event loop sleeps until the next timer is due, so timers don't need a reactor of their
+//! own. For tests, a [`VirtualClock`] can be installed on the thread, after which new timers only
+//! fire when the clock is moved forward by hand.
+
+use futures_lite::Stream;
+
+use std::cell::{Cell, RefCell};
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
+
+/// Wait for a duration to pass.
+pub fn sleep(duration: Duration) -> Timer {
+    Timer::after(duration)
+}
+
+/// Fire once every `period`, starting one period from now.
+pub fn interval(period: Duration) -> Timer {
+    Timer::interval(period)
+}
+
+/// A timer that fires at a point in time, and optionally repeats.
+///
+/// As a [`Future`], the timer resolves to the time that it fired at. As a [`Stream`], it yields
+/// that time every time it fires. A timer that doesn't repeat ends after firing once.
+pub struct Timer {
+    inner: TimerInner,
+}
+
+enum TimerInner {
+    /// A timer driven by the event loop.
+    Real(async_winit::Timer),
+
+    /// A timer driven by a virtual clock.
+    Virtual(VirtualTimer),
+}
+
+impl Timer {
+    /// Create a timer that fires once after a duration.
+    pub fn after(duration: Duration) -> Self {
+        Self::at(now() + duration)
+    }
+
+    /// Create a timer that fires once at a point in time.
+    pub fn at(deadline: Instant) -> Self {
+        Self::new(deadline, None, async_winit::Timer::at)
+    }
+
+    /// Create a timer that fires once every `period`, starting one period from now.
+    pub fn interval(period: Duration) -> Self {
+        Self::interval_at(now() + period, period)
+    }
+
+    /// Create a timer that fires at `start`, and then once every `period`.
+    pub fn interval_at(start: Instant, period: Duration) -> Self {
+        Self::new(start, Some(period), |start| {
+            async_winit::Timer::interval_at(start, period)
+        })
+    }
+
+    fn new(
+        deadline: Instant,
+        period: Option<Duration>,
+        real: impl FnOnce(Instant) -> async_winit::Timer,
+    ) -> Self {
+        let inner = match VirtualClock::current() {
+            Some(clock) => TimerInner::Virtual(VirtualTimer {
+                clock,
+                deadline: Some(deadline),
+                period,
+                id: None,
+            }),
+            None => TimerInner::Real(real(deadline)),
+        };
+
+        Self { inner }
+    }
+}
+
+impl Future for Timer {
+    type Output = Instant;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.as_mut().poll_next(cx) {
+            Poll::Ready(Some(fired)) => Poll::Ready(fired),
+
+            // A timer that has already fired never fires again.
+            Poll::Ready(None) | Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl Stream for Timer {
+    type Item = Instant;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match &mut self.inner {
+            TimerInner::Real(timer) => Pin::new(timer).poll_next(cx),
+            TimerInner::Virtual(timer) => timer.poll_next(cx),
+        }
+    }
+}
+
+/// Get the current time, according to the thread's clock.
+///
+/// This is the time on the [`VirtualClock`] if one is installed.
+pub fn now() -> Instant {
+    match VirtualClock::current() {
+        Some(clock) => clock.now.get(),
+        None => Instant::now(),
+    }
+}
+
+std::thread_local! {
+    static VIRTUAL_CLOCK: RefCell<Option<Rc<ClockState>>> = const { RefCell::new(None) };
+}
+
+/// A clock that only moves when told to, for testing code that uses timers.
+///
+/// While the clock is installed on a thread, timers created on that thread are driven by it
+/// instead of the event loop. Dropping the clock goes back to real time, although timers created
+/// with it keep using it.
+pub struct VirtualClock {
+    state: Rc<ClockState>,
+
+    /// The clock that was installed before this one.
+    previous: Option<Rc<ClockState>>,
+}
+
+struct ClockState {
+    /// The current time on the clock.
+    now: Cell<Instant>,
+
+    /// The timers waiting for the clock to reach their deadlines.
+    waiting: RefCell<Vec<Waiting>>,
+
+    /// The ID to give to the next waiting timer.
+    next_id: Cell<u64>,
+}
+
+/// A timer waiting for a virtual clock.
+struct Waiting {
+    id: u64,
+    deadline: Instant,
+    waker: Waker,
+}
+
+impl VirtualClock {
+    /// Install a new virtual clock on this thread, starting at the current time.
+    pub fn install() -> Self {
+        let state = Rc::new(ClockState {
+            now: Cell::new(Instant::now()),
+            waiting: RefCell::new(Vec::new()),
+            next_id: Cell::new(0),
+        });
+        let previous = VIRTUAL_CLOCK.with(|clock| clock.borrow_mut().replace(state.clone()));
+
+        Self { state, previous }
+    }
+
+    /// Get the current time on the clock.
+    pub fn now(&self) -> Instant {
+        self.state.now.get()
+    }
+
+    /// Move the clock forward, waking up every timer that is now due.
+    pub fn advance(&self, duration: Duration) {
+        let now = self.state.now.get() + duration;
+        self.state.now.set(now);
+
+        // Take the wakers out first, in case waking a timer creates another one.
+        let mut due = {
+            let mut waiting = self.state.waiting.borrow_mut();
+            let (due, pending): (Vec<_>, Vec<_>) =
+                waiting.drain(..).partition(|timer| timer.deadline <= now);
+            *waiting = pending;
+            due
+        };
+
+        // Wake timers up in the order they were due.
+        due.sort_by_key(|timer| timer.deadline);
+
+        for timer in due {
+            timer.waker.wake();
+        }
+    }
+
+    /// Get the clock installed on this thread.
+    fn current() -> Option<Rc<ClockState>> {
+        VIRTUAL_CLOCK.with(|clock| clock.borrow().clone())
+    }
+}
+
+impl Drop for VirtualClock {
+    fn drop(&mut self) {
+        VIRTUAL_CLOCK.with(|clock| *clock.borrow_mut() = self.previous.take());
+    }
+}
+
+/// A timer driven by a virtual clock.
+struct VirtualTimer {
+    clock: Rc<ClockState>,
+
+    /// When the timer fires next, or `None` if it has finished.
+    deadline: Option<Instant>,
+
+    /// How often the timer repeats.
+    period: Option<Duration>,
+
+    /// The ID of this timer's entry in the clock's waiting list.
+    id: Option<u64>,
+}
+
+impl VirtualTimer {
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Instant>> {
+        let deadline = match self.deadline {
+            Some(deadline) => deadline,
+            None => return Poll::Ready(None),
+        };
+
+        if self.clock.now.get() >= deadline {
+            self.deregister();
+            self.deadline = self.period.map(|period| deadline + period);
+            return Poll::Ready(Some(deadline));
+        }
+
+        // Wait for the clock to reach the deadline.
+        let mut waiting = self.clock.waiting.borrow_mut();
+        match self
+            .id
+            .and_then(|id| waiting.iter_mut().find(|timer| timer.id == id))
+        {
+            Some(timer) => {
+                if !timer.waker.will_wake(cx.waker()) {
+                    timer.waker = cx.waker().clone();
+                }
+            }
+
+            None => {
+                let id = self.clock.next_id.get();
+                self.clock.next_id.set(id + 1);
+                self.id = Some(id);
+
+                waiting.push(Waiting {
+                    id,
+                    deadline,
+                    waker: cx.waker().clone(),
+                });
+            }
+        }
+
+        Poll::Pending
+    }
+
+    /// Remove this timer from the clock's waiting list.
+    fn deregister(&mut self) {
+        if let Some(id) = self.id.take() {
+            self.clock
+                .waiting
+                .borrow_mut()
+                .retain(|timer| timer.id != id);
+        }
+    }
+}
+
+impl Drop for VirtualTimer {
+    fn drop(&mut self) {
+        self.deregister();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_lite::future;
+    use futures_lite::prelude::*;
+
+    use std::sync::{Arc, Mutex};
+    use std::task::Wake;
+
+    /// Records the name of each timer as it is woken up.
+    struct Record {
+        name: &'static str,
+        woken: Arc<Mutex<Vec<&'static str>>>,
+    }
+
+    impl Wake for Record {
+        fn wake(self: Arc<Self>) {
+            self.woken.lock().unwrap().push(self.name);
+        }
+    }
+
+    /// Poll a timer once, with a waker that records its name.
+    fn poll(
+        timer: &mut Timer,
+        name: &'static str,
+        woken: &Arc<Mutex<Vec<&'static str>>>,
+    ) -> Poll<Option<Instant>> {
+        let waker = Waker::from(Arc::new(Record {
+            name,
+            woken: woken.clone(),
+        }));
+        Pin::new(timer).poll_next(&mut Context::from_waker(&waker))
+    }
+
+    #[test]
+    fn sleep_waits_for_clock() {
+        let clock = VirtualClock::install();
+        let start = clock.now();
+        let mut timer = sleep(Duration::from_millis(10));
+
+        assert_eq!(future::block_on(future::poll_once(&mut timer)), None);
+        clock.advance(Duration::from_millis(9));
+        assert_eq!(future::block_on(future::poll_once(&mut timer)), None);
+        clock.advance(Duration::from_millis(1));
+        assert_eq!(
+            future::block_on(future::poll_once(&mut timer)),
+            Some(start + Duration::from_millis(10))
+        );
+
+        // A timer that has fired doesn't fire again.
+        assert_eq!(future::block_on(timer.next()), None);
+    }
+
+    #[test]
+    fn timers_wake_in_deadline_order() {
+        let clock = VirtualClock::install();
+        let start = clock.now();
+        let woken = Arc::new(Mutex::new(Vec::new()));
+
+        let mut late = Timer::at(start + Duration::from_millis(30));
+        let mut early = Timer::at(start + Duration::from_millis(10));
+        let mut middle = sleep(Duration::from_millis(20));
+        assert!(poll(&mut late, "late", &woken).is_pending());
+        assert!(poll(&mut early, "early", &woken).is_pending());
+        assert!(poll(&mut middle, "middle", &woken).is_pending());
+
+        clock.advance(Duration::from_millis(15));
+        assert_eq!(*woken.lock().unwrap(), ["early"]);
+
+        clock.advance(Duration::from_millis(100));
+        assert_eq!(*woken.lock().unwrap(), ["early", "middle", "late"]);
+
+        assert_eq!(
+            poll(&mut late, "late", &woken),
+            Poll::Ready(Some(start + Duration::from_millis(30)))
+        );
+    }
+
+    #[test]
+    fn timer_in_the_past_fires_immediately() {
+        let clock = VirtualClock::install();
+        clock.advance(Duration::from_secs(1));
+        let deadline = clock.now() - Duration::from_millis(500);
+
+        let mut timer = Timer::at(deadline);
+        assert_eq!(
+            future::block_on(future::poll_once(&mut timer)),
+            Some(deadline)
+        );
+    }
+
+    #[test]
+    fn interval_does_not_drift() {
+        let clock = VirtualClock::install();
+        let start = clock.now();
+        let period = Duration::from_millis(10);
+        let mut timer = interval(period);
+
+        // Firing late doesn't push back the ticks after it.
+        for tick in 1..=3 {
+            clock.advance(Duration::from_millis(13));
+            let fired = future::block_on(future::poll_once(timer.next()));
+            assert_eq!(fired, Some(Some(start + period * tick)));
+        }
+
+        // The clock is now at 39ms, so the tick at 40ms hasn't happened yet.
+        assert_eq!(future::block_on(future::poll_once(timer.next())), None);
+    }
+
+    #[test]
+    fn interval_catches_up() {
+        let clock = VirtualClock::install();
+        let start = clock.now();
+        let period = Duration::from_millis(10);
+        let woken = Arc::new(Mutex::new(Vec::new()));
+        let mut timer = interval(period);
+
+        assert!(poll(&mut timer, "interval", &woken).is_pending());
+        clock.advance(Duration::from_millis(35));
+        assert_eq!(*woken.lock().unwrap(), ["interval"]);
+
+        // Every tick that was missed fires straight away, in order.
+        for tick in 1..=3 {
+            assert_eq!(
+                poll(&mut timer, "interval", &woken),
+                Poll::Ready(Some(start + period * tick))
+            );
+        }
+        assert!(poll(&mut timer, "interval", &woken).is_pending());
+
+        clock.advance(Duration::from_millis(5));
+        assert_eq!(
+            poll(&mut timer, "interval", &woken),
+            Poll::Ready(Some(start + period * 4))
+        );
+    }
+
+    #[test]
+    fn dropped_clock_restores_previous() {
+        let outer = VirtualClock::install();
+        outer.advance(Duration::from_secs(1));
+
+        {
+            let inner = VirtualClock::install();
+            assert_eq!(now(), inner.now());
+        }
+
+        assert_eq!(now(), outer.now());
+    }
+}
@@ -18,10 +18,13 @@ License along with `gui-tools`. If not, see <https://www.gnu.org/licenses/>.
 */
 
 use super::{
-    cvt_position, cvt_size, DisplayInner, DrawState, Error, Fullscreen, Handler, Icon,
-    RenderContext, Theme, WindowButtons, WindowLevel, WindowPosition, WindowSize,
+    cvt_position, cvt_size, DisplayInner, DrawState, Error, Fullscreen, Handler, Icon, Key,
+    KeyInput, Modifiers, MouseButton, MouseInput, Point, Rect, RenderContext, ScrollDelta, Size,
+    Theme, TouchInput, TouchPhase, WindowButtons, WindowLevel, WindowPosition, WindowSize,
 };
+use async_winit::event::ElementState;
 use async_winit::window::{Window as WinitWindow, WindowBuilder as WinitWindowBuilder};
+use piet::RenderContext as _;
 
 use std::cell::RefCell;
 use std::mem;
@@ -31,12 +34,12 @@ use std::rc::Rc;
 #[derive(Clone)]
 pub struct Window(Rc<WindowState>);
 
-struct WindowState {
+pub(crate) struct WindowState {
     /// Handle involving the windowing system.
     inner: WinitWindow,
 
     /// Surface for drawing.
-    surface: RefCell<theo::Surface>,
+    pub(crate) surface: RefCell<theo::Surface>,
 }
 
 impl Window {
@@ -334,24 +337,223 @@ impl Window {
         Handler::new(self.0.inner.redraw_requested())
     }
 
+    /// Wait for the window to gain or lose focus, returning whether it has focus now.
+    pub async fn focus_changed(&self) -> bool {
+        self.0.inner.focused().await
+    }
+
+    /// Wait for the cursor to move, returning its new position in physical pixels.
+    pub async fn cursor_moved(&self) -> Point {
+        let moved = self.0.inner.cursor_moved().await;
+        Point::new(moved.position.x, moved.position.y)
+    }
+
+    /// Wait for the cursor to enter the window.
+    pub async fn cursor_entered(&self) {
+        self.0.inner.cursor_entered().await;
+    }
+
+    /// Wait for the cursor to leave the window.
+    pub async fn cursor_left(&self) {
+        self.0.inner.cursor_left().await;
+    }
+
+    /// Wait for a mouse button to be pressed or released.
+    pub async fn mouse_input(&self) -> MouseInput {
+        use async_winit::event::MouseButton as Mb;
+
+        let input = self.0.inner.mouse_input().await;
+        MouseInput {
+            button: match input.button {
+                Mb::Left => MouseButton::Left,
+                Mb::Right => MouseButton::Right,
+                Mb::Middle => MouseButton::Middle,
+                Mb::Other(other) => MouseButton::Other(other),
+            },
+            pressed: input.state == ElementState::Pressed,
+        }
+    }
+
+    /// Wait for the mouse wheel or touchpad to scroll.
+    pub async fn mouse_wheel(&self) -> ScrollDelta {
+        use async_winit::event::MouseScrollDelta as Msd;
+
+        match self.0.inner.mouse_wheel().await.delta {
+            // winit reports the wheel rolling towards the user as a negative delta.
+            Msd::LineDelta(x, y) => ScrollDelta::Lines { x: -x, y: -y },
+            Msd::PixelDelta(delta) => ScrollDelta::Pixels {
+                x: -delta.x,
+                y: -delta.y,
+            },
+        }
+    }
+
+    /// Wait for a key to be pressed or released while the window has focus.
+    pub async fn keyboard_input(&self) -> KeyInput {
+        use async_winit::event::VirtualKeyCode as Vk;
+
+        let input = self.0.inner.keyboard_input().await.input;
+        let key = input.virtual_keycode.and_then(|code| {
+            Some(match code {
+                Vk::Tab => Key::Tab,
+                Vk::Return | Vk::NumpadEnter => Key::Enter,
+                Vk::Escape => Key::Escape,
+                Vk::Back => Key::Backspace,
+                Vk::Delete => Key::Delete,
+                Vk::Space => Key::Space,
+                Vk::Left => Key::Left,
+                Vk::Right => Key::Right,
+                Vk::Up => Key::Up,
+                Vk::Down => Key::Down,
+                Vk::Home => Key::Home,
+                Vk::End => Key::End,
+                Vk::PageUp => Key::PageUp,
+                Vk::PageDown => Key::PageDown,
+                Vk::Insert => Key::Insert,
+                Vk::F1 => Key::Function(1),
+                Vk::F2 => Key::Function(2),
+                Vk::F3 => Key::Function(3),
+                Vk::F4 => Key::Function(4),
+                Vk::F5 => Key::Function(5),
+                Vk::F6 => Key::Function(6),
+                Vk::F7 => Key::Function(7),
+                Vk::F8 => Key::Function(8),
+                Vk::F9 => Key::Function(9),
+                Vk::F10 => Key::Function(10),
+                Vk::F11 => Key::Function(11),
+                Vk::F12 => Key::Function(12),
+                Vk::A => Key::Character('a'),
+                Vk::B => Key::Character('b'),
+                Vk::C => Key::Character('c'),
+                Vk::D => Key::Character('d'),
+                Vk::E => Key::Character('e'),
+                Vk::F => Key::Character('f'),
+                Vk::G => Key::Character('g'),
+                Vk::H => Key::Character('h'),
+                Vk::I => Key::Character('i'),
+                Vk::J => Key::Character('j'),
+                Vk::K => Key::Character('k'),
+                Vk::L => Key::Character('l'),
+                Vk::M => Key::Character('m'),
+                Vk::N => Key::Character('n'),
+                Vk::O => Key::Character('o'),
+                Vk::P => Key::Character('p'),
+                Vk::Q => Key::Character('q'),
+                Vk::R => Key::Character('r'),
+                Vk::S => Key::Character('s'),
+                Vk::T => Key::Character('t'),
+                Vk::U => Key::Character('u'),
+                Vk::V => Key::Character('v'),
+                Vk::W => Key::Character('w'),
+                Vk::X => Key::Character('x'),
+                Vk::Y => Key::Character('y'),
+                Vk::Z => Key::Character('z'),
+                Vk::Key0 | Vk::Numpad0 => Key::Character('0'),
+                Vk::Key1 | Vk::Numpad1 => Key::Character('1'),
+                Vk::Key2 | Vk::Numpad2 => Key::Character('2'),
+                Vk::Key3 | Vk::Numpad3 => Key::Character('3'),
+                Vk::Key4 | Vk::Numpad4 => Key::Character('4'),
+                Vk::Key5 | Vk::Numpad5 => Key::Character('5'),
+                Vk::Key6 | Vk::Numpad6 => Key::Character('6'),
+                Vk::Key7 | Vk::Numpad7 => Key::Character('7'),
+                Vk::Key8 | Vk::Numpad8 => Key::Character('8'),
+                Vk::Key9 | Vk::Numpad9 => Key::Character('9'),
+                _ => return None,
+            })
+        });
+
+        KeyInput {
+            key,
+            scancode: input.scancode,
+            pressed: input.state == ElementState::Pressed,
+        }
+    }
+
+    /// Wait for a character to be typed into the window.
+    ///
+    /// This takes the keyboard layout and dead keys into account, so it should be used for text
+    /// entry instead of [`Window::keyboard_input`]. Some platforms also report control characters
+    /// here, such as a backspace.
+    pub async fn received_character(&self) -> char {
+        self.0.inner.received_character().await
+    }
+
+    /// Wait for a finger to touch, move on or leave the screen.
+    pub async fn touch_input(&self) -> TouchInput {
+        use async_winit::event::TouchPhase as Tp;
+
+        let touch = self.0.inner.touch().await;
+        TouchInput {
+            id: touch.id,
+            phase: match touch.phase {
+                Tp::Started => TouchPhase::Started,
+                Tp::Moved => TouchPhase::Moved,
+                Tp::Ended => TouchPhase::Ended,
+                Tp::Cancelled => TouchPhase::Cancelled,
+            },
+            position: Point::new(touch.location.x, touch.location.y),
+            pressure: touch.force.map(|force| force.normalized()),
+        }
+    }
+
+    /// Wait for the modifier keys held down to change, returning the new state.
+    pub async fn modifiers_changed(&self) -> Modifiers {
+        let state = self.0.inner.modifiers_changed().await;
+
+        Modifiers {
+            shift: state.shift(),
+            control: state.ctrl(),
+            alt: state.alt(),
+            logo: state.logo(),
+        }
+    }
+
+    /// Get the size of the window's drawing area, in physical pixels.
+    pub async fn inner_size(&self) -> Size {
+        let size = self.0.inner.inner_size().await;
+        Size::new(size.width as f64, size.height as f64)
+    }
+
+    /// Get the number of physical pixels per logical pixel for this window.
+    pub async fn scale_factor(&self) -> f64 {
+        self.0.inner.scale_factor().await
+    }
+
+    /// Ask the windowing system to redraw this window.
+    pub async fn request_redraw(&self) {
+        self.0.inner.request_redraw().await;
+    }
+
+    /// Begin drawing to the window.
+    ///
+    /// The display stays borrowed until the returned context is dropped. While it is, trying to
+    /// draw to any other window fails with an error where [`Error::is_busy`] is true.
+    pub fn begin_draw(&self, width: u32, height: u32) -> Result<RenderContext, Error> {
+        RenderContext::new(DisplayInner::get(), self.0.clone(), width, height)
+    }
+
+    /// Begin drawing to part of the window.
+    ///
+    /// Drawing is clipped to `damage`, in physical pixels, so the rest of the window keeps what
+    /// was drawn in earlier frames.
+    pub fn begin_draw_damaged(
+        &self,
+        width: u32,
+        height: u32,
+        damage: Rect,
+    ) -> Result<RenderContext, Error> {
+        let mut rc = self.begin_draw(width, height)?;
+        rc.clip(damage);
+        Ok(rc)
+    }
+
     /// Run a closure with a rendering context.
     pub async fn draw<R>(
         &self,
-        f: impl FnOnce(&mut RenderContext<'_, '_>) -> Result<R, Error>,
+        f: impl FnOnce(&mut RenderContext) -> Result<R, Error>,
     ) -> Result<R, Error> {
         let inner_size = self.0.inner.inner_size().await;
-        let display = DisplayInner::get();
-        let mut draw = display.draw.borrow_mut();
-        let mut surface = self.0.surface.borrow_mut();
-        let display = match &mut *draw {
-            DrawState::Ready(display) => display,
-            _ => unreachable!("cannot poll an empty hole"),
-        };
-
-        let rc =
-            theo::RenderContext::new(display, &mut surface, inner_size.width, inner_size.height)?;
-        let ret = f(&mut RenderContext::new(rc))?;
-
-        Ok(ret)
+        let mut rc = self.begin_draw(inner_size.width, inner_size.height)?;
+        f(&mut rc)
     }
 }
@@ -17,4 +17,1144 @@ License along with `async-gui`. If not, see <https://www.gnu.org/licenses/>.
 
 */
 
-use gui_tools::{Display, DisplayBuilder};
+//! Draw `async-gui` widgets into native windows provided by `gui-tools`.
+
+use async_gui::{DrawParameters, Drawable, Focus, Listener, PointerCapture, System};
+use futures_lite::{future, prelude::*, stream};
+use gui_tools::piet::kurbo::{Affine, Rect};
+use gui_tools::piet::RenderContext as _;
+use gui_tools::{piet, MouseButton, Point, RenderContext, ScrollDelta, Timer, TouchPhase, Window};
+use sunder::gesture::{ClickCounter, GestureSettings};
+use sunder::piet::{ui_theme::Theme, PietBackend};
+use sunder::{
+    BoxConstraints, Damage, Event, Key, KeyEvent, Modifiers, PointerButton, PointerButtons,
+    PointerEvent, PointerKind, Rectangle, Size,
+};
+
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::pin::Pin;
+use std::rc::{Rc, Weak};
+use std::time::Instant;
+
+/// The number of logical pixels scrolled by each line of a mouse wheel.
+const LINE_HEIGHT: f64 = 40.0;
+
+type BackResult = Result<(), piet::Error>;
+
+/// A drawing context for a native window.
+///
+/// Every widget drawn into this context is drawn into a single frame. A frame is started when
+/// the window asks to be redrawn, and finished once every widget woken up for it has drawn.
+/// Overlays, such as dialogs, are drawn again whenever something is drawn over them during a
+/// frame, so that they stay on top.
+pub struct Context {
+    /// The window being drawn to.
+    window: Window,
+
+    /// The theme to draw widgets with.
+    theme: Rc<Theme>,
+
+    /// The frame currently being drawn.
+    frame: RefCell<Option<PietBackend<RenderContext>>>,
+
+    /// The size of the current frame, in logical pixels.
+    size: Cell<Size>,
+
+    /// The number of physical pixels per logical pixel.
+    scale: Rc<Cell<f64>>,
+
+    /// The input shared between every listener on the window.
+    input: Rc<WindowInput>,
+
+    /// Whether the frame is about to be finished.
+    finishing: Cell<bool>,
+
+    /// The number of frames that have been started.
+    frames: Cell<u64>,
+
+    /// The number of children waiting for the next frame.
+    waiting: Cell<usize>,
+
+    /// The number of children woken up for the current frame that haven't drawn yet.
+    pending_draws: Cell<usize>,
+
+    /// Notified when a frame is started.
+    began: event_listener::Event,
+
+    /// Notified when a child has drawn during the current frame.
+    drawn: event_listener::Event,
+
+    /// The area that each child covered when it was last drawn, in logical pixels.
+    regions: RefCell<HashMap<usize, Rectangle>>,
+
+    /// Whether something tried to draw outside of a frame.
+    dirty: Cell<bool>,
+
+    /// The area that needs to be repainted in the next frame, in logical pixels.
+    damage: Cell<Damage>,
+
+    /// The area being repainted in the current frame, or `None` if it covers the whole window.
+    frame_damage: Cell<Option<Rectangle>>,
+
+    /// The time that the current frame started at.
+    frame_time: Cell<Instant>,
+
+    /// Notified when something tries to draw outside of a frame.
+    invalidated: event_listener::Event,
+
+    /// Whether the context has been closed.
+    closed: Cell<bool>,
+
+    /// Notified when the context is closed.
+    close: event_listener::Event,
+
+    /// An error that occurred outside of a call to `draw`.
+    error: Cell<Option<piet::Error>>,
+
+    /// The number of children added to this context.
+    children: Cell<usize>,
+
+    /// The keys of the children that are drawn on top of the others.
+    overlays: RefCell<Vec<usize>>,
+
+    /// Whether an overlay has been drawn during the current frame.
+    overlay_drawn: Cell<bool>,
+
+    /// Whether another child has been drawn over an overlay during the current frame.
+    overlay_stale: Cell<bool>,
+
+    /// Notified when overlays need to be drawn again.
+    restack: event_listener::Event,
+}
+
+impl Context {
+    /// Create a new context for drawing into a window.
+    pub fn new(window: &Window) -> Self {
+        Self::with_theme(window, Rc::new(Theme::default()))
+    }
+
+    /// Create a new context for drawing into a window with a specific theme.
+    pub fn with_theme(window: &Window, theme: Rc<Theme>) -> Self {
+        let scale = Rc::new(Cell::new(1.0));
+        let input = Rc::new(WindowInput {
+            inputs: RefCell::new(inputs(window)),
+            scale: scale.clone(),
+            focus: Focus::new(),
+            queues: RefCell::new(Vec::new()),
+            sent: event_listener::Event::new(),
+            reading: Cell::new(false),
+            position: Cell::new(Point::ZERO),
+            buttons: Cell::new(PointerButtons::new()),
+            modifiers: Cell::new(gui_tools::Modifiers::default()),
+            held: RefCell::new(HashSet::new()),
+            touches: RefCell::new(HashMap::new()),
+            capture: PointerCapture::new(),
+            pending: RefCell::new(VecDeque::new()),
+            gestures: Cell::new(GestureSettings::default()),
+            clicks: RefCell::new(ClickCounter::new()),
+            epoch: gui_tools::now(),
+            long_press: Cell::new(None),
+            hover: Cell::new(None),
+        });
+
+        Self {
+            window: window.clone(),
+            theme,
+            frame: RefCell::new(None),
+            size: Cell::new(Size::default()),
+            scale,
+            input,
+            finishing: Cell::new(false),
+            frames: Cell::new(0),
+            waiting: Cell::new(0),
+            pending_draws: Cell::new(0),
+            began: event_listener::Event::new(),
+            drawn: event_listener::Event::new(),
+            regions: RefCell::new(HashMap::new()),
+            dirty: Cell::new(false),
+            damage: Cell::new(Damage::None),
+            frame_damage: Cell::new(None),
+            frame_time: Cell::new(gui_tools::now()),
+            invalidated: event_listener::Event::new(),
+            closed: Cell::new(false),
+            close: event_listener::Event::new(),
+            error: Cell::new(None),
+            children: Cell::new(0),
+            overlays: RefCell::new(Vec::new()),
+            overlay_drawn: Cell::new(false),
+            overlay_stale: Cell::new(false),
+            restack: event_listener::Event::new(),
+        }
+    }
+
+    /// Get the window being drawn to.
+    pub fn window(&self) -> &Window {
+        &self.window
+    }
+
+    /// Close the context.
+    ///
+    /// Every widget drawing into the context stops. The context is also closed once the window is
+    /// asked to close.
+    pub fn close(&self) {
+        self.closed.set(true);
+        self.close.notify(usize::MAX);
+    }
+
+    /// Get the settings used to recognize gestures, such as double clicks and long presses.
+    pub fn gesture_settings(&self) -> GestureSettings {
+        self.input.gestures.get()
+    }
+
+    /// Change the settings used to recognize gestures, such as double clicks and long presses.
+    ///
+    /// By default, the settings are the defaults of the platform.
+    pub fn set_gesture_settings(&self, settings: GestureSettings) {
+        self.input.gestures.set(settings);
+    }
+
+    /// Whether the context has been closed.
+    pub fn is_closed(&self) -> bool {
+        self.closed.get()
+    }
+
+    /// Ask for another frame to be drawn, repainting the whole window.
+    fn invalidate(&self) {
+        self.damage(Damage::Full);
+    }
+
+    /// Ask for another frame to be drawn, repainting at least the given area.
+    fn damage(&self, damage: Damage) {
+        self.damage.set(self.damage.get().union(damage));
+        self.dirty.set(true);
+        self.invalidated.notify(usize::MAX);
+    }
+
+    /// Start a new frame, if one isn't already being drawn.
+    fn begin_frame(&self, size: gui_tools::Size, scale: f64) {
+        let mut frame = self.frame.borrow_mut();
+        if frame.is_some() {
+            return;
+        }
+
+        let logical_size = Size {
+            width: (size.width / scale) as u32,
+            height: (size.height / scale) as u32,
+        };
+
+        // Only repaint the damaged area, unless the window has changed since the last frame. If
+        // nothing was damaged, nothing is repainted.
+        let resized = logical_size != self.size.get() || scale != self.scale.get();
+        let damage = match self.damage.take() {
+            Damage::Full => None,
+            _ if resized => None,
+            Damage::Region(region) => Some(region),
+            Damage::None => Some(Rectangle::default()),
+        };
+
+        let result = match damage {
+            Some(region) => self.window.begin_draw_damaged(
+                size.width as u32,
+                size.height as u32,
+                physical_rect(region, scale),
+            ),
+            None => self
+                .window
+                .begin_draw(size.width as u32, size.height as u32),
+        };
+
+        match result {
+            Ok(context) => {
+                let mut backend = PietBackend::new(context, self.theme.clone());
+                backend.context().clear(
+                    damage.map(|region| physical_rect(region, scale)),
+                    piet::Color::WHITE,
+                );
+
+                // Widgets draw in logical pixels.
+                backend.context().transform(Affine::scale(scale));
+
+                self.size.set(logical_size);
+                self.scale.set(scale);
+                self.frame_damage.set(damage);
+                self.frame_time.set(gui_tools::now());
+                self.overlay_drawn.set(false);
+                self.overlay_stale.set(false);
+                *frame = Some(backend);
+
+                // Every child waiting for this frame is woken up to draw into it.
+                self.frames.set(self.frames.get() + 1);
+                self.pending_draws.set(self.waiting.get());
+                self.began.notify(usize::MAX);
+            }
+
+            // Another window is being drawn to; try again on the next redraw.
+            Err(err) if err.is_busy() => self.invalidate(),
+
+            Err(err) => self
+                .error
+                .set(Some(piet::Error::BackendError(Box::new(err)))),
+        }
+    }
+
+    /// Finish the current frame.
+    fn finish_frame(&self) {
+        if let Some(mut backend) = self.frame.borrow_mut().take() {
+            if let Err(err) = backend.context().finish() {
+                self.error.set(Some(err));
+            }
+        }
+
+        self.finishing.set(false);
+    }
+
+    /// Wait for the next frame.
+    ///
+    /// Overlays also wake up to draw themselves again when something is drawn over them.
+    fn redraw_requested(&self, overlay: bool) -> RedrawRequested<'_> {
+        Box::pin(async move {
+            // The first widget to finish drawing finishes the frame, once every other widget woken
+            // up for it has drawn.
+            if self.frame.borrow().is_some() && !self.finishing.replace(true) {
+                let guard = FinishFrame(self);
+                self.wait_for_draws(|| self.pending_draws.get() == 0).await;
+
+                // Overlays have to be drawn again if something was drawn over them.
+                if overlay && self.overlay_stale.get() {
+                    // Let this overlay draw, and finish the frame afterwards.
+                    std::mem::forget(guard);
+                    self.finishing.set(false);
+                    return;
+                }
+
+                self.wait_for_draws(|| !self.overlay_stale.get()).await;
+            }
+
+            // Wake up for the next frame, even if another widget started it.
+            let waiting = Waiting::new(self);
+            let began = async {
+                loop {
+                    let listener = self.began.listen();
+
+                    if self.frames.get() > waiting.frame {
+                        return;
+                    }
+
+                    listener.await;
+                }
+            };
+
+            let restack = async {
+                if !overlay {
+                    return future::pending().await;
+                }
+
+                loop {
+                    let listener = self.restack.listen();
+
+                    if self.overlay_stale.get() {
+                        return;
+                    }
+
+                    listener.await;
+                }
+            };
+
+            self.next_frame().or(began).or(restack).await
+        })
+    }
+
+    /// Wait until a condition holds, checking it again whenever a child draws.
+    async fn wait_for_draws(&self, done: impl Fn() -> bool) {
+        loop {
+            let listener = self.drawn.listen();
+
+            if done() {
+                return;
+            }
+
+            listener.await;
+        }
+    }
+
+    /// Wait for the window to be redrawn, and start a new frame.
+    async fn next_frame(&self) {
+        // Ask the window for a redraw whenever something is invalidated in the meantime.
+        let request = async {
+            loop {
+                let listener = self.invalidated.listen();
+
+                if self.dirty.replace(false) {
+                    self.window.request_redraw().await;
+                } else {
+                    listener.await;
+                }
+            }
+        };
+
+        self.window.redraw_requested().or(request).await;
+        let size = self.window.inner_size().await;
+        let scale = self.window.scale_factor().await;
+        self.begin_frame(size, scale);
+    }
+}
+
+/// Future that waits for the next frame of a [`Context`].
+pub type RedrawRequested<'a> = Pin<Box<dyn Future<Output = ()> + 'a>>;
+
+/// Future that waits for a [`Context`] to be closed.
+pub type Closed<'a> = Pin<Box<dyn Future<Output = ()> + 'a>>;
+
+impl System for Context {
+    type Backend = PietBackend<RenderContext>;
+    type Listener = WindowListener;
+    type RedrawRequested<'a> = RedrawRequested<'a>;
+    type Closed<'a> = Closed<'a>;
+
+    fn add_child(&self) -> usize {
+        let key = self.children.get();
+        self.children.set(key + 1);
+        key
+    }
+
+    fn add_overlay(&self) -> usize {
+        let key = self.add_child();
+        self.overlays.borrow_mut().push(key);
+        key
+    }
+
+    fn remove_child(&self, child: usize) {
+        let mut overlays = self.overlays.borrow_mut();
+        overlays.retain(|&overlay| overlay != child);
+
+        // Don't wait for an overlay that is gone.
+        if overlays.is_empty() {
+            self.overlay_stale.set(false);
+            self.drawn.notify(usize::MAX);
+        }
+
+        // Repaint the area that the child used to cover.
+        if let Some(region) = self.regions.borrow_mut().remove(&child) {
+            self.damage(Damage::Region(region));
+        }
+    }
+
+    fn closed(&self) -> Self::Closed<'_> {
+        Box::pin(async move {
+            let close_requested = async {
+                self.window.close_requested().await;
+                self.close();
+            };
+
+            let closed = async {
+                loop {
+                    let listener = self.close.listen();
+
+                    if self.closed.get() {
+                        return;
+                    }
+
+                    listener.await;
+                }
+            };
+
+            closed.or(close_requested).await
+        })
+    }
+
+    fn listen(&self) -> Self::Listener {
+        let queue = Rc::new(RefCell::new(VecDeque::new()));
+        self.input.queues.borrow_mut().push(Rc::downgrade(&queue));
+
+        WindowListener {
+            input: self.input.clone(),
+            queue,
+        }
+    }
+
+    fn focus(&self) -> &Focus {
+        &self.input.focus
+    }
+
+    fn pointer_capture(&self) -> &PointerCapture {
+        &self.input.capture
+    }
+
+    fn invalidate(&self, region: Rectangle) {
+        self.damage(Damage::Region(region));
+    }
+
+    fn request_frame(&self) -> Self::RedrawRequested<'_> {
+        // Ask for a frame without anything to repaint.
+        self.damage(Damage::None);
+        self.redraw_requested(false)
+    }
+
+    fn frame_time(&self) -> Instant {
+        self.frame_time.get()
+    }
+
+    fn draw(
+        &self,
+        child: usize,
+        mut item: impl Drawable<Self::Backend>,
+    ) -> (BackResult, Self::RedrawRequested<'_>) {
+        let overlay = self.overlays.borrow().contains(&child);
+
+        let result = (|| {
+            if let Some(err) = self.error.take() {
+                return Err(err);
+            }
+
+            let mut frame = self.frame.borrow_mut();
+            let backend = match &mut *frame {
+                Some(backend) => backend,
+                None => {
+                    // Draw this item during the next frame instead, repainting the area it covered.
+                    // A child that hasn't been drawn yet could cover any part of the window.
+                    match self.regions.borrow().get(&child) {
+                        Some(&region) => self.damage(Damage::Region(region)),
+                        None => self.invalidate(),
+                    }
+                    return Ok(());
+                }
+            };
+
+            let mut params = DrawParameters::window(self.size.get(), self.scale.get());
+            params.damage = self.frame_damage.get();
+
+            let size = item.measure(BoxConstraints::loose(self.size.get()), backend)?;
+            item.draw(backend, params)?;
+
+            // If the child has moved or changed size, the area it no longer covers is repainted
+            // during the next frame.
+            let region = Rectangle {
+                x: 0,
+                y: 0,
+                width: size.width,
+                height: size.height,
+            };
+            if let Some(old) = self.regions.borrow_mut().insert(child, region) {
+                if old != region {
+                    self.damage(Damage::Region(old.union(region)));
+                }
+            }
+
+            // Keep overlays on top of everything else drawn during this frame.
+            if overlay {
+                self.overlay_drawn.set(true);
+                self.overlay_stale.set(false);
+            } else if self.overlay_drawn.get() {
+                self.overlay_stale.set(true);
+                self.restack.notify(usize::MAX);
+            }
+
+            self.drawn.notify(usize::MAX);
+            Ok(())
+        })();
+
+        (result, self.redraw_requested(overlay))
+    }
+}
+
+/// Convert a region in logical pixels to the smallest rectangle of physical pixels covering it.
+fn physical_rect(region: Rectangle, scale: f64) -> Rect {
+    Rect::new(
+        region.x as f64 * scale,
+        region.y as f64 * scale,
+        (region.x as f64 + region.width as f64) * scale,
+        (region.y as f64 + region.height as f64) * scale,
+    )
+    .expand()
+}
+
+/// Finishes the frame when dropped.
+struct FinishFrame<'a>(&'a Context);
+
+impl Drop for FinishFrame<'_> {
+    fn drop(&mut self) {
+        self.0.finish_frame();
+    }
+}
+
+/// Counts a widget as waiting for the next frame of a [`Context`].
+struct Waiting<'a> {
+    /// The context being waited on.
+    context: &'a Context,
+
+    /// The number of frames that had been started when the widget started waiting.
+    frame: u64,
+}
+
+impl<'a> Waiting<'a> {
+    /// Start waiting for the next frame.
+    fn new(context: &'a Context) -> Self {
+        context.waiting.set(context.waiting.get() + 1);
+
+        Self {
+            context,
+            frame: context.frames.get(),
+        }
+    }
+}
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        let context = self.context;
+        context.waiting.set(context.waiting.get() - 1);
+
+        // Widgets draw as soon as they are woken up, so the frame can be finished once every widget
+        // that was waiting for it has stopped waiting.
+        if context.frames.get() > self.frame {
+            context
+                .pending_draws
+                .set(context.pending_draws.get().saturating_sub(1));
+            context.drawn.notify(usize::MAX);
+        }
+    }
+}
+
+/// Input shared between every listener on a window.
+///
+/// Only one listener reads from the window at a time. Every event it reads is sent to all of the
+/// listeners, after keyboard focus has had a chance to handle it.
+struct WindowInput {
+    /// The input read from the window.
+    ///
+    /// This listens to the window for as long as it is open, so that input that comes in while
+    /// nothing is reading isn't missed.
+    inputs: RefCell<Inputs>,
+
+    /// The number of physical pixels per logical pixel.
+    scale: Rc<Cell<f64>>,
+
+    /// The keyboard focus of the window.
+    focus: Focus,
+
+    /// The event queues of the listeners.
+    queues: RefCell<Vec<Weak<RefCell<VecDeque<Event>>>>>,
+
+    /// Notified when events are sent, or when the reading listener stops reading.
+    sent: event_listener::Event,
+
+    /// Whether a listener is currently reading from the window.
+    reading: Cell<bool>,
+
+    /// The last known position of the cursor, in logical pixels.
+    position: Cell<Point>,
+
+    /// The mouse buttons currently held down.
+    buttons: Cell<PointerButtons>,
+
+    /// The modifier keys currently held down.
+    modifiers: Cell<gui_tools::Modifiers>,
+
+    /// The scan codes of the keys currently held down, used to tell repeats from new presses.
+    held: RefCell<HashSet<u32>>,
+
+    /// The last known positions of the touches currently on the screen, in logical pixels.
+    touches: RefCell<HashMap<u64, Point>>,
+
+    /// The pointer capture of the window.
+    capture: PointerCapture,
+
+    /// Events made up by the listener that haven't been read yet.
+    pending: RefCell<VecDeque<Event>>,
+
+    /// The settings used to recognize gestures.
+    gestures: Cell<GestureSettings>,
+
+    /// Counts presses in a row.
+    clicks: RefCell<ClickCounter>,
+
+    /// The time that presses are counted from.
+    epoch: Instant,
+
+    /// The press that becomes a long press if it is held in place, and when it does.
+    long_press: Cell<Option<(Instant, PointerEvent)>>,
+
+    /// When the mouse will have rested in place for long enough to hover.
+    hover: Cell<Option<Instant>>,
+}
+
+/// The input read from a window.
+type Inputs = Pin<Box<dyn Stream<Item = Input>>>;
+
+/// Input that the window listener cares about.
+enum Input {
+    Moved(Point),
+    Entered,
+    Left,
+    Button(gui_tools::MouseInput),
+    Touch(gui_tools::TouchInput),
+    Key(gui_tools::KeyInput),
+    Character(char),
+    Modifiers(gui_tools::Modifiers),
+    Wheel(gui_tools::ScrollDelta),
+    Focused(bool),
+    LongPress,
+    Hover,
+}
+
+impl WindowInput {
+    /// Read the next event from the window.
+    async fn read(&self) -> Event {
+        loop {
+            if let Some(event) = self.pending.borrow_mut().pop_front() {
+                return event;
+            }
+
+            let window = async {
+                let input = future::poll_fn(|cx| self.inputs.borrow_mut().poll_next(cx)).await;
+                input.expect("window input never ends")
+            };
+            let long_press = async {
+                match self.long_press.get() {
+                    Some((deadline, _)) => Timer::at(deadline).await,
+                    None => future::pending().await,
+                };
+                Input::LongPress
+            };
+            let hover = async {
+                match self.hover.get() {
+                    Some(deadline) => Timer::at(deadline).await,
+                    None => future::pending().await,
+                };
+                Input::Hover
+            };
+            let input = window.or(long_press).or(hover).await;
+
+            let mut event = match input {
+                Input::Moved(new_position) => {
+                    // Events are delivered in logical pixels.
+                    self.position.set(self.to_logical(new_position));
+                    Event::PointerMove(self.pointer(None))
+                }
+
+                Input::Entered => Event::PointerEnter(self.pointer(None)),
+
+                Input::Left => Event::PointerLeave(self.pointer(None)),
+
+                Input::Button(input) => {
+                    let button = match input.button {
+                        MouseButton::Left => PointerButton::Primary,
+                        MouseButton::Right => PointerButton::Secondary,
+                        MouseButton::Middle => PointerButton::Auxiliary,
+                        MouseButton::Other(other) => PointerButton::Other(other),
+                        _ => continue,
+                    };
+
+                    let mut buttons = self.buttons.get();
+                    if input.pressed {
+                        buttons.insert(button);
+                        self.buttons.set(buttons);
+                        Event::PointerDown(self.pointer(Some(button)))
+                    } else {
+                        buttons.remove(button);
+                        self.buttons.set(buttons);
+                        Event::PointerUp(self.pointer(Some(button)))
+                    }
+                }
+
+                Input::Touch(touch) => {
+                    let position = self.to_logical(touch.position);
+                    match touch.phase {
+                        TouchPhase::Started | TouchPhase::Moved => {
+                            self.touches.borrow_mut().insert(touch.id, position);
+                        }
+                        TouchPhase::Ended | TouchPhase::Cancelled => {
+                            self.touches.borrow_mut().remove(&touch.id);
+                        }
+                    }
+
+                    let down = matches!(touch.phase, TouchPhase::Started | TouchPhase::Moved);
+                    let mut buttons = PointerButtons::new();
+                    if down {
+                        buttons.insert(PointerButton::Primary);
+                    }
+
+                    let pointer = PointerEvent {
+                        x: position.x,
+                        y: position.y,
+                        kind: PointerKind::Touch { id: touch.id },
+                        button: match touch.phase {
+                            TouchPhase::Moved => None,
+                            _ => Some(PointerButton::Primary),
+                        },
+                        buttons,
+                        modifiers: self.modifiers(),
+                        pressure: match touch.pressure {
+                            Some(pressure) if down => pressure,
+                            None if down => 0.5,
+                            _ => 0.0,
+                        },
+                        count: 0,
+                    };
+
+                    match touch.phase {
+                        TouchPhase::Started => Event::PointerDown(pointer),
+                        TouchPhase::Moved => Event::PointerMove(pointer),
+                        TouchPhase::Ended => Event::PointerUp(pointer),
+                        TouchPhase::Cancelled => Event::PointerCancel(pointer),
+                    }
+                }
+
+                Input::Key(input) => {
+                    let repeat = if input.pressed {
+                        !self.held.borrow_mut().insert(input.scancode)
+                    } else {
+                        self.held.borrow_mut().remove(&input.scancode);
+                        false
+                    };
+
+                    let event = KeyEvent {
+                        key: input.key.map_or(Key::Unidentified, convert_key),
+                        scancode: input.scancode,
+                        modifiers: self.modifiers(),
+                        repeat,
+                    };
+
+                    if input.pressed {
+                        Event::KeyDown(event)
+                    } else {
+                        Event::KeyUp(event)
+                    }
+                }
+
+                Input::Character(c) => {
+                    // Shortcuts and control characters aren't text. Control and alt together are
+                    // AltGr on some platforms, which does type text.
+                    let modifiers = self.modifiers.get();
+                    if c.is_control() || modifiers.logo || (modifiers.control && !modifiers.alt) {
+                        continue;
+                    }
+
+                    Event::Text(c.to_string())
+                }
+
+                Input::Modifiers(modifiers) => {
+                    self.modifiers.set(modifiers);
+                    continue;
+                }
+
+                // Keys released while the window is in the background are never reported.
+                Input::Focused(true) => {
+                    self.held.borrow_mut().clear();
+                    continue;
+                }
+
+                // Neither are buttons or touches, so cancel every pointer that is still down.
+                // This also ends any pointer capture.
+                Input::Focused(false) => {
+                    self.held.borrow_mut().clear();
+                    self.cancel_pointers();
+                    continue;
+                }
+
+                Input::LongPress => match self.long_press.take() {
+                    Some((_, pressed)) => Event::LongPress(PointerEvent {
+                        modifiers: self.modifiers(),
+                        ..pressed
+                    }),
+                    None => continue,
+                },
+
+                // Only the mouse can hover, and only while nothing is pressed.
+                Input::Hover => {
+                    self.hover.set(None);
+                    if !self.buttons.get().is_empty() {
+                        continue;
+                    }
+
+                    Event::Hover(self.pointer(None))
+                }
+
+                Input::Wheel(delta) => {
+                    // Events are delivered in logical pixels.
+                    let (delta_x, delta_y) = match delta {
+                        ScrollDelta::Lines { x, y } => {
+                            (x as f64 * LINE_HEIGHT, y as f64 * LINE_HEIGHT)
+                        }
+                        ScrollDelta::Pixels { x, y } => {
+                            (x / self.scale.get(), y / self.scale.get())
+                        }
+                    };
+
+                    let position = self.position.get();
+                    Event::Scroll {
+                        x: position.x,
+                        y: position.y,
+                        delta_x,
+                        delta_y,
+                        modifiers: self.modifiers(),
+                    }
+                }
+            };
+
+            self.track(&mut event);
+            return event;
+        }
+    }
+
+    /// Count presses in a row, and start or stop waiting for long presses and hovers.
+    fn track(&self, event: &mut Event) {
+        let settings = self.gestures.get();
+        let now = gui_tools::now();
+
+        match event {
+            Event::PointerDown(pointer) => {
+                let time = now.saturating_duration_since(self.epoch);
+                pointer.count = self.clicks.borrow_mut().press(&settings, pointer, time);
+
+                // Only the primary button held down on its own can be a long press. Pressing
+                // anything else cancels the one being waited for.
+                let mut others = pointer.buttons;
+                others.remove(PointerButton::Primary);
+                let long_press = pointer.is_primary() && others.is_empty();
+                self.long_press
+                    .set(long_press.then(|| (now + settings.long_press_time, *pointer)));
+                self.hover.set(None);
+            }
+
+            Event::PointerMove(pointer) => {
+                // Moving too far away from the press means it isn't being held in place.
+                if let Some((_, pressed)) = self.long_press.get() {
+                    if pressed.kind == pointer.kind
+                        && !settings.is_near(pointer.x - pressed.x, pointer.y - pressed.y)
+                    {
+                        self.long_press.set(None);
+                    }
+                }
+
+                if pointer.kind == PointerKind::Mouse && pointer.buttons.is_empty() {
+                    self.hover.set(Some(now + settings.hover_time));
+                }
+            }
+
+            Event::PointerUp(pointer) | Event::PointerCancel(pointer) => {
+                if let Some((_, pressed)) = self.long_press.get() {
+                    if pressed.kind == pointer.kind {
+                        self.long_press.set(None);
+                    }
+                }
+            }
+
+            Event::PointerLeave(_) => self.hover.set(None),
+
+            _ => {}
+        }
+    }
+
+    /// Queue a cancel event for every pointer that is down.
+    fn cancel_pointers(&self) {
+        let mut pending = self.pending.borrow_mut();
+        self.long_press.set(None);
+        self.hover.set(None);
+
+        if !self.buttons.get().is_empty() {
+            self.buttons.set(PointerButtons::new());
+            pending.push_back(Event::PointerCancel(self.pointer(None)));
+        }
+
+        for (id, position) in self.touches.borrow_mut().drain() {
+            pending.push_back(Event::PointerCancel(PointerEvent {
+                x: position.x,
+                y: position.y,
+                kind: PointerKind::Touch { id },
+                modifiers: self.modifiers(),
+                ..PointerEvent::default()
+            }));
+        }
+    }
+
+    /// Convert a position in physical pixels to logical pixels.
+    fn to_logical(&self, position: Point) -> Point {
+        (position.to_vec2() / self.scale.get()).to_point()
+    }
+
+    /// Get the modifier keys currently held down.
+    fn modifiers(&self) -> Modifiers {
+        let modifiers = self.modifiers.get();
+        Modifiers {
+            shift: modifiers.shift,
+            control: modifiers.control,
+            alt: modifiers.alt,
+            logo: modifiers.logo,
+        }
+    }
+
+    /// Describe the mouse in its current state.
+    fn pointer(&self, button: Option<PointerButton>) -> PointerEvent {
+        let position = self.position.get();
+        let buttons = self.buttons.get();
+
+        PointerEvent {
+            x: position.x,
+            y: position.y,
+            kind: PointerKind::Mouse,
+            button,
+            buttons,
+            modifiers: self.modifiers(),
+            pressure: if buttons.is_empty() { 0.0 } else { 0.5 },
+            count: 0,
+        }
+    }
+
+    /// Send an event to every listener, and route it to the widgets of the window.
+    fn dispatch(&self, event: Event) {
+        if self.focus.handle_event(&event) {
+            return;
+        }
+
+        self.capture.dispatch(&event);
+
+        self.queues
+            .borrow_mut()
+            .retain(|queue| match queue.upgrade() {
+                Some(queue) => {
+                    queue.borrow_mut().push_back(event.clone());
+                    true
+                }
+                None => false,
+            });
+
+        self.sent.notify(usize::MAX);
+    }
+}
+
+/// Read every kind of input that the window listener cares about from a window.
+fn inputs(window: &Window) -> Inputs {
+    /// Read one kind of input from the window, over and over.
+    fn each<F: Future<Output = Input> + 'static>(
+        window: &Window,
+        read: impl Fn(Window) -> F + 'static,
+    ) -> impl Stream<Item = Input> {
+        stream::unfold(window.clone(), move |window| {
+            let input = read(window.clone());
+            async move { Some((input.await, window)) }
+        })
+    }
+
+    let moved = each(window, |window| async move {
+        Input::Moved(window.cursor_moved().await)
+    });
+    let entered = each(window, |window| async move {
+        window.cursor_entered().await;
+        Input::Entered
+    });
+    let left = each(window, |window| async move {
+        window.cursor_left().await;
+        Input::Left
+    });
+    let button = each(window, |window| async move {
+        Input::Button(window.mouse_input().await)
+    });
+    let touch = each(window, |window| async move {
+        Input::Touch(window.touch_input().await)
+    });
+    let key = each(window, |window| async move {
+        Input::Key(window.keyboard_input().await)
+    });
+    let character = each(window, |window| async move {
+        Input::Character(window.received_character().await)
+    });
+    let modifiers = each(window, |window| async move {
+        Input::Modifiers(window.modifiers_changed().await)
+    });
+    let wheel = each(window, |window| async move {
+        Input::Wheel(window.mouse_wheel().await)
+    });
+    let focused = each(window, |window| async move {
+        Input::Focused(window.focus_changed().await)
+    });
+
+    Box::pin(
+        moved
+            .or(entered)
+            .or(left)
+            .or(button)
+            .or(touch)
+            .or(key)
+            .or(character)
+            .or(modifiers)
+            .or(wheel)
+            .or(focused),
+    )
+}
+
+/// Convert a key from `gui-tools` to a key for `sunder`.
+fn convert_key(key: gui_tools::Key) -> Key {
+    use gui_tools::Key as Gk;
+
+    match key {
+        Gk::Tab => Key::Tab,
+        Gk::Enter => Key::Enter,
+        Gk::Escape => Key::Escape,
+        Gk::Backspace => Key::Backspace,
+        Gk::Delete => Key::Delete,
+        Gk::Space => Key::Space,
+        Gk::Left => Key::Left,
+        Gk::Right => Key::Right,
+        Gk::Up => Key::Up,
+        Gk::Down => Key::Down,
+        Gk::Home => Key::Home,
+        Gk::End => Key::End,
+        Gk::PageUp => Key::PageUp,
+        Gk::PageDown => Key::PageDown,
+        Gk::Insert => Key::Insert,
+        Gk::Function(n) => Key::Function(n),
+        Gk::Character(c) => Key::Character(c),
+        _ => Key::Unidentified,
+    }
+}
+
+/// Lets other listeners read from the window when dropped.
+struct Reading<'a>(&'a WindowInput);
+
+impl Drop for Reading<'_> {
+    fn drop(&mut self) {
+        self.0.reading.set(false);
+        self.0.sent.notify(usize::MAX);
+    }
+}
+
+/// Listens for input events on a window.
+pub struct WindowListener {
+    /// The input shared with the other listeners.
+    input: Rc<WindowInput>,
+
+    /// The events that have not been received yet.
+    queue: Rc<RefCell<VecDeque<Event>>>,
+}
+
+impl Listener<Event> for WindowListener {
+    type Stream<'a> = Pin<Box<dyn Stream<Item = Event> + 'a>>;
+
+    fn events(&mut self) -> Self::Stream<'_> {
+        let input = &*self.input;
+        let queue = &*self.queue;
+
+        Box::pin(stream::unfold((), move |()| async move {
+            loop {
+                let event = queue.borrow_mut().pop_front();
+                if let Some(event) = event {
+                    return Some((event, ()));
+                }
+
+                if !input.reading.replace(true) {
+                    // Read from the window on behalf of every listener.
+                    let _guard = Reading(input);
+                    let event = input.read().await;
+                    input.dispatch(event);
+                } else {
+                    let sent = input.sent.listen();
+
+                    if queue.borrow().is_empty() && input.reading.get() {
+                        sent.await;
+                    }
+                }
+            }
+        }))
+    }
+}
@@ -0,0 +1,400 @@
+/*
+
+`async-gui` is free software: you can redistribute it and/or modify it under the terms of one of
+the following licenses:
+
+- The GNU Affero General Public License as published by the Free Software Foundation, either version
+  3 of the License, or (at your option) any later version.
+- The Patron License at https://github.com/notgull/async-gui/blob/main/LICENSE-PATRON.md, for
+  sponsors and contributors, who can ignore the copyleft provisions of the GNU AGPL for this project.
+
+`async-gui` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+Public License and the Patron License for more details.
+
+You should have received a copy of the GNU Affero General Public License and the corresponding Patron
+License along with `async-gui`. If not, see <https://www.gnu.org/licenses/>.
+
+*/
+
+//! Animations that run in step with the frames of a window.
+//!
+//! An [`Animation`] describes how long a change takes and how it speeds up and slows down. Its
+//! [`values`] are produced once per frame, measured against the frame clock of the system that a
+//! widget is drawn into, so animations never need to sleep.
+//!
+//! ```ignore
+//! let fade = Animation::new(Duration::from_millis(200), Easing::EaseOut);
+//! let mut colors = fade.tween(&widget, Color::WHITE, Color::BLACK);
+//!
+//! while let Some(color) = colors.next().await {
+//!     background.set(color);
+//! }
+//! ```
+//!
+//! [`values`]: Animation::values
+
+use crate::{System, Widget};
+use kurbo::{Point, Rect, Size, Vec2};
+use sunder::RenderedWidget;
+
+use futures_lite::{future, stream, Stream, StreamExt};
+
+use std::pin::Pin;
+use std::time::{Duration, Instant};
+
+/// A stream of values from an [`Animation`], one for each frame.
+pub type Values<'a, T> = Pin<Box<dyn Stream<Item = T> + 'a>>;
+
+/// A change that happens over a period of time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Animation {
+    /// How long the animation takes.
+    duration: Duration,
+
+    /// How the animation speeds up and slows down.
+    easing: Easing,
+}
+
+impl Animation {
+    /// Create a new animation.
+    pub fn new(duration: Duration, easing: Easing) -> Self {
+        Self { duration, easing }
+    }
+
+    /// Get how long the animation takes.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Get how the animation speeds up and slows down.
+    pub fn easing(&self) -> Easing {
+        self.easing
+    }
+
+    /// Get the progress of the animation after some time has passed.
+    ///
+    /// The progress starts at 0.0 and ends at 1.0, although some easing curves go past either end
+    /// in between.
+    pub fn progress(&self, elapsed: Duration) -> f64 {
+        let t = if self.duration.is_zero() {
+            1.0
+        } else {
+            (elapsed.as_secs_f64() / self.duration.as_secs_f64()).min(1.0)
+        };
+
+        self.easing.apply(t)
+    }
+
+    /// Get the progress of the animation once per frame, until it is finished.
+    ///
+    /// Each value requests another frame from the widget's system. The animation starts at the
+    /// first frame and always ends with a progress of 1.0, unless the widget is unmounted or its
+    /// system is closed first.
+    pub fn values<'a, 'w: 'a, Sys, S>(&self, widget: &'a Widget<'w, Sys, S>) -> Values<'a, f64>
+    where
+        Sys: System + ?Sized + 'a,
+        S: RenderedWidget<Sys::Backend> + 'a,
+    {
+        let animation = *self;
+
+        // The state is the time that the animation started, or `None` once it has finished.
+        Box::pin(stream::unfold(
+            Some(None),
+            move |start: Option<Option<Instant>>| async move {
+                let start = start?;
+                let system = widget.mount.system();
+
+                let frame = async {
+                    system.request_frame().await;
+                    true
+                };
+                let gone = async {
+                    widget.mount.gone().await;
+                    false
+                };
+
+                // Stop once the widget is unmounted.
+                if !widget.is_mounted() || !future::or(gone, frame).await {
+                    return None;
+                }
+
+                let now = system.frame_time();
+                let start = start.unwrap_or(now);
+                let elapsed = now.saturating_duration_since(start);
+
+                let next = if elapsed >= animation.duration {
+                    None
+                } else {
+                    Some(Some(start))
+                };
+
+                Some((animation.progress(elapsed), next))
+            },
+        ))
+    }
+
+    /// Move from one value to another once per frame, until the animation is finished.
+    ///
+    /// This works like [`Animation::values`], but produces values between `from` and `to`.
+    pub fn tween<'a, 'w: 'a, Sys, S, T>(
+        &self,
+        widget: &'a Widget<'w, Sys, S>,
+        from: T,
+        to: T,
+    ) -> Values<'a, T>
+    where
+        Sys: System + ?Sized + 'a,
+        S: RenderedWidget<Sys::Backend> + 'a,
+        T: Tween + 'a,
+    {
+        Box::pin(self.values(widget).map(move |t| from.tween(&to, t)))
+    }
+}
+
+/// How an animation speeds up and slows down.
+///
+/// These match the easing functions of CSS transitions.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Easing {
+    /// The animation moves at the same speed the whole time.
+    Linear,
+
+    /// The animation speeds up quickly and slows down gradually.
+    #[default]
+    Ease,
+
+    /// The animation starts slowly.
+    EaseIn,
+
+    /// The animation ends slowly.
+    EaseOut,
+
+    /// The animation starts and ends slowly.
+    EaseInOut,
+
+    /// A cubic Bézier curve from (0, 0) to (1, 1), with two control points.
+    ///
+    /// The x coordinates are the time and should be between 0 and 1. The y coordinates are the
+    /// progress and can go past either end.
+    CubicBezier { x1: f64, y1: f64, x2: f64, y2: f64 },
+}
+
+impl Easing {
+    /// Get the progress at a time between 0.0 and 1.0.
+    pub fn apply(self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+
+        let (x1, y1, x2, y2) = match self {
+            Easing::Linear => return t,
+            Easing::Ease => (0.25, 0.1, 0.25, 1.0),
+            Easing::EaseIn => (0.42, 0.0, 1.0, 1.0),
+            Easing::EaseOut => (0.0, 0.0, 0.58, 1.0),
+            Easing::EaseInOut => (0.42, 0.0, 0.58, 1.0),
+            Easing::CubicBezier { x1, y1, x2, y2 } => (x1, y1, x2, y2),
+        };
+
+        // Both ends are fixed, no matter the control points.
+        if t == 0.0 || t == 1.0 {
+            return t;
+        }
+
+        bezier(solve_bezier(t, x1, x2), y1, y2)
+    }
+}
+
+/// Evaluate one coordinate of a cubic Bézier curve from 0 to 1.
+fn bezier(s: f64, p1: f64, p2: f64) -> f64 {
+    let inv = 1.0 - s;
+    3.0 * inv * inv * s * p1 + 3.0 * inv * s * s * p2 + s * s * s
+}
+
+/// Find the curve parameter where the x coordinate of a cubic Bézier curve equals `x`.
+fn solve_bezier(x: f64, x1: f64, x2: f64) -> f64 {
+    const EPSILON: f64 = 1e-7;
+
+    // Newton's method converges quickly for most curves.
+    let mut s = x;
+    for _ in 0..8 {
+        let error = bezier(s, x1, x2) - x;
+        if error.abs() < EPSILON {
+            return s;
+        }
+
+        let inv = 1.0 - s;
+        let slope = 3.0 * inv * inv * x1 + 6.0 * inv * s * (x2 - x1) + 3.0 * s * s * (1.0 - x2);
+        if slope.abs() < EPSILON {
+            break;
+        }
+
+        s -= error / slope;
+    }
+
+    // Fall back to bisection for flat parts of the curve.
+    let (mut low, mut high) = (0.0, 1.0);
+    s = x;
+    while high - low > EPSILON {
+        if bezier(s, x1, x2) < x {
+            low = s;
+        } else {
+            high = s;
+        }
+
+        s = (low + high) / 2.0;
+    }
+
+    s
+}
+
+/// A value that can be animated from one value to another.
+pub trait Tween: Clone {
+    /// Get the value `t` of the way from this value to `to`.
+    ///
+    /// `t` is usually between 0.0 and 1.0, but can go past either end.
+    fn tween(&self, to: &Self, t: f64) -> Self;
+}
+
+impl Tween for f64 {
+    fn tween(&self, to: &Self, t: f64) -> Self {
+        self + (to - self) * t
+    }
+}
+
+impl Tween for f32 {
+    fn tween(&self, to: &Self, t: f64) -> Self {
+        self + (to - self) * t as f32
+    }
+}
+
+impl Tween for Point {
+    fn tween(&self, to: &Self, t: f64) -> Self {
+        self.lerp(*to, t)
+    }
+}
+
+impl Tween for Vec2 {
+    fn tween(&self, to: &Self, t: f64) -> Self {
+        self.lerp(*to, t)
+    }
+}
+
+impl Tween for Size {
+    fn tween(&self, to: &Self, t: f64) -> Self {
+        Size::new(
+            self.width.tween(&to.width, t),
+            self.height.tween(&to.height, t),
+        )
+    }
+}
+
+impl Tween for Rect {
+    fn tween(&self, to: &Self, t: f64) -> Self {
+        Rect::new(
+            self.x0.tween(&to.x0, t),
+            self.y0.tween(&to.y0, t),
+            self.x1.tween(&to.x1, t),
+            self.y1.tween(&to.y1, t),
+        )
+    }
+}
+
+#[cfg(feature = "piet")]
+impl Tween for piet::Color {
+    fn tween(&self, to: &Self, t: f64) -> Self {
+        let (r1, g1, b1, a1) = self.as_rgba();
+        let (r2, g2, b2, a2) = to.as_rgba();
+
+        // Keep the channels in range for curves that overshoot.
+        let channel = |from: f64, to: f64| from.tween(&to, t).clamp(0.0, 1.0);
+        piet::Color::rgba(
+            channel(r1, r2),
+            channel(g1, g2),
+            channel(b1, b2),
+            channel(a1, a2),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRESETS: [Easing; 5] = [
+        Easing::Linear,
+        Easing::Ease,
+        Easing::EaseIn,
+        Easing::EaseOut,
+        Easing::EaseInOut,
+    ];
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-4,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn easing_ends_are_fixed() {
+        for easing in PRESETS {
+            assert_eq!(easing.apply(0.0), 0.0);
+            assert_eq!(easing.apply(1.0), 1.0);
+
+            // Times past either end are clamped.
+            assert_eq!(easing.apply(-1.0), 0.0);
+            assert_eq!(easing.apply(2.0), 1.0);
+        }
+    }
+
+    #[test]
+    fn easing_matches_css() {
+        assert_close(Easing::Linear.apply(0.3), 0.3);
+        assert_close(Easing::Ease.apply(0.5), 0.8024);
+        assert_close(Easing::EaseIn.apply(0.5), 0.3153);
+        assert_close(Easing::EaseOut.apply(0.5), 0.6847);
+        assert_close(Easing::EaseInOut.apply(0.5), 0.5);
+
+        // Ease-in-out is the same going forwards and backwards.
+        for t in [0.1, 0.25, 0.4] {
+            let ease = Easing::EaseInOut;
+            assert_close(ease.apply(t) + ease.apply(1.0 - t), 1.0);
+        }
+    }
+
+    #[test]
+    fn easing_can_overshoot() {
+        let back = Easing::CubicBezier {
+            x1: 0.3,
+            y1: -0.5,
+            x2: 0.7,
+            y2: 1.5,
+        };
+
+        assert!(back.apply(0.1) < 0.0);
+        assert!(back.apply(0.9) > 1.0);
+    }
+
+    #[test]
+    fn solves_for_time() {
+        let curves = [
+            (0.25, 0.25),
+            (0.42, 1.0),
+            (0.0, 0.58),
+            // Flat at both ends, where Newton's method struggles.
+            (1.0, 0.0),
+            (0.0, 0.0),
+        ];
+
+        for (x1, x2) in curves {
+            for i in 0..=20 {
+                let x = i as f64 / 20.0;
+                let s = solve_bezier(x, x1, x2);
+                assert!((0.0..=1.0).contains(&s));
+                assert_close(bezier(s, x1, x2), x);
+            }
+        }
+
+        // Control points on the diagonal make x the same as the curve parameter.
+        assert_close(solve_bezier(0.7, 1.0 / 3.0, 2.0 / 3.0), 0.7);
+    }
+}
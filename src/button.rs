@@ -0,0 +1,108 @@
+/*
+
+`async-gui` is free software: you can redistribute it and/or modify it under the terms of one of
+the following licenses:
+
+- The GNU Affero General Public License as published by the Free Software Foundation, either version
+  3 of the License, or (at your option) any later version.
+- The Patron License at https://github.com/notgull/async-gui/blob/main/LICENSE-PATRON.md, for
+  sponsors and contributors, who can ignore the copyleft provisions of the GNU AGPL for this project.
+
+`async-gui` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+Public License and the Patron License for more details.
+
+You should have received a copy of the GNU Affero General Public License and the corresponding Patron
+License along with `async-gui`. If not, see <https://www.gnu.org/licenses/>.
+
+*/
+
+//! A push button that can be clicked.
+
+use crate::{Error, System, Widget};
+use sunder::widgets::PushButton;
+use sunder::RenderedWidget;
+
+use std::cell::Cell;
+
+/// A push button that can be clicked.
+pub struct Button<Sys: System>
+where
+    PushButton<'static>: RenderedWidget<Sys::Backend>,
+{
+    /// The underlying widget.
+    widget: Widget<'static, Sys, PushButton<'static>>,
+
+    /// How many times the button has been clicked.
+    clicks: Cell<u64>,
+
+    /// How many times the button had been clicked the last time `clicked` returned.
+    seen: Cell<u64>,
+
+    /// Notified when the button is clicked.
+    clicked: event_listener::Event,
+}
+
+impl<Sys: System> Button<Sys>
+where
+    PushButton<'static>: RenderedWidget<Sys::Backend>,
+{
+    /// Create a new button with the given text.
+    pub fn new(system: Sys, text: impl Into<String>) -> Self {
+        Self {
+            widget: Widget::new(system, PushButton::new(text.into())),
+            clicks: Cell::new(0),
+            seen: Cell::new(0),
+            clicked: event_listener::Event::new(),
+        }
+    }
+
+    /// Wait for the button to be clicked.
+    ///
+    /// This returns straight away if the button was clicked since it last returned, so that clicks
+    /// made while nothing was waiting aren't missed.
+    pub async fn clicked(&self) {
+        loop {
+            let listener = self.clicked.listen();
+
+            let clicks = self.clicks.get();
+            if self.seen.replace(clicks) != clicks {
+                return;
+            }
+
+            listener.await;
+        }
+    }
+
+    /// Draw the button and handle its input.
+    ///
+    /// This returns once the button is unmounted or its system is closed, or if drawing the
+    /// button fails.
+    pub async fn draw(&self) -> Result<(), Error<Sys::Backend>> {
+        self.widget
+            .draw_with(|state| {
+                if state.take_clicked() {
+                    self.clicks.set(self.clicks.get().wrapping_add(1));
+                    self.clicked.notify(usize::MAX);
+                }
+            })
+            .await
+    }
+
+    /// Move keyboard focus to the button.
+    ///
+    /// While the button has focus, pressing enter or space clicks it.
+    pub fn request_focus(&self) {
+        self.widget.request_focus();
+    }
+
+    /// Wait until the button has keyboard focus.
+    pub async fn focus(&self) {
+        self.widget.focus().await
+    }
+
+    /// Unmount the button from its system.
+    pub fn unmount(&self) {
+        self.widget.unmount();
+    }
+}
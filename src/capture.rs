@@ -0,0 +1,455 @@
+/*
+
+`async-gui` is free software: you can redistribute it and/or modify it under the terms of one of
+the following licenses:
+
+- The GNU Affero General Public License as published by the Free Software Foundation, either version
+  3 of the License, or (at your option) any later version.
+- The Patron License at https://github.com/notgull/async-gui/blob/main/LICENSE-PATRON.md, for
+  sponsors and contributors, who can ignore the copyleft provisions of the GNU AGPL for this project.
+
+`async-gui` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+Public License and the Patron License for more details.
+
+You should have received a copy of the GNU Affero General Public License and the corresponding Patron
+License along with `async-gui`. If not, see <https://www.gnu.org/licenses/>.
+
+*/
+
+//! Routing input events to the widgets of a window, and pointer capture.
+
+use sunder::tree::{self, Container, Router};
+use sunder::{Damage, Event, PointerEvent, PointerKind, Rectangle};
+
+use futures_lite::prelude::*;
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+
+/// Routes the input events of a window to its widgets, and tracks which widget has captured each
+/// pointer.
+///
+/// Every widget, container and overlay in a window registers itself here when it is mounted.
+/// Events sent to the window are routed once, before any widget reads them:
+///
+/// - Pointer events go to the topmost widget under the pointer. Widgets registered later are on
+///   top of the ones registered before them, so overlays are on top of everything else. The
+///   containers above that widget can take presses before it in the capture phase, and see the
+///   event after it in the bubble phase. While a [modal](PointerCapture::modal) overlay is open,
+///   it takes every pointer event instead.
+/// - Every other event goes to every widget.
+///
+/// A widget that a pointer was pressed on can capture it, so that it keeps receiving every event
+/// for that pointer even once the pointer moves outside of the widget or the window. Capture ends
+/// once the last button of the pointer is released, or when the pointer is cancelled. Windows
+/// cancel their pointers when they lose focus.
+///
+/// Whether a press captures its pointer is decided once, when it is routed, and a released
+/// pointer is let go of only after its owner has been sent the event that released it. Widgets
+/// don't capture or release pointers while they handle events, so they always agree on where
+/// each event went.
+#[derive(Debug, Default)]
+pub struct PointerCapture {
+    /// Remembers which widget has captured each pointer, and which one the mouse is over.
+    router: RefCell<Router>,
+
+    /// Every widget by its ID, or `None` once it has been unregistered.
+    handlers: RefCell<Vec<Option<Handler>>>,
+
+    /// The modal overlays that are open, with the topmost one last.
+    modals: RefCell<Vec<CaptureId>>,
+
+    /// Notified when events are routed.
+    routed: event_listener::Event,
+}
+
+/// Identifies a widget that can capture pointers in a [`PointerCapture`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CaptureId(usize);
+
+/// A widget registered with a [`PointerCapture`].
+#[derive(Debug, Default)]
+struct Handler {
+    /// The container that events bubble up to from this widget.
+    parent: Option<CaptureId>,
+
+    /// The region of the widget, in window coordinates.
+    region: Rectangle,
+
+    /// The part of the widget that can be seen, in window coordinates.
+    clip: Rectangle,
+
+    /// Whether the widget captures pointers that are pressed on it.
+    captures: bool,
+
+    /// The regions where the widget takes presses before anything inside of it, in window
+    /// coordinates.
+    intercepts: Vec<Rectangle>,
+
+    /// The events routed to the widget that haven't been read yet, if it is reading them.
+    inbox: Option<VecDeque<Event>>,
+}
+
+impl PointerCapture {
+    /// Create a new pointer capture with no widgets registered.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a new widget, on top of every widget registered so far.
+    ///
+    /// Events bubble up from the widget to `parent`, and then on to its parents.
+    pub fn register(&self, parent: Option<CaptureId>) -> CaptureId {
+        let mut handlers = self.handlers.borrow_mut();
+        handlers.push(Some(Handler {
+            parent,
+            ..Handler::default()
+        }));
+        CaptureId(handlers.len() - 1)
+    }
+
+    /// Stop routing events to a widget, and release every pointer that it has captured.
+    ///
+    /// The ID is not reused.
+    pub fn unregister(&self, id: CaptureId) {
+        if let Some(handler) = self.handlers.borrow_mut().get_mut(id.0) {
+            *handler = None;
+        }
+
+        self.release_all(id);
+        self.modals.borrow_mut().retain(|&modal| modal != id);
+    }
+
+    /// Tell the router where a widget was drawn.
+    ///
+    /// `region` and `clip` are in window coordinates. Pointer events are only routed to the widget
+    /// while they are inside of `clip`, and are translated so that they are relative to the top
+    /// left corner of `region`.
+    pub fn place(&self, id: CaptureId, region: Rectangle, clip: Rectangle) {
+        self.with_handler(id, |handler| {
+            handler.region = region;
+            handler.clip = clip;
+        });
+    }
+
+    /// Set whether a widget captures pointers that are pressed on it.
+    pub fn set_captures_pointer(&self, id: CaptureId, captures: bool) {
+        self.with_handler(id, |handler| handler.captures = captures);
+    }
+
+    /// Set the regions where a widget takes presses before anything inside of it.
+    ///
+    /// The regions are in window coordinates. A press in one of them goes only to the widget,
+    /// which captures the pointer until it is released. Scroll views use this to keep their
+    /// scrollbars working on top of their content.
+    pub fn set_intercepts(&self, id: CaptureId, regions: impl IntoIterator<Item = Rectangle>) {
+        self.with_handler(id, |handler| {
+            handler.intercepts.clear();
+            handler.intercepts.extend(regions);
+        });
+    }
+
+    /// Start reading the events routed to a widget.
+    ///
+    /// Events are only kept for the widget while the [`Inbox`] is alive.
+    pub fn inbox(&self, id: CaptureId) -> Inbox<'_> {
+        self.with_handler(id, |handler| {
+            handler.inbox.get_or_insert_with(VecDeque::new);
+        });
+
+        Inbox { capture: self, id }
+    }
+
+    /// Send every pointer event to a widget, until the [`Modal`] is dropped.
+    ///
+    /// This is used for modal overlays, which take input away from every other widget. If several
+    /// are open, the one opened last gets the events.
+    pub fn modal(&self, id: CaptureId) -> Modal<'_> {
+        self.modals.borrow_mut().push(id);
+        Modal { capture: self, id }
+    }
+
+    /// Route an event to the widgets of the window.
+    ///
+    /// The event's position is in window coordinates. Each widget reads the events routed to it
+    /// from its [`Inbox`], relative to its own top left corner.
+    pub fn dispatch(&self, event: &Event) {
+        let mut handlers = self.handlers.borrow_mut();
+
+        if event.position().is_none() {
+            let inboxes = handlers
+                .iter_mut()
+                .flatten()
+                .flat_map(|handler| &mut handler.inbox);
+            for inbox in inboxes {
+                inbox.push_back(event.clone());
+            }
+        } else {
+            tree::dispatch(
+                &mut Routing {
+                    handlers: &mut handlers,
+                    router: &mut self.router.borrow_mut(),
+                    modal: self.modals.borrow().last().copied(),
+                },
+                event.clone(),
+            );
+        }
+
+        self.routed.notify(usize::MAX);
+    }
+
+    /// Cancel every pointer that has been captured.
+    ///
+    /// Each widget that captured a pointer is sent an [`Event::PointerCancel`] for it, so that it
+    /// lets go of any press or drag in progress. Modal overlays use this to take input away from
+    /// the rest of the window.
+    pub fn cancel_all(&self) {
+        let mut router = self.router.borrow_mut();
+        let mut handlers = self.handlers.borrow_mut();
+        let captured: Vec<_> = router.captured_pointers().collect();
+
+        for (kind, index) in captured {
+            router.release(kind);
+
+            // Where the pointer is doesn't matter once it is cancelled.
+            if let Some(Some(Handler {
+                inbox: Some(inbox), ..
+            })) = handlers.get_mut(index)
+            {
+                inbox.push_back(Event::PointerCancel(PointerEvent {
+                    kind,
+                    ..PointerEvent::default()
+                }));
+            }
+        }
+
+        self.routed.notify(usize::MAX);
+    }
+
+    /// Get the widget that has captured a pointer.
+    pub fn owner(&self, kind: PointerKind) -> Option<CaptureId> {
+        self.router.borrow().captured(kind).map(CaptureId)
+    }
+
+    /// Capture a pointer for a widget, taking it from any other widget.
+    pub fn capture(&self, kind: PointerKind, id: CaptureId) {
+        self.router.borrow_mut().capture(kind, id.0);
+    }
+
+    /// Release a pointer, if a widget has captured it.
+    pub fn release(&self, kind: PointerKind, id: CaptureId) {
+        if self.owner(kind) == Some(id) {
+            self.router.borrow_mut().release(kind);
+        }
+    }
+
+    /// Release every pointer that a widget has captured.
+    pub fn release_all(&self, id: CaptureId) {
+        self.router.borrow_mut().release_all(id.0);
+    }
+
+    /// Change a widget, if it is still registered.
+    fn with_handler(&self, id: CaptureId, f: impl FnOnce(&mut Handler)) {
+        if let Some(Some(handler)) = self.handlers.borrow_mut().get_mut(id.0) {
+            f(handler);
+        }
+    }
+}
+
+/// The events routed to a widget, from [`PointerCapture::inbox`].
+///
+/// The widget stops receiving events once this is dropped.
+pub struct Inbox<'a> {
+    /// The router that the widget is registered with.
+    capture: &'a PointerCapture,
+
+    /// The ID of the widget.
+    id: CaptureId,
+}
+
+impl Inbox<'_> {
+    /// Take the next event routed to the widget, if there is one.
+    pub fn pop(&self) -> Option<Event> {
+        match self.capture.handlers.borrow_mut().get_mut(self.id.0) {
+            Some(Some(handler)) => handler.inbox.as_mut()?.pop_front(),
+            _ => None,
+        }
+    }
+
+    /// Wait for the next event routed to the widget.
+    ///
+    /// `sent` is the stream of events sent to the window, from [`System::listen`]. Reading from
+    /// it keeps events coming in, and this returns `None` once it ends and every routed event
+    /// has been taken.
+    ///
+    /// [`System::listen`]: crate::System::listen
+    pub async fn next(&self, sent: &mut (impl Stream + Unpin)) -> Option<Event> {
+        loop {
+            let routed = self.capture.routed.listen();
+            if let Some(event) = self.pop() {
+                return Some(event);
+            }
+
+            let sent = async { sent.next().await.is_some() };
+            let routed = async {
+                routed.await;
+                true
+            };
+
+            if !sent.or(routed).await {
+                return self.pop();
+            }
+        }
+    }
+}
+
+impl Drop for Inbox<'_> {
+    fn drop(&mut self) {
+        self.capture
+            .with_handler(self.id, |handler| handler.inbox = None);
+    }
+}
+
+/// A modal overlay, from [`PointerCapture::modal`].
+///
+/// The overlay stops taking every pointer event once this is dropped.
+pub struct Modal<'a> {
+    /// The router that the overlay is registered with.
+    capture: &'a PointerCapture,
+
+    /// The ID of the overlay.
+    id: CaptureId,
+}
+
+impl Drop for Modal<'_> {
+    fn drop(&mut self) {
+        let mut modals = self.capture.modals.borrow_mut();
+        if let Some(index) = modals.iter().rposition(|&modal| modal == self.id) {
+            modals.remove(index);
+        }
+    }
+}
+
+/// The widgets of a window, as the children of a container that covers the whole window.
+struct Routing<'a> {
+    /// Every widget by its ID.
+    handlers: &'a mut [Option<Handler>],
+
+    /// Remembers where pointers are.
+    router: &'a mut Router,
+
+    /// The modal overlay that takes every pointer event, if one is open.
+    modal: Option<CaptureId>,
+}
+
+impl Routing<'_> {
+    /// Get a widget, if it is still registered.
+    fn handler(&self, index: usize) -> Option<&Handler> {
+        self.handlers.get(index).and_then(Option::as_ref)
+    }
+
+    /// Send an event in window coordinates to a widget, in the widget's coordinates.
+    fn send(&mut self, id: CaptureId, event: &Event) {
+        if let Some(Some(handler)) = self.handlers.get_mut(id.0) {
+            let region = handler.region;
+            if let Some(inbox) = &mut handler.inbox {
+                inbox.push_back(event.clone().translate(-region.x as f64, -region.y as f64));
+            }
+        }
+    }
+
+    /// Get the containers above a widget, from the outermost one down, followed by the widget.
+    fn path(&self, index: usize) -> Vec<CaptureId> {
+        let mut path = vec![CaptureId(index)];
+        while let Some(parent) = path
+            .last()
+            .and_then(|id| self.handler(id.0))
+            .and_then(|handler| handler.parent)
+        {
+            path.push(parent);
+        }
+
+        path.reverse();
+        path
+    }
+}
+
+impl Container for Routing<'_> {
+    fn child_count(&self) -> usize {
+        self.handlers.len()
+    }
+
+    fn child_region(&self, index: usize) -> Rectangle {
+        self.handler(index)
+            .map_or_else(Rectangle::default, |handler| handler.region)
+    }
+
+    fn child_clip(&self, index: usize) -> Rectangle {
+        self.handler(index)
+            .map_or_else(Rectangle::default, |handler| handler.clip)
+    }
+
+    fn child_event(&mut self, index: usize, event: Event) -> Damage {
+        if let Some(Some(Handler {
+            inbox: Some(inbox), ..
+        })) = self.handlers.get_mut(index)
+        {
+            inbox.push_back(event);
+        }
+
+        // Widgets redraw themselves once they have read their events.
+        Damage::None
+    }
+
+    fn child_captures_pointer(&self, index: usize) -> bool {
+        self.handler(index).is_some_and(|handler| handler.captures)
+    }
+
+    fn router(&mut self) -> &mut Router {
+        self.router
+    }
+
+    fn capture(&mut self, event: &Event) -> Option<Damage> {
+        // A modal overlay takes every pointer event from the widgets below it.
+        if let Some(modal) = self.modal {
+            self.send(modal, event);
+            return Some(Damage::None);
+        }
+
+        // Presses on a container's intercepts go to the container, unless the pointer is
+        // already captured.
+        let pointer = match event {
+            Event::PointerDown(pointer) if self.router.captured(pointer.kind).is_none() => pointer,
+            _ => return None,
+        };
+        let hit = tree::hit_test(self, pointer.x, pointer.y)?;
+        let container = self.path(hit).into_iter().find(|id| {
+            self.handler(id.0).is_some_and(|handler| {
+                handler
+                    .intercepts
+                    .iter()
+                    .any(|region| region.contains(pointer.x, pointer.y))
+            })
+        })?;
+
+        self.send(container, event);
+        if !pointer.buttons.is_empty() {
+            self.router.capture(pointer.kind, container.0);
+        }
+        Some(Damage::None)
+    }
+
+    fn bubble(&mut self, event: &Event, target: Option<usize>) -> Damage {
+        let mut parent = target
+            .and_then(|index| self.handler(index))
+            .and_then(|handler| handler.parent);
+
+        while let Some(id) = parent {
+            self.send(id, event);
+            parent = self.handler(id.0).and_then(|handler| handler.parent);
+        }
+
+        Damage::None
+    }
+}
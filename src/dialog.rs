@@ -0,0 +1,235 @@
+/*
+
+`async-gui` is free software: you can redistribute it and/or modify it under the terms of one of
+the following licenses:
+
+- The GNU Affero General Public License as published by the Free Software Foundation, either version
+  3 of the License, or (at your option) any later version.
+- The Patron License at https://github.com/notgull/async-gui/blob/main/LICENSE-PATRON.md, for
+  sponsors and contributors, who can ignore the copyleft provisions of the GNU AGPL for this project.
+
+`async-gui` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+Public License and the Patron License for more details.
+
+You should have received a copy of the GNU Affero General Public License and the corresponding Patron
+License along with `async-gui`. If not, see <https://www.gnu.org/licenses/>.
+
+*/
+
+//! Modal dialogs that wait for the user to make a choice.
+
+use crate::{BackResult, DrawParameters, Drawable, Error, Listener, Mount, System};
+use sunder::widgets::{MessageBox, MessageBoxState};
+use sunder::Widget as _;
+use sunder::{Backend, BoxConstraints, Damage, Event, Rectangle, RenderedWidget, Size};
+
+use futures_lite::{pin, prelude::*};
+
+use std::any::type_name;
+use std::cell::{Cell, RefCell};
+use std::fmt;
+
+/// A modal dialog, drawn on top of a window.
+///
+/// While the dialog is open, it takes input away from every other widget in the window, and every
+/// pointer event goes to the dialog wherever the pointer is. The user
+/// can click one of its buttons, or move between them with Tab and press enter, or press escape
+/// to dismiss the dialog without choosing anything.
+///
+/// Dialogs are drawn as overlays, so they should be opened on the system for the whole window
+/// rather than on a container inside of it.
+pub struct Dialog<Sys: System>
+where
+    MessageBox<'static>: RenderedWidget<Sys::Backend>,
+{
+    /// The underlying widget.
+    widget: RefCell<MessageBox<'static>>,
+
+    /// The widget's immediate state.
+    state: RefCell<MessageBoxState>,
+
+    /// Cache of widget-specific data.
+    cache: RefCell<<MessageBox<'static> as RenderedWidget<Sys::Backend>>::Cache>,
+
+    /// The size that the widget was last measured at.
+    size: Cell<Size>,
+
+    /// The region that the widget was last drawn into.
+    region: Cell<Rectangle>,
+
+    /// The dialog's registration in the system to be drawn into.
+    mount: Mount<Sys>,
+}
+
+impl<Sys: System> Dialog<Sys>
+where
+    MessageBox<'static>: RenderedWidget<Sys::Backend>,
+{
+    /// Show a message with a button for each choice, and wait for one of them to be chosen.
+    ///
+    /// Each button is labelled with its choice. This resolves to the choice that was made, or to
+    /// `None` if the dialog was dismissed or the system was closed.
+    pub async fn message<T: fmt::Display>(
+        system: Sys,
+        message: impl Into<String>,
+        choices: impl IntoIterator<Item = T>,
+    ) -> Result<Option<T>, Error<Sys::Backend>> {
+        let choices: Vec<T> = choices.into_iter().collect();
+        let dialog = Self::new(system, message, choices.iter().map(ToString::to_string));
+
+        let chosen = dialog.run().await?;
+        Ok(chosen.and_then(|index| choices.into_iter().nth(index)))
+    }
+
+    /// Create a new dialog with a message and buttons.
+    ///
+    /// The dialog isn't shown until [`Dialog::run`] is called.
+    pub fn new(
+        system: Sys,
+        message: impl Into<String>,
+        buttons: impl IntoIterator<Item = String>,
+    ) -> Self {
+        Self {
+            widget: RefCell::new(MessageBox::new(message.into(), buttons)),
+            state: RefCell::new(MessageBoxState::default()),
+            cache: RefCell::new(Default::default()),
+            size: Cell::new(Size::default()),
+            region: Cell::new(Rectangle::default()),
+            mount: Mount::overlay(system),
+        }
+    }
+
+    /// Show the dialog, and wait for one of its buttons to be clicked.
+    ///
+    /// This resolves to the index of the button that was clicked, or to `None` if the dialog was
+    /// dismissed or the system was closed. The dialog is unmounted afterwards, and input goes
+    /// back to the rest of the window.
+    pub async fn run(&self) -> Result<Option<usize>, Error<Sys::Backend>> {
+        if !self.mount.is_mounted() {
+            return Ok(None);
+        }
+
+        // Take input away from the rest of the window, including any press or drag in progress.
+        let capture = self.mount.system().pointer_capture();
+        let grab = self.mount.system().focus().grab();
+        capture.cancel_all();
+        let modal = capture.modal(self.mount.capture_id());
+        self.handle_event(Event::FocusIn);
+
+        let handle_events = async {
+            let inbox = capture.inbox(self.mount.capture_id());
+            let mut listener = self.mount.system().listen();
+            let events = listener.events();
+            pin!(events);
+
+            while let Some(event) = inbox.next(&mut events).await {
+                // A dialog opened on top of this one gets the input instead, but a press on
+                // this one that it cancelled still has to end.
+                if !grab.is_topmost() && !matches!(event, Event::PointerCancel(_)) {
+                    continue;
+                }
+
+                self.handle_event(event);
+
+                let mut state = self.state.borrow_mut();
+                if let Some(index) = state.take_chosen() {
+                    return Ok(Some(index));
+                }
+
+                if state.take_dismissed() {
+                    return Ok(None);
+                }
+            }
+
+            // No more events will come in, so nothing can be chosen.
+            Ok(None)
+        };
+
+        let draw = async {
+            loop {
+                let key = self.mount.key();
+                let (res, wait) = self.mount.system().draw(key, self);
+                res.map_err(|err| Error::new(err, type_name::<MessageBox<'_>>(), key))?;
+                wait.await;
+            }
+        };
+
+        let gone = async {
+            self.mount.gone().await;
+            Ok(None)
+        };
+
+        let result = gone.or(handle_events.or(draw)).await;
+
+        // Give input back to the window before anything else is drawn into it.
+        drop(modal);
+        drop(grab);
+        self.mount.unmount();
+        result
+    }
+
+    /// Deliver an event that is relative to the top left corner of the widget.
+    fn handle_event(&self, event: Event) {
+        let region = self.region.get();
+        let damage = self
+            .widget
+            .borrow_mut()
+            .handle_event(&mut self.state.borrow_mut(), event);
+
+        if let Damage::Region(damaged) = damage.within(region) {
+            self.mount.system().invalidate(damaged);
+        }
+    }
+}
+
+impl<Sys: System> Drawable<Sys::Backend> for &Dialog<Sys>
+where
+    MessageBox<'static>: RenderedWidget<Sys::Backend>,
+{
+    fn measure(
+        &mut self,
+        constraints: BoxConstraints,
+        backend: &mut Sys::Backend,
+    ) -> Result<Size, <Sys::Backend as Backend>::Error> {
+        let mut widget = self.widget.borrow_mut();
+        let size = widget.measure(constraints, &mut self.cache.borrow_mut(), backend)?;
+
+        // The dialog centers itself in whatever region it is drawn into.
+        self.size.set(size);
+        Ok(size)
+    }
+
+    fn draw(self, backend: &mut Sys::Backend, params: DrawParameters) -> BackResult<Sys::Backend> {
+        let size = self.size.get();
+        let relative = Rectangle {
+            x: (params.region.width.saturating_sub(size.width) / 2) as i32,
+            y: (params.region.height.saturating_sub(size.height) / 2) as i32,
+            width: size.width,
+            height: size.height,
+        };
+        let params = params.child(relative);
+
+        // If the dialog has moved, the area that it left behind needs to be repainted too.
+        let old_region = self.region.replace(params.region);
+        if old_region != params.region && !old_region.is_empty() {
+            self.mount
+                .system()
+                .invalidate(old_region.union(params.region));
+        }
+
+        // Route pointer events to where the dialog is now.
+        let capture = self.mount.system().pointer_capture();
+        let id = self.mount.capture_id();
+        capture.place(id, params.region, params.clip);
+        capture.set_captures_pointer(id, self.widget.borrow().captures_pointer());
+
+        backend.with_region(relative, |backend| {
+            let mut widget = self.widget.borrow_mut();
+            let mut cache = self.cache.borrow_mut();
+
+            widget.arrange(params.region, &mut cache, backend)?;
+            widget.render(&self.state.borrow(), &mut cache, backend)
+        })
+    }
+}
@@ -0,0 +1,84 @@
+/*
+
+`async-gui` is free software: you can redistribute it and/or modify it under the terms of one of
+the following licenses:
+
+- The GNU Affero General Public License as published by the Free Software Foundation, either version
+  3 of the License, or (at your option) any later version.
+- The Patron License at https://github.com/notgull/async-gui/blob/main/LICENSE-PATRON.md, for
+  sponsors and contributors, who can ignore the copyleft provisions of the GNU AGPL for this project.
+
+`async-gui` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+Public License and the Patron License for more details.
+
+You should have received a copy of the GNU Affero General Public License and the corresponding Patron
+License along with `async-gui`. If not, see <https://www.gnu.org/licenses/>.
+
+*/
+
+//! Errors that can occur while drawing widgets.
+
+use sunder::Backend;
+
+use std::fmt;
+
+/// An error that occurred while drawing a widget.
+pub struct Error<B: Backend> {
+    /// The error produced by the backend.
+    error: B::Error,
+
+    /// The type name of the widget that failed to draw.
+    widget: &'static str,
+
+    /// The key of the widget in its system.
+    key: usize,
+}
+
+impl<B: Backend> Error<B> {
+    pub(crate) fn new(error: B::Error, widget: &'static str, key: usize) -> Self {
+        Self { error, widget, key }
+    }
+
+    /// Get the error produced by the backend.
+    pub fn backend_error(&self) -> &B::Error {
+        &self.error
+    }
+
+    /// Take the error produced by the backend.
+    pub fn into_backend_error(self) -> B::Error {
+        self.error
+    }
+
+    /// Get the type name of the widget that failed to draw.
+    pub fn widget(&self) -> &'static str {
+        self.widget
+    }
+
+    /// Get the key of the widget that failed to draw, in its system.
+    pub fn key(&self) -> usize {
+        self.key
+    }
+}
+
+impl<B: Backend> fmt::Debug for Error<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Error")
+            .field("error", &self.error)
+            .field("widget", &self.widget)
+            .field("key", &self.key)
+            .finish()
+    }
+}
+
+impl<B: Backend> fmt::Display for Error<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "failed to draw widget `{}` (key {}): {}",
+            self.widget, self.key, self.error
+        )
+    }
+}
+
+impl<B: Backend> std::error::Error for Error<B> {}
@@ -0,0 +1,407 @@
+/*
+
+`async-gui` is free software: you can redistribute it and/or modify it under the terms of one of
+the following licenses:
+
+- The GNU Affero General Public License as published by the Free Software Foundation, either version
+  3 of the License, or (at your option) any later version.
+- The Patron License at https://github.com/notgull/async-gui/blob/main/LICENSE-PATRON.md, for
+  sponsors and contributors, who can ignore the copyleft provisions of the GNU AGPL for this project.
+
+`async-gui` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+Public License and the Patron License for more details.
+
+You should have received a copy of the GNU Affero General Public License and the corresponding Patron
+License along with `async-gui`. If not, see <https://www.gnu.org/licenses/>.
+
+*/
+
+//! Keyboard focus for the widgets of a window.
+
+use event_listener::EventListener;
+use sunder::{Event, Key, KeyEvent};
+
+use std::cell::{Cell, RefCell};
+
+/// Tracks which widget in a window has keyboard focus.
+///
+/// Focusable widgets register themselves when they are created. Pressing Tab moves focus to the
+/// next widget in the order they were registered in, and Shift+Tab moves it to the previous one.
+/// Widgets with focusable parts of their own, such as containers, can
+/// [keep Tab](Focus::set_steps_within) to move focus between those parts first.
+///
+/// Modal dialogs [grab](Focus::grab) input, which takes it away from every widget in the window
+/// until the dialog is closed.
+#[derive(Debug, Default)]
+pub struct Focus {
+    /// The focusable widgets, in tab order.
+    order: RefCell<Vec<FocusId>>,
+
+    /// The widget that currently has focus.
+    focused: Cell<Option<FocusId>>,
+
+    /// Whether Tab and Shift+Tab move focus within the focused widget, rather than out of it.
+    steps_within: Cell<(bool, bool)>,
+
+    /// The next ID to hand out.
+    next_id: Cell<u64>,
+
+    /// The grabs currently held, from the oldest to the most recent.
+    grabs: RefCell<Vec<GrabEntry>>,
+
+    /// The next ID to hand out to a grab.
+    next_grab: Cell<u64>,
+
+    /// Notified when focus moves.
+    changed: event_listener::Event,
+}
+
+/// Takes input away from the widgets of a window while it is held.
+///
+/// This is created by [`Focus::grab`].
+#[derive(Debug)]
+pub struct Grab<'a> {
+    /// The focus of the window.
+    focus: &'a Focus,
+
+    /// Identifies this grab in the stack of grabs.
+    id: u64,
+}
+
+/// A grab on the stack of grabs held on a [`Focus`].
+#[derive(Debug)]
+struct GrabEntry {
+    /// Identifies the grab.
+    id: u64,
+
+    /// The widget to give focus back to once the grab is released.
+    previous: Option<FocusId>,
+}
+
+/// Identifies a focusable widget in a [`Focus`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FocusId(u64);
+
+impl Focus {
+    /// Create a new focus manager with no focusable widgets.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a new focusable widget at the end of the tab order.
+    pub fn register(&self) -> FocusId {
+        let id = FocusId(self.next_id.get());
+        self.next_id.set(id.0 + 1);
+        self.order.borrow_mut().push(id);
+        id
+    }
+
+    /// Remove a widget from the tab order.
+    ///
+    /// If the widget has focus, nothing has focus afterwards.
+    pub fn unregister(&self, id: FocusId) {
+        self.order.borrow_mut().retain(|&other| other != id);
+
+        if self.is_focused(id) {
+            self.set_focus(None);
+        }
+    }
+
+    /// Get the widget that currently has focus.
+    pub fn focused(&self) -> Option<FocusId> {
+        self.focused.get()
+    }
+
+    /// Whether a widget currently has focus.
+    pub fn is_focused(&self, id: FocusId) -> bool {
+        self.focused.get() == Some(id)
+    }
+
+    /// Move focus to a widget, or remove focus from every widget.
+    ///
+    /// Widgets that aren't registered can't receive focus.
+    pub fn set_focus(&self, id: Option<FocusId>) {
+        if let Some(id) = id {
+            if !self.order.borrow().contains(&id) {
+                return;
+            }
+        }
+
+        if self.focused.replace(id) != id {
+            self.steps_within.set((false, false));
+            self.changed.notify(usize::MAX);
+        }
+    }
+
+    /// Let Tab, and Shift+Tab, move focus within a widget rather than out of it.
+    ///
+    /// This does nothing unless the widget has focus, and is forgotten once focus moves. While it
+    /// is set, those keys are delivered to the widget instead of moving focus.
+    pub fn set_steps_within(&self, id: FocusId, forwards: bool, backwards: bool) {
+        if self.is_focused(id) {
+            self.steps_within.set((forwards, backwards));
+        }
+    }
+
+    /// Move focus to the next widget in the tab order, wrapping around at the end.
+    pub fn focus_next(&self) {
+        self.step(true);
+    }
+
+    /// Move focus to the previous widget in the tab order, wrapping around at the start.
+    pub fn focus_previous(&self) {
+        self.step(false);
+    }
+
+    /// Take input away from every widget in the window, for as long as the returned guard is held.
+    ///
+    /// This is used by modal dialogs, which read input for themselves. Focus is taken away from
+    /// the focused widget, and given back to it once the grab is released.
+    pub fn grab(&self) -> Grab<'_> {
+        let previous = self.focused.get();
+        self.set_focus(None);
+
+        let id = self.next_grab.get();
+        self.next_grab.set(id + 1);
+        self.grabs.borrow_mut().push(GrabEntry { id, previous });
+
+        Grab { focus: self, id }
+    }
+
+    /// Whether input has been taken away from the widgets of the window.
+    pub fn is_grabbed(&self) -> bool {
+        !self.grabs.borrow().is_empty()
+    }
+
+    /// Handle a keyboard event for the window, moving focus on Tab and Shift+Tab.
+    ///
+    /// Returns `true` if the event was used to move focus, in which case it shouldn't be delivered
+    /// to any widget. While input is grabbed, or while the focused widget
+    /// [moves focus within itself](Focus::set_steps_within), focus doesn't move and the event is
+    /// delivered.
+    pub fn handle_event(&self, event: &Event) -> bool {
+        if self.is_grabbed() {
+            return false;
+        }
+
+        match event {
+            Event::KeyDown(KeyEvent {
+                key: Key::Tab,
+                modifiers,
+                ..
+            }) => {
+                let (forwards, backwards) = self.steps_within.get();
+                if modifiers.shift {
+                    if backwards {
+                        return false;
+                    }
+                    self.focus_previous();
+                } else {
+                    if forwards {
+                        return false;
+                    }
+                    self.focus_next();
+                }
+
+                true
+            }
+
+            Event::KeyUp(KeyEvent { key: Key::Tab, .. }) => true,
+
+            _ => false,
+        }
+    }
+
+    /// Listen for focus to move.
+    pub(crate) fn changed(&self) -> EventListener {
+        self.changed.listen()
+    }
+
+    /// Move focus one step through the tab order.
+    fn step(&self, forwards: bool) {
+        let next = {
+            let order = self.order.borrow();
+            if order.is_empty() {
+                return;
+            }
+
+            let current = self
+                .focused
+                .get()
+                .and_then(|id| order.iter().position(|&other| other == id));
+
+            let index = match (current, forwards) {
+                (Some(i), true) => (i + 1) % order.len(),
+                (Some(i), false) => (i + order.len() - 1) % order.len(),
+                (None, true) => 0,
+                (None, false) => order.len() - 1,
+            };
+
+            order[index]
+        };
+
+        self.set_focus(Some(next));
+    }
+}
+
+impl Grab<'_> {
+    /// Whether this is the most recent grab, which input should go to.
+    pub fn is_topmost(&self) -> bool {
+        self.focus.grabs.borrow().last().map(|grab| grab.id) == Some(self.id)
+    }
+}
+
+impl Drop for Grab<'_> {
+    fn drop(&mut self) {
+        let mut grabs = self.focus.grabs.borrow_mut();
+        let index = match grabs.iter().position(|grab| grab.id == self.id) {
+            Some(index) => index,
+            None => return,
+        };
+        let grab = grabs.remove(index);
+
+        match grabs.get_mut(index) {
+            // A grab taken after this one is still held, so focus goes back to where this grab
+            // found it once that one is released.
+            Some(next) => next.previous = grab.previous,
+
+            // This was the topmost grab, so give focus back.
+            None => {
+                drop(grabs);
+                self.focus.set_focus(grab.previous);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sunder::Modifiers;
+
+    /// Press Tab, or Shift+Tab if `shift` is set, returning whether focus moved.
+    fn tab(focus: &Focus, shift: bool) -> bool {
+        let modifiers = Modifiers {
+            shift,
+            ..Modifiers::default()
+        };
+        focus.handle_event(&Event::KeyDown(KeyEvent::new(Key::Tab, modifiers)))
+    }
+
+    #[test]
+    fn tabs_in_registration_order() {
+        let focus = Focus::new();
+        let [a, b, c] = [focus.register(), focus.register(), focus.register()];
+        assert_eq!(focus.focused(), None);
+
+        assert!(tab(&focus, false));
+        assert_eq!(focus.focused(), Some(a));
+        assert!(tab(&focus, false));
+        assert_eq!(focus.focused(), Some(b));
+        assert!(tab(&focus, true));
+        assert_eq!(focus.focused(), Some(a));
+
+        // Focus wraps around at both ends.
+        assert!(tab(&focus, true));
+        assert_eq!(focus.focused(), Some(c));
+        assert!(tab(&focus, false));
+        assert_eq!(focus.focused(), Some(a));
+
+        // Releasing Tab is swallowed too, but other keys aren't.
+        let release = Event::KeyUp(KeyEvent::new(Key::Tab, Modifiers::default()));
+        assert!(focus.handle_event(&release));
+        let enter = Event::KeyDown(KeyEvent::new(Key::Enter, Modifiers::default()));
+        assert!(!focus.handle_event(&enter));
+        assert_eq!(focus.focused(), Some(a));
+    }
+
+    #[test]
+    fn starts_from_the_end_backwards() {
+        let focus = Focus::new();
+        let [_, b] = [focus.register(), focus.register()];
+
+        assert!(tab(&focus, true));
+        assert_eq!(focus.focused(), Some(b));
+    }
+
+    #[test]
+    fn unregistered_widgets_lose_focus() {
+        let focus = Focus::new();
+        let [a, b] = [focus.register(), focus.register()];
+
+        focus.set_focus(Some(b));
+        focus.unregister(b);
+        assert_eq!(focus.focused(), None);
+
+        // Widgets that aren't registered can't be focused, or be tabbed to.
+        focus.set_focus(Some(b));
+        assert_eq!(focus.focused(), None);
+        tab(&focus, false);
+        tab(&focus, false);
+        assert_eq!(focus.focused(), Some(a));
+    }
+
+    #[test]
+    fn grab_takes_focus_away() {
+        let focus = Focus::new();
+        let [a, _] = [focus.register(), focus.register()];
+        focus.set_focus(Some(a));
+
+        let grab = focus.grab();
+        assert!(focus.is_grabbed());
+        assert!(grab.is_topmost());
+        assert_eq!(focus.focused(), None);
+
+        // Tab goes to the dialog instead of moving focus.
+        assert!(!tab(&focus, false));
+        assert_eq!(focus.focused(), None);
+
+        drop(grab);
+        assert!(!focus.is_grabbed());
+        assert_eq!(focus.focused(), Some(a));
+    }
+
+    #[test]
+    fn grabs_give_focus_back_in_any_order() {
+        let focus = Focus::new();
+        let a = focus.register();
+        focus.set_focus(Some(a));
+
+        let first = focus.grab();
+        let second = focus.grab();
+        assert!(!first.is_topmost());
+        assert!(second.is_topmost());
+
+        // Releasing the older grab first still leaves input grabbed until the newer one goes,
+        // and focus goes back to where the older grab found it.
+        drop(first);
+        assert!(focus.is_grabbed());
+        assert!(second.is_topmost());
+        assert_eq!(focus.focused(), None);
+
+        drop(second);
+        assert_eq!(focus.focused(), Some(a));
+    }
+
+    #[test]
+    fn tab_can_step_within_a_widget() {
+        let focus = Focus::new();
+        let [a, b] = [focus.register(), focus.register()];
+        focus.set_focus(Some(a));
+
+        // Only the focused widget can keep Tab for itself.
+        focus.set_steps_within(b, true, true);
+        focus.set_steps_within(a, true, false);
+        assert!(!tab(&focus, false));
+        assert_eq!(focus.focused(), Some(a));
+
+        assert!(tab(&focus, true));
+        assert_eq!(focus.focused(), Some(b));
+
+        // Moving focus forgets about it.
+        focus.set_focus(Some(a));
+        assert!(tab(&focus, false));
+        assert_eq!(focus.focused(), Some(b));
+    }
+}
@@ -19,47 +19,224 @@ License along with `async-gui`. If not, see <https://www.gnu.org/licenses/>.
 
 //! A retained mode GUI framework with emphasis on asynchronous code flow.
 
-use futures_lite::prelude::*;
-use sunder::{Backend, RenderedWidget, Widget as SunWidget};
+use futures_lite::{pin, prelude::*};
+use kurbo::Affine;
+use signal::Dependencies;
+use sunder::{
+    Backend, BoxConstraints, Damage, Event, Key, KeyEvent, PointerKind, Rectangle, RenderedWidget,
+    Size,
+};
 
-use std::cell::RefCell;
+use std::any::type_name;
+use std::cell::{Cell, Ref, RefCell};
 use std::future::Future;
+use std::time::Instant;
+
+mod animation;
+mod button;
+mod capture;
+mod dialog;
+mod error;
+mod focus;
+mod mount;
+mod scroll_view;
+mod signal;
+mod stack;
+mod text_area;
+mod text_input;
+
+pub mod testing;
+
+pub use animation::{Animation, Easing, Tween, Values};
+pub use button::Button;
+pub use capture::{CaptureId, Inbox, Modal, PointerCapture};
+pub use dialog::Dialog;
+pub use error::Error;
+pub use focus::{Focus, FocusId, Grab};
+pub use kurbo;
+pub use mount::{Closed, Mount};
+pub use scroll_view::ScrollView;
+pub use signal::{Computed, Signal};
+pub use stack::{LayoutChanged, Stack, StackDirection};
+pub use text_area::TextArea;
+pub use text_input::TextInput;
 
 type BackResult<B> = Result<<B as Backend>::Output, <B as Backend>::Error>;
 
+/// A closure that updates a widget before it is drawn.
+type Binding<'a, S> = Box<dyn FnMut(&mut S) + 'a>;
+
 /// The system to be drawn into.
 pub trait System {
     /// The backend used to draw widgets.
     type Backend: Backend;
 
-    /// A type for listening to events.
-    type Listener<T>: Listener<T>;
+    /// A type for listening to input events.
+    type Listener: Listener<Event>;
 
     /// The future for waiting until a redraw is requested of a component.
     type RedrawRequested<'a>: Future<Output = ()> + 'a
     where
         Self: 'a;
 
-    /// Draw a widget.
+    /// The future for waiting until this system is closed.
+    type Closed<'a>: Future<Output = ()> + 'a
+    where
+        Self: 'a;
+
+    /// Add a new child to this system.
+    ///
+    /// Returns a key that the child uses to identify itself when drawing.
+    fn add_child(&self) -> usize;
+
+    /// Add a new child that is drawn on top of every other child of this system.
+    ///
+    /// This is used for dialogs and other overlays, which should be added to the system for the
+    /// whole window. By default, this is the same as [`System::add_child`].
+    fn add_overlay(&self) -> usize {
+        self.add_child()
+    }
+
+    /// Remove a child from this system, freeing its slot.
+    ///
+    /// The system lays out its remaining children again. The key is not reused.
+    fn remove_child(&self, child: usize);
+
+    /// Wait until this system is closed.
+    ///
+    /// Once a system is closed, its children should stop drawing into it.
+    fn closed(&self) -> Self::Closed<'_>;
+
+    /// Listen for input events.
+    ///
+    /// Event positions are in window coordinates. Keyboard events used to move focus are not
+    /// delivered.
+    fn listen(&self) -> Self::Listener;
+
+    /// Get the keyboard focus of the window that this system draws into.
+    fn focus(&self) -> &Focus;
+
+    /// Get the pointer capture of the window that this system draws into.
+    ///
+    /// This also routes the input events of the window to its widgets.
+    fn pointer_capture(&self) -> &PointerCapture;
+
+    /// Get the container that events bubble up to from the children of this system.
+    ///
+    /// By default, this is `None`, and events don't bubble past the children.
+    fn event_parent(&self) -> Option<CaptureId> {
+        None
+    }
+
+    /// Draw a child.
     fn draw(
         &self,
-        f: impl FnOnce(&mut Self::Backend, DrawParameters) -> BackResult<Self::Backend>,
+        child: usize,
+        item: impl Drawable<Self::Backend>,
     ) -> (BackResult<Self::Backend>, Self::RedrawRequested<'_>);
+
+    /// Ask for a region of the window to be repainted.
+    ///
+    /// The region is in window coordinates. Every child is asked to redraw during the next frame,
+    /// but only the area covered by the regions invalidated since the last frame has to be
+    /// repainted.
+    fn invalidate(&self, region: Rectangle);
+
+    /// Ask for another frame to be drawn, and wait for it to start.
+    ///
+    /// This is used to run animations. Nothing has to be repainted for the frame, unless it is
+    /// also invalidated.
+    fn request_frame(&self) -> Self::RedrawRequested<'_>;
+
+    /// Get the time that the current frame started at.
+    ///
+    /// This is the clock that animations are measured against.
+    fn frame_time(&self) -> Instant;
+
+    /// Scroll any viewports that this system is drawn inside of, so that a region is visible.
+    ///
+    /// The region is in window coordinates. By default, this does nothing.
+    fn scroll_into_view(&self, region: Rectangle) {
+        let _ = region;
+    }
 }
 
 impl<'x, Sys: System + ?Sized> System for &'x Sys {
     type Backend = Sys::Backend;
-    type Listener<T> = Sys::Listener<T>;
+    type Listener = Sys::Listener;
     type RedrawRequested<'a> = Sys::RedrawRequested<'a> where 'x: 'a;
+    type Closed<'a> = Sys::Closed<'a> where 'x: 'a;
+
+    fn add_child(&self) -> usize {
+        (**self).add_child()
+    }
+
+    fn add_overlay(&self) -> usize {
+        (**self).add_overlay()
+    }
+
+    fn remove_child(&self, child: usize) {
+        (**self).remove_child(child)
+    }
+
+    fn closed(&self) -> Self::Closed<'_> {
+        (**self).closed()
+    }
+
+    fn listen(&self) -> Self::Listener {
+        (**self).listen()
+    }
+
+    fn focus(&self) -> &Focus {
+        (**self).focus()
+    }
+
+    fn pointer_capture(&self) -> &PointerCapture {
+        (**self).pointer_capture()
+    }
+
+    fn event_parent(&self) -> Option<CaptureId> {
+        (**self).event_parent()
+    }
 
     fn draw(
         &self,
-        f: impl FnOnce(&mut Self::Backend, DrawParameters) -> BackResult<Self::Backend>,
+        child: usize,
+        item: impl Drawable<Self::Backend>,
     ) -> (BackResult<Self::Backend>, Self::RedrawRequested<'_>) {
-        (**self).draw(f)
+        (**self).draw(child, item)
+    }
+
+    fn invalidate(&self, region: Rectangle) {
+        (**self).invalidate(region)
+    }
+
+    fn request_frame(&self) -> Self::RedrawRequested<'_> {
+        (**self).request_frame()
+    }
+
+    fn frame_time(&self) -> Instant {
+        (**self).frame_time()
+    }
+
+    fn scroll_into_view(&self, region: Rectangle) {
+        (**self).scroll_into_view(region)
     }
 }
 
+/// Something that can be drawn into a [`System`].
+pub trait Drawable<B: Backend> {
+    /// Measure the size that this item takes up, within the given constraints.
+    ///
+    /// Systems call this before [`Drawable::draw`], so containers can lay out the item.
+    fn measure(&mut self, constraints: BoxConstraints, backend: &mut B) -> Result<Size, B::Error>;
+
+    /// Arrange the item in its region and draw it.
+    ///
+    /// The backend's origin is at the top left corner of the item's region.
+    fn draw(self, backend: &mut B, params: DrawParameters) -> BackResult<B>;
+}
+
 /// Listener for new events.
 pub trait Listener<T> {
     type Stream<'a>: Stream<Item = T> + 'a
@@ -68,21 +245,108 @@ pub trait Listener<T> {
     fn events<'a>(&'a mut self) -> Self::Stream<'a>;
 }
 
-pub struct DrawParameters {}
+/// Parameters used to draw an item.
+///
+/// Window coordinates are measured in logical pixels from the top left corner of the window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DrawParameters {
+    /// The region assigned to the item, in window coordinates.
+    pub region: Rectangle,
+
+    /// The area that drawing is clipped to, in window coordinates.
+    ///
+    /// This is the item's region, cut down by the clip of every container above it.
+    pub clip: Rectangle,
+
+    /// The number of physical pixels per logical pixel.
+    pub scale: f64,
+
+    /// The transform from the item's coordinates to physical pixels in the window.
+    pub transform: Affine,
+
+    /// The area that needs to be repainted, in window coordinates.
+    ///
+    /// If this is `None`, the entire item needs to be repainted.
+    pub damage: Option<Rectangle>,
+}
+
+impl DrawParameters {
+    /// Create the parameters for an item that covers an entire window.
+    pub fn window(size: Size, scale: f64) -> Self {
+        let region = Rectangle {
+            x: 0,
+            y: 0,
+            width: size.width,
+            height: size.height,
+        };
+
+        Self {
+            region,
+            clip: region,
+            scale,
+            transform: Affine::scale(scale),
+            damage: None,
+        }
+    }
+
+    /// Get the parameters for a child item, placed relative to this item's region.
+    pub fn child(&self, relative: Rectangle) -> Self {
+        let region = Rectangle {
+            x: self.region.x + relative.x,
+            y: self.region.y + relative.y,
+            ..relative
+        };
+
+        Self {
+            region,
+            clip: self.clip.intersect(region),
+            scale: self.scale,
+            transform: self.transform * Affine::translate((relative.x as f64, relative.y as f64)),
+            damage: self.damage,
+        }
+    }
+}
+
+/// Input received by a widget's event loop.
+enum Input {
+    /// An input event routed to the widget.
+    Event(Event),
+
+    /// No more input events will be received.
+    Ended,
+
+    /// Keyboard focus has moved.
+    FocusChanged,
+}
 
 /// Wraps a `sunder` widget.
 pub struct Widget<'a, Sys: System + ?Sized, S: RenderedWidget<Sys::Backend>> {
     /// The underlying widget.
-    widget: S,
+    widget: RefCell<S>,
 
     /// The widget's immediate state.
-    state: S::Immediate<'a>,
+    state: RefCell<S::Immediate<'a>>,
 
     /// Cache of widget-specific data.
     cache: RefCell<S::Cache>,
 
-    /// The system to be drawn into.
-    system: Sys,
+    /// The region that the widget was last drawn into.
+    region: Cell<Rectangle>,
+
+    /// Whether the widget had focus when it was last told about it.
+    has_focus: Cell<bool>,
+
+    /// Notified when the widget needs to be redrawn.
+    redraw: event_listener::Event,
+
+    /// Closures that update the widget from signals before it is drawn.
+    bindings: RefCell<Vec<Binding<'a, S>>>,
+
+    /// The signals read during the last draw.
+    dependencies: RefCell<Dependencies>,
+
+    /// The widget's registration in the system to be drawn into.
+    mount: Mount<Sys>,
 }
 
 impl<'a, Sys: System + ?Sized, S: RenderedWidget<Sys::Backend>> Widget<'a, Sys, S> {
@@ -92,26 +356,373 @@ impl<'a, Sys: System + ?Sized, S: RenderedWidget<Sys::Backend>> Widget<'a, Sys,
         Sys: Sized,
     {
         Self {
-            state: <S::Immediate<'a>>::default(),
+            state: RefCell::new(<S::Immediate<'a>>::default()),
             cache: RefCell::new(<S::Cache>::default()),
-            widget,
-            system,
+            region: Cell::new(Rectangle::default()),
+            has_focus: Cell::new(false),
+            redraw: event_listener::Event::new(),
+            bindings: RefCell::new(Vec::new()),
+            dependencies: RefCell::new(Dependencies::default()),
+            mount: if widget.is_focusable() {
+                Mount::focusable(system)
+            } else {
+                Mount::new(system)
+            },
+            widget: RefCell::new(widget),
         }
     }
 
-    pub async fn draw<'x>(&'x self) -> ! {
+    /// Draw the widget every time it needs to be redrawn, and deliver input events to it.
+    ///
+    /// This returns once the widget is unmounted or its system is closed, or if drawing the
+    /// widget fails.
+    pub async fn draw(&self) -> Result<(), Error<Sys::Backend>> {
+        self.draw_with(|_| {}).await
+    }
+
+    /// Draw the widget and deliver input events to it, calling `on_event` after each event.
+    ///
+    /// `on_event` can be used to inspect the immediate state after it has changed.
+    ///
+    /// This returns once the widget is unmounted or its system is closed, or if drawing the
+    /// widget fails.
+    pub async fn draw_with(
+        &self,
+        mut on_event: impl FnMut(&mut S::Immediate<'a>),
+    ) -> Result<(), Error<Sys::Backend>> {
+        if !self.mount.is_mounted() {
+            return Ok(());
+        }
+
+        let handle_events = async {
+            let focus = self.mount.system().focus();
+            let inbox = self
+                .mount
+                .system()
+                .pointer_capture()
+                .inbox(self.mount.capture_id());
+            let mut listener = self.mount.system().listen();
+            let events = listener.events();
+            pin!(events);
+            let mut ended = false;
+
+            loop {
+                let focus_changed = focus.changed();
+
+                if self.update_focus() {
+                    on_event(&mut self.state.borrow_mut());
+                }
+
+                // No more events will come in, but focus can still move.
+                if ended {
+                    focus_changed.await;
+                    continue;
+                }
+
+                let next = async {
+                    inbox
+                        .next(&mut events)
+                        .await
+                        .map_or(Input::Ended, Input::Event)
+                };
+                let focus_changed = async {
+                    focus_changed.await;
+                    Input::FocusChanged
+                };
+
+                match next.or(focus_changed).await {
+                    // A modal dialog has taken input away from the widget. It still has to let go
+                    // of the pointers that the dialog cancelled.
+                    Input::Event(event)
+                        if focus.is_grabbed() && !matches!(event, Event::PointerCancel(_)) => {}
+
+                    Input::Event(event) => {
+                        self.deliver(event);
+                        on_event(&mut self.state.borrow_mut());
+                    }
+
+                    Input::Ended => ended = true,
+                    Input::FocusChanged => {}
+                }
+            }
+        };
+
+        let draw = async {
+            loop {
+                // Draw the widget.
+                let key = self.mount.key();
+                let (res, wait) = self.mount.system().draw(key, self);
+                res.map_err(|err| Error::new(err, type_name::<S>(), key))?;
+
+                // Wait for the next redraw, or for a signal that was read to change.
+                let changed = self.dependencies.take().changed();
+                let changed = async {
+                    self.redraw.listen().or(changed).await;
+                    true
+                };
+                let redraw = async {
+                    wait.await;
+                    false
+                };
+
+                // If the widget changed by itself, repaint it during the next frame.
+                let region = self.region.get();
+                if redraw.or(changed).await && !region.is_empty() {
+                    self.mount.system().invalidate(region);
+                    self.mount.system().request_frame().await;
+                }
+            }
+        };
+
+        let gone = async {
+            self.mount.gone().await;
+            Ok(())
+        };
+
+        gone.or(handle_events.or(draw)).await
+    }
+
+    /// Unmount the widget from its system.
+    ///
+    /// The widget's slot is freed and any running [`Widget::draw`] call returns. The widget can't
+    /// be mounted again.
+    pub fn unmount(&self) {
+        self.mount.unmount();
+    }
+
+    /// Whether the widget is still mounted in its system.
+    pub fn is_mounted(&self) -> bool {
+        self.mount.is_mounted()
+    }
+
+    /// Keep the widget up to date with a closure.
+    ///
+    /// `f` is called every time before the widget is drawn. If it reads any [`Signal`] or
+    /// [`Computed`] value, the widget is redrawn when that value changes.
+    pub fn bind(&self, f: impl FnMut(&mut S) + 'a) {
+        self.bindings.borrow_mut().push(Box::new(f));
+        self.redraw.notify(usize::MAX);
+    }
+
+    /// Whether the widget has keyboard focus.
+    pub fn is_focused(&self) -> bool {
+        self.mount
+            .focus_id()
+            .is_some_and(|id| self.mount.system().focus().is_focused(id))
+    }
+
+    /// Move keyboard focus to this widget.
+    ///
+    /// This does nothing if the widget can't receive focus.
+    pub fn request_focus(&self) {
+        if let Some(id) = self.mount.focus_id() {
+            self.mount.system().focus().set_focus(Some(id));
+        }
+    }
+
+    /// Wait until the widget has keyboard focus.
+    ///
+    /// This never completes if the widget can't receive focus.
+    pub async fn focus(&self) {
         loop {
-            // Draw the widget.
-            let (res, wait) = self.system.draw(|backend, _| {
-                self.widget
-                    .render(&self.state, &mut self.cache.borrow_mut(), backend)
-            });
+            let changed = self.mount.system().focus().changed();
+
+            if self.is_focused() {
+                return;
+            }
+
+            changed.await;
+        }
+    }
+
+    /// Tell the widget about a change in focus since the last time this was called.
+    ///
+    /// Returns `true` if the widget was sent an event.
+    fn update_focus(&self) -> bool {
+        let focused = self.is_focused();
+        if self.has_focus.replace(focused) == focused {
+            return false;
+        }
+
+        // Make sure that the user can see the widget that they are typing into.
+        if focused {
+            self.mount.system().scroll_into_view(self.region.get());
+        }
 
-            // TODO: Handle error
-            let _ = res;
+        self.handle_event(if focused {
+            Event::FocusIn
+        } else {
+            Event::FocusOut
+        });
+        true
+    }
+
+    /// Get the underlying widget.
+    pub fn widget(&self) -> Ref<'_, S> {
+        self.widget.borrow()
+    }
+
+    /// Change the underlying widget, and redraw it.
+    pub fn update<R>(&self, f: impl FnOnce(&mut S) -> R) -> R {
+        let result = f(&mut self.widget.borrow_mut());
+        self.redraw.notify(usize::MAX);
+        result
+    }
+
+    /// Get the widget's immediate state.
+    pub fn state(&self) -> Ref<'_, S::Immediate<'a>> {
+        self.state.borrow()
+    }
+
+    /// Deliver an event to the widget.
+    ///
+    /// The event is translated from window coordinates into the widget's coordinates. If part of
+    /// the widget needs to be redrawn in response, that part of the window is invalidated.
+    ///
+    /// Keyboard events are dropped unless the widget has focus. Pressing a pointer inside of a
+    /// focusable widget moves focus to it. If Tab reaches a widget that can't move focus within
+    /// itself any further, focus moves on to the next widget instead.
+    ///
+    /// Events sent to the window are already delivered to the widgets that they are routed to
+    /// while [`Widget::draw`] is running, so this is only needed for events made up by the
+    /// application. Pointer capture is decided when events are routed, so this doesn't capture
+    /// or release any pointers.
+    pub fn handle_event(&self, event: Event) {
+        let region = self.region.get();
+        self.deliver(event.translate(-region.x as f64, -region.y as f64));
+    }
+
+    /// Deliver an event that is relative to the top left corner of the widget.
+    fn deliver(&self, event: Event) {
+        let region = self.region.get();
 
-            // Wait for the next redraw.
-            wait.await;
+        match event {
+            _ if event.is_keyboard() && !self.is_focused() => return,
+
+            // The window only lets Tab through to widgets that move focus within themselves.
+            Event::KeyDown(KeyEvent {
+                key: Key::Tab,
+                modifiers,
+                ..
+            }) if !self.widget.borrow().steps_focus(!modifiers.shift) => {
+                let focus = self.mount.system().focus();
+                if modifiers.shift {
+                    focus.focus_previous();
+                } else {
+                    focus.focus_next();
+                }
+                return;
+            }
+
+            Event::PointerDown(pointer)
+                if pointer.x >= 0.0
+                    && pointer.y >= 0.0
+                    && pointer.x < region.width as f64
+                    && pointer.y < region.height as f64 =>
+            {
+                self.request_focus();
+            }
+
+            _ => {}
         }
+
+        let damage = self
+            .widget
+            .borrow_mut()
+            .handle_event(&mut self.state.borrow_mut(), event);
+        self.invalidate(damage);
+
+        // Let the window know whether Tab should stay inside of the widget.
+        if let Some(id) = self.mount.focus_id() {
+            let widget = self.widget.borrow();
+            self.mount.system().focus().set_steps_within(
+                id,
+                widget.steps_focus(true),
+                widget.steps_focus(false),
+            );
+        }
+    }
+
+    /// Capture a pointer, so that this widget receives every event for it.
+    ///
+    /// The pointer stays captured until its last button is released or it is cancelled, or until
+    /// [`Widget::release_pointer`] is called.
+    pub fn capture_pointer(&self, kind: PointerKind) {
+        self.mount
+            .system()
+            .pointer_capture()
+            .capture(kind, self.mount.capture_id());
+    }
+
+    /// Release a pointer that this widget has captured.
+    pub fn release_pointer(&self, kind: PointerKind) {
+        self.mount
+            .system()
+            .pointer_capture()
+            .release(kind, self.mount.capture_id());
+    }
+
+    /// Whether this widget has captured a pointer.
+    pub fn has_pointer_capture(&self, kind: PointerKind) -> bool {
+        self.mount.system().pointer_capture().owner(kind) == Some(self.mount.capture_id())
+    }
+
+    /// Ask for part of the widget to be redrawn.
+    ///
+    /// The damage is relative to the top left corner of the widget. Only the damaged area of the
+    /// window is repainted in the next frame.
+    pub fn invalidate(&self, damage: Damage) {
+        if let Damage::Region(region) = damage.within(self.region.get()) {
+            self.mount.system().invalidate(region);
+        }
+    }
+}
+
+impl<'a, Sys: System + ?Sized, S: RenderedWidget<Sys::Backend>> Drawable<Sys::Backend>
+    for &Widget<'a, Sys, S>
+{
+    fn measure(
+        &mut self,
+        constraints: BoxConstraints,
+        backend: &mut Sys::Backend,
+    ) -> Result<Size, <Sys::Backend as Backend>::Error> {
+        let (size, dependencies) = Dependencies::track(|| {
+            let mut widget = self.widget.borrow_mut();
+            for binding in self.bindings.borrow_mut().iter_mut() {
+                binding(&mut widget);
+            }
+
+            widget.measure(constraints, &mut self.cache.borrow_mut(), backend)
+        });
+
+        *self.dependencies.borrow_mut() = dependencies;
+        size
+    }
+
+    fn draw(self, backend: &mut Sys::Backend, params: DrawParameters) -> BackResult<Sys::Backend> {
+        // If the widget has moved, the area that it left behind needs to be repainted too.
+        let old_region = self.region.replace(params.region);
+        if old_region != params.region && !old_region.is_empty() {
+            self.mount
+                .system()
+                .invalidate(old_region.union(params.region));
+        }
+
+        // Route pointer events to where the widget is now.
+        let capture = self.mount.system().pointer_capture();
+        let id = self.mount.capture_id();
+        capture.place(id, params.region, params.clip);
+        capture.set_captures_pointer(id, self.widget.borrow().captures_pointer());
+
+        let (output, dependencies) = Dependencies::track(|| {
+            let mut widget = self.widget.borrow_mut();
+            let mut cache = self.cache.borrow_mut();
+
+            widget.arrange(params.region, &mut cache, backend)?;
+            widget.render(&self.state.borrow(), &mut cache, backend)
+        });
+
+        self.dependencies.borrow_mut().extend(dependencies);
+        output
     }
 }
@@ -0,0 +1,208 @@
+/*
+
+`async-gui` is free software: you can redistribute it and/or modify it under the terms of one of
+the following licenses:
+
+- The GNU Affero General Public License as published by the Free Software Foundation, either version
+  3 of the License, or (at your option) any later version.
+- The Patron License at https://github.com/notgull/async-gui/blob/main/LICENSE-PATRON.md, for
+  sponsors and contributors, who can ignore the copyleft provisions of the GNU AGPL for this project.
+
+`async-gui` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+Public License and the Patron License for more details.
+
+You should have received a copy of the GNU Affero General Public License and the corresponding Patron
+License along with `async-gui`. If not, see <https://www.gnu.org/licenses/>.
+
+*/
+
+//! Registration of children with the system they are drawn into.
+
+use crate::{CaptureId, FocusId, System};
+
+use event_listener::EventListener;
+use futures_lite::future::{self, Or};
+use futures_lite::ready;
+
+use std::cell::Cell;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// A child's registration with the system that it is drawn into.
+///
+/// Mounting a child reserves a slot for it in the system. The slot is freed when the child is
+/// unmounted, either explicitly or by dropping the `Mount`.
+pub struct Mount<Sys: System + ?Sized> {
+    /// The key of the child in the system.
+    key: usize,
+
+    /// The child's place in the tab order, if it can receive focus.
+    focus: Option<FocusId>,
+
+    /// The child's ID for routing events and capturing pointers.
+    capture: CaptureId,
+
+    /// Set once the child has been unmounted.
+    unmounted: Latch,
+
+    /// The system that the child is mounted in.
+    system: Sys,
+}
+
+impl<Sys: System + ?Sized> Mount<Sys> {
+    /// Mount a new child in a system.
+    pub fn new(system: Sys) -> Self
+    where
+        Sys: Sized,
+    {
+        Self {
+            key: system.add_child(),
+            focus: None,
+            capture: system.pointer_capture().register(system.event_parent()),
+            unmounted: Latch::new(),
+            system,
+        }
+    }
+
+    /// Mount a new child in a system, on top of every other child.
+    pub fn overlay(system: Sys) -> Self
+    where
+        Sys: Sized,
+    {
+        Self {
+            key: system.add_overlay(),
+            focus: None,
+            capture: system.pointer_capture().register(system.event_parent()),
+            unmounted: Latch::new(),
+            system,
+        }
+    }
+
+    /// Mount a new child in a system, and add it to the tab order of the system's window.
+    pub fn focusable(system: Sys) -> Self
+    where
+        Sys: Sized,
+    {
+        let mut mount = Self::new(system);
+        mount.focus = Some(mount.system.focus().register());
+        mount
+    }
+
+    /// Get the key of the child in the system.
+    pub fn key(&self) -> usize {
+        self.key
+    }
+
+    /// Get the child's place in the tab order, if it can receive focus.
+    pub fn focus_id(&self) -> Option<FocusId> {
+        self.focus
+    }
+
+    /// Get the child's ID for routing events and capturing pointers.
+    pub fn capture_id(&self) -> CaptureId {
+        self.capture
+    }
+
+    /// Get the system that the child is mounted in.
+    pub fn system(&self) -> &Sys {
+        &self.system
+    }
+
+    /// Whether the child is still mounted.
+    pub fn is_mounted(&self) -> bool {
+        !self.unmounted.is_set()
+    }
+
+    /// Unmount the child, freeing its slot in the system.
+    ///
+    /// This does nothing if the child has already been unmounted.
+    pub fn unmount(&self) {
+        if !self.unmounted.is_set() {
+            self.unmounted.set();
+            self.system.remove_child(self.key);
+
+            if let Some(focus) = self.focus {
+                self.system.focus().unregister(focus);
+            }
+
+            self.system.pointer_capture().unregister(self.capture);
+        }
+    }
+
+    /// Wait until the child is unmounted, or until the system it is mounted in is closed.
+    pub fn gone(&self) -> Or<Closed<'_>, Sys::Closed<'_>> {
+        future::or(self.unmounted.wait(), self.system.closed())
+    }
+}
+
+impl<Sys: System + ?Sized> Drop for Mount<Sys> {
+    fn drop(&mut self) {
+        self.unmount();
+    }
+}
+
+/// A flag that can be set once and waited on.
+pub(crate) struct Latch {
+    /// Whether the flag is set.
+    set: Cell<bool>,
+
+    /// Notified when the flag is set.
+    event: event_listener::Event,
+}
+
+impl Latch {
+    pub(crate) fn new() -> Self {
+        Self {
+            set: Cell::new(false),
+            event: event_listener::Event::new(),
+        }
+    }
+
+    pub(crate) fn is_set(&self) -> bool {
+        self.set.get()
+    }
+
+    pub(crate) fn set(&self) {
+        self.set.set(true);
+        self.event.notify(usize::MAX);
+    }
+
+    pub(crate) fn wait(&self) -> Closed<'_> {
+        Closed {
+            latch: self,
+            listener: None,
+        }
+    }
+}
+
+/// Future that resolves once a child has been unmounted or a system has been closed.
+pub struct Closed<'a> {
+    /// The flag to wait on.
+    latch: &'a Latch,
+
+    /// Waiting for the flag to be set.
+    listener: Option<EventListener>,
+}
+
+impl Future for Closed<'_> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        loop {
+            if self.latch.is_set() {
+                return Poll::Ready(());
+            }
+
+            match &mut self.listener {
+                Some(listener) => {
+                    ready!(Pin::new(listener).poll(cx));
+                    self.listener = None;
+                }
+
+                None => self.listener = Some(self.latch.event.listen()),
+            }
+        }
+    }
+}
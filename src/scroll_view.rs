@@ -0,0 +1,383 @@
+/*
+
+`async-gui` is free software: you can redistribute it and/or modify it under the terms of one of
+the following licenses:
+
+- The GNU Affero General Public License as published by the Free Software Foundation, either version
+  3 of the License, or (at your option) any later version.
+- The Patron License at https://github.com/notgull/async-gui/blob/main/LICENSE-PATRON.md, for
+  sponsors and contributors, who can ignore the copyleft provisions of the GNU AGPL for this project.
+
+`async-gui` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+Public License and the Patron License for more details.
+
+You should have received a copy of the GNU Affero General Public License and the corresponding Patron
+License along with `async-gui`. If not, see <https://www.gnu.org/licenses/>.
+
+*/
+
+//! A container that shows part of its content through a scrollable viewport.
+
+use crate::stack::{LayoutChanged, Slot};
+use crate::{
+    BackResult, CaptureId, Closed, DrawParameters, Drawable, Focus, Listener, Mount,
+    PointerCapture, System,
+};
+use sunder::widgets::{ScrollAxes, ScrollState, Scrollbars};
+use sunder::{Backend, BoxConstraints, Event, Rectangle, RenderedWidget, Size};
+
+use futures_lite::future::{self, Or};
+use futures_lite::{pin, prelude::*};
+
+use std::cell::{Cell, Ref, RefCell};
+use std::time::Instant;
+
+/// A container that shows part of its content through a scrollable viewport.
+///
+/// Children are placed at the top left corner of the content, and can be as long as they want
+/// along the axes that scroll. To lay out several children, place a [`Stack`] inside of the
+/// scroll view. The content is clipped to the viewport, and scrollbars are drawn over it.
+///
+/// The viewport responds to the mouse wheel and to its scrollbars being dragged while
+/// [`ScrollView::run`] is running. Presses on the scrollbars go to the scroll view before its
+/// children, and every event routed to a child bubbles up to the scroll view afterwards. It also
+/// scrolls to show children when they gain focus.
+///
+/// [`Stack`]: crate::Stack
+pub struct ScrollView<Sys: System> {
+    /// The axes that the content can scroll along.
+    axes: ScrollAxes,
+
+    /// The scroll position and scrollbar state.
+    state: RefCell<ScrollState>,
+
+    /// The region of the viewport, in window coordinates.
+    region: Cell<Rectangle>,
+
+    /// Layout information for each child, or `None` if the child has been removed.
+    slots: RefCell<Vec<Option<Slot>>>,
+
+    /// The scroll view's registration in the system to be drawn into.
+    mount: Mount<Sys>,
+}
+
+impl<Sys: System> ScrollView<Sys> {
+    /// Create a new scroll view.
+    pub fn new(system: Sys, axes: ScrollAxes) -> Self {
+        Self {
+            axes,
+            state: RefCell::new(ScrollState::default()),
+            region: Cell::new(Rectangle::default()),
+            slots: RefCell::new(Vec::new()),
+            mount: Mount::new(system),
+        }
+    }
+
+    /// Get the axes that the content can scroll along.
+    pub fn axes(&self) -> ScrollAxes {
+        self.axes
+    }
+
+    /// Get the scroll position and scrollbar state.
+    pub fn state(&self) -> Ref<'_, ScrollState> {
+        self.state.borrow()
+    }
+
+    /// Get how far the content is scrolled, to the right and down.
+    pub fn offset(&self) -> (f64, f64) {
+        self.state.borrow().offset()
+    }
+
+    /// Scroll as little as possible to bring an area of the content into view.
+    ///
+    /// `target` is relative to the top left corner of the content.
+    pub fn scroll_to(&self, target: Rectangle) {
+        let changed = self.state.borrow_mut().scroll_to(target);
+        if changed {
+            self.invalidate();
+        }
+    }
+
+    /// Unmount the scroll view from its system.
+    ///
+    /// This closes the scroll view, so that all of its children stop drawing.
+    pub fn unmount(&self) {
+        self.mount.unmount();
+    }
+
+    /// Scroll in response to input events.
+    ///
+    /// This returns once the scroll view is unmounted or its system is closed.
+    pub async fn run(&self) {
+        let handle_events = async {
+            let inbox = self
+                .mount
+                .system()
+                .pointer_capture()
+                .inbox(self.mount.capture_id());
+            let mut listener = self.mount.system().listen();
+            let events = listener.events();
+            pin!(events);
+
+            while let Some(event) = inbox.next(&mut events).await {
+                // A modal dialog has taken input away from the viewport, but a scrollbar drag
+                // that it cancelled still has to end.
+                let cancelled = matches!(event, Event::PointerCancel(_));
+                if self.mount.system().focus().is_grabbed() && !cancelled {
+                    continue;
+                }
+
+                // Presses on the scrollbars are captured for the scroll view when they are
+                // routed, so dragging keeps working outside of the viewport.
+                let changed = self.state.borrow_mut().handle_event(&event);
+                if changed {
+                    self.invalidate();
+                }
+            }
+
+            // No more events will come in.
+            future::pending::<()>().await;
+        };
+
+        self.mount.gone().or(handle_events).await
+    }
+
+    /// Redraw every child at its new position.
+    fn invalidate(&self) {
+        for slot in self.slots.borrow_mut().iter_mut().flatten() {
+            slot.invalidate();
+        }
+    }
+
+    /// Get the size of the content.
+    fn content_size(slots: &[Option<Slot>]) -> Size {
+        slots
+            .iter()
+            .flatten()
+            .fold(Size::default(), |size, slot| Size {
+                width: size.width.max(slot.size.width),
+                height: size.height.max(slot.size.height),
+            })
+    }
+}
+
+impl<Sys: System> System for ScrollView<Sys>
+where
+    Scrollbars: RenderedWidget<Sys::Backend>,
+{
+    type Backend = Sys::Backend;
+    type Listener = Sys::Listener;
+    type RedrawRequested<'a>
+        = Or<Sys::RedrawRequested<'a>, LayoutChanged<'a>>
+    where
+        Sys: 'a;
+    type Closed<'a>
+        = Or<Closed<'a>, Sys::Closed<'a>>
+    where
+        Sys: 'a;
+
+    fn add_child(&self) -> usize {
+        let mut slots = self.slots.borrow_mut();
+        slots.push(Some(Slot::default()));
+        slots.len() - 1
+    }
+
+    fn remove_child(&self, child: usize) {
+        let mut slots = self.slots.borrow_mut();
+
+        if slots.get_mut(child).and_then(Option::take).is_some() {
+            // The content might have shrunk, so every remaining child needs to be redrawn.
+            for slot in slots.iter_mut().flatten() {
+                slot.invalidate();
+            }
+        }
+    }
+
+    fn closed(&self) -> Self::Closed<'_> {
+        self.mount.gone()
+    }
+
+    fn listen(&self) -> Self::Listener {
+        self.mount.system().listen()
+    }
+
+    fn focus(&self) -> &Focus {
+        self.mount.system().focus()
+    }
+
+    fn pointer_capture(&self) -> &PointerCapture {
+        self.mount.system().pointer_capture()
+    }
+
+    // Events routed to the content bubble up to the viewport.
+    fn event_parent(&self) -> Option<CaptureId> {
+        Some(self.mount.capture_id())
+    }
+
+    fn draw(
+        &self,
+        child: usize,
+        item: impl Drawable<Self::Backend>,
+    ) -> (BackResult<Self::Backend>, Self::RedrawRequested<'_>) {
+        let (result, wait) = self.mount.system().draw(
+            self.mount.key(),
+            ScrollItem {
+                view: self,
+                child,
+                item,
+            },
+        );
+
+        (
+            result,
+            future::or(wait, LayoutChanged::new(&self.slots, child)),
+        )
+    }
+
+    fn invalidate(&self, region: Rectangle) {
+        // Only the part of the content inside of the viewport is shown.
+        self.mount
+            .system()
+            .invalidate(region.intersect(self.region.get()))
+    }
+
+    fn request_frame(&self) -> Self::RedrawRequested<'_> {
+        future::or(
+            self.mount.system().request_frame(),
+            LayoutChanged::never(&self.slots),
+        )
+    }
+
+    fn frame_time(&self) -> Instant {
+        self.mount.system().frame_time()
+    }
+
+    fn scroll_into_view(&self, region: Rectangle) {
+        let viewport = self.region.get();
+        let (x, y) = self.offset();
+
+        // Convert the region from window coordinates to content coordinates.
+        let target = Rectangle {
+            x: region.x - viewport.x + x as i32,
+            y: region.y - viewport.y + y as i32,
+            ..region
+        };
+        self.scroll_to(target);
+
+        // Bring the part of the viewport that now shows the region into view as well.
+        let (x, y) = self.offset();
+        let shown = Rectangle {
+            x: target.x - x as i32 + viewport.x,
+            y: target.y - y as i32 + viewport.y,
+            ..target
+        };
+        self.mount
+            .system()
+            .scroll_into_view(shown.intersect(viewport));
+    }
+}
+
+/// A child being drawn through its scroll view.
+struct ScrollItem<'a, Sys: System, D> {
+    /// The scroll view being drawn into.
+    view: &'a ScrollView<Sys>,
+
+    /// The key of the child.
+    child: usize,
+
+    /// The child's item.
+    item: D,
+}
+
+impl<Sys: System, D: Drawable<Sys::Backend>> Drawable<Sys::Backend> for ScrollItem<'_, Sys, D>
+where
+    Scrollbars: RenderedWidget<Sys::Backend>,
+{
+    fn measure(
+        &mut self,
+        constraints: BoxConstraints,
+        backend: &mut Sys::Backend,
+    ) -> Result<Size, <Sys::Backend as Backend>::Error> {
+        let child_constraints = self.view.axes.content_constraints(constraints);
+        let size = self.item.measure(child_constraints, backend)?;
+        let mut slots = self.view.slots.borrow_mut();
+
+        if let Some(slot) = &mut slots[self.child] {
+            slot.size = size;
+        }
+
+        Ok(constraints.constrain(ScrollView::<Sys>::content_size(&slots)))
+    }
+
+    fn draw(self, backend: &mut Sys::Backend, params: DrawParameters) -> BackResult<Sys::Backend> {
+        let viewport = params.region;
+        self.view.region.set(viewport);
+
+        let content = {
+            let mut slots = self.view.slots.borrow_mut();
+
+            // We are drawing now, so we don't need another redraw for the layout.
+            if let Some(slot) = &mut slots[self.child] {
+                slot.invalidated = false;
+            }
+
+            ScrollView::<Sys>::content_size(&slots)
+        };
+
+        // Keep the offset in range, now that the sizes are known.
+        let size = Size {
+            width: viewport.width,
+            height: viewport.height,
+        };
+        let state = {
+            let mut state = self.view.state.borrow_mut();
+            state.set_sizes(size, content);
+            state.clone()
+        };
+
+        // Presses on the scrollbars go to the scroll view, even over the content.
+        let capture = self.view.mount.system().pointer_capture();
+        let id = self.view.mount.capture_id();
+        capture.place(id, viewport, params.clip);
+        capture.set_intercepts(
+            id,
+            [true, false]
+                .into_iter()
+                .filter_map(|vertical| state.scrollbar(vertical))
+                .map(|(track, _)| Rectangle {
+                    x: viewport.x + track.x,
+                    y: viewport.y + track.y,
+                    ..track
+                }),
+        );
+
+        let (x, y) = state.offset();
+        let bounds = Rectangle {
+            x: 0,
+            y: 0,
+            width: size.width,
+            height: size.height,
+        };
+        let region = Rectangle {
+            x: -x as i32,
+            y: -y as i32,
+            width: content.width,
+            height: content.height,
+        };
+
+        let params = params.child(region);
+        let item = self.item;
+        backend.with_region(bounds, move |backend| {
+            let output = backend.with_region(region, move |backend| item.draw(backend, params))?;
+
+            // Draw the scrollbars over the content.
+            let mut scrollbars = Scrollbars;
+            let mut cache = Default::default();
+            scrollbars.measure(BoxConstraints::tight(size), &mut cache, backend)?;
+            scrollbars.render(&state, &mut cache, backend)?;
+
+            Ok(output)
+        })
+    }
+}
@@ -0,0 +1,482 @@
+/*
+
+`async-gui` is free software: you can redistribute it and/or modify it under the terms of one of
+the following licenses:
+
+- The GNU Affero General Public License as published by the Free Software Foundation, either version
+  3 of the License, or (at your option) any later version.
+- The Patron License at https://github.com/notgull/async-gui/blob/main/LICENSE-PATRON.md, for
+  sponsors and contributors, who can ignore the copyleft provisions of the GNU AGPL for this project.
+
+`async-gui` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+Public License and the Patron License for more details.
+
+You should have received a copy of the GNU Affero General Public License and the corresponding Patron
+License along with `async-gui`. If not, see <https://www.gnu.org/licenses/>.
+
+*/
+
+//! Reactive state that schedules redraws when it changes.
+//!
+//! A [`Signal`] holds a value that can be written to from anywhere in the application. Reading a
+//! signal while a [`Widget`] is being measured or rendered subscribes the widget to it, so that
+//! the widget is redrawn the next time the signal is written to. A [`Computed`] derives a value
+//! from other signals and is recomputed lazily when any of them change.
+//!
+//! [`Widget`]: crate::Widget
+
+use event_listener::EventListener;
+
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+
+thread_local! {
+    /// The dependencies being collected by the innermost call to [`Dependencies::track`].
+    static TRACKER: RefCell<Option<Vec<Dependency>>> = const { RefCell::new(None) };
+}
+
+/// A value that redraws the widgets that read it when it is written to.
+///
+/// Cloning a signal creates another handle to the same value.
+pub struct Signal<T> {
+    inner: Rc<SignalInner<T>>,
+}
+
+struct SignalInner<T> {
+    /// The current value.
+    value: RefCell<T>,
+
+    /// Notifies readers of changes.
+    source: Rc<Source>,
+}
+
+impl<T> Clone for Signal<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Signal<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Signal")
+            .field(&self.inner.value.borrow())
+            .finish()
+    }
+}
+
+impl<T: Default> Default for Signal<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T> Signal<T> {
+    /// Create a new signal.
+    pub fn new(value: T) -> Self {
+        Self {
+            inner: Rc::new(SignalInner {
+                value: RefCell::new(value),
+                source: Rc::new(Source::new()),
+            }),
+        }
+    }
+
+    /// Get a copy of the current value.
+    pub fn get(&self) -> T
+    where
+        T: Clone,
+    {
+        self.with(T::clone)
+    }
+
+    /// Run a closure with a reference to the current value.
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        self.inner.source.record();
+        f(&self.inner.value.borrow())
+    }
+
+    /// Replace the current value.
+    pub fn set(&self, value: T) {
+        self.update(|current| *current = value);
+    }
+
+    /// Modify the current value in place.
+    pub fn update(&self, f: impl FnOnce(&mut T)) {
+        f(&mut self.inner.value.borrow_mut());
+        self.inner.source.bump();
+    }
+
+    /// Wait until the signal is written to.
+    pub async fn changed(&self) {
+        let version = self.inner.source.version.get();
+
+        loop {
+            let listener = self.inner.source.changed.listen();
+
+            if self.inner.source.version.get() != version {
+                return;
+            }
+
+            listener.await;
+        }
+    }
+}
+
+/// A value derived from other signals.
+///
+/// The value is computed the first time it is read, and computed again when it is read after
+/// any signal it depends on has changed. Reading it while a widget is drawn subscribes the widget
+/// to those signals.
+///
+/// Cloning a computed value creates another handle to the same value.
+pub struct Computed<T> {
+    inner: Rc<ComputedInner<T>>,
+}
+
+struct ComputedInner<T> {
+    /// Computes the value.
+    compute: Box<dyn Fn() -> T>,
+
+    /// The last computed value.
+    value: RefCell<Option<T>>,
+
+    /// The signals that the last computed value was derived from.
+    dependencies: RefCell<Dependencies>,
+}
+
+impl<T> Clone for Computed<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Computed<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Computed")
+            .field(&self.inner.value.borrow())
+            .finish()
+    }
+}
+
+impl<T> Computed<T> {
+    /// Create a new value derived by a closure.
+    ///
+    /// Every signal read by `compute` becomes a dependency of this value.
+    pub fn new(compute: impl Fn() -> T + 'static) -> Self {
+        Self {
+            inner: Rc::new(ComputedInner {
+                compute: Box::new(compute),
+                value: RefCell::new(None),
+                dependencies: RefCell::new(Dependencies::default()),
+            }),
+        }
+    }
+
+    /// Get a copy of the current value.
+    pub fn get(&self) -> T
+    where
+        T: Clone,
+    {
+        self.with(T::clone)
+    }
+
+    /// Run a closure with a reference to the current value.
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        let inner = &self.inner;
+
+        if inner.value.borrow().is_none() || inner.dependencies.borrow().is_stale() {
+            let (value, dependencies) = Dependencies::track(|| (inner.compute)());
+            *inner.value.borrow_mut() = Some(value);
+            *inner.dependencies.borrow_mut() = dependencies;
+        }
+
+        // Whoever reads this value depends on the same signals that it does.
+        inner.dependencies.borrow().record();
+
+        let value = inner.value.borrow();
+        f(value.as_ref().unwrap())
+    }
+
+    /// Wait until any signal this value depends on is written to.
+    pub async fn changed(&self) {
+        let dependencies = self.inner.dependencies.borrow().clone();
+        dependencies.changed().await
+    }
+}
+
+/// The change notifications of a signal.
+struct Source {
+    /// Incremented every time the signal is written to.
+    version: Cell<u64>,
+
+    /// Notified every time the signal is written to.
+    changed: event_listener::Event,
+}
+
+impl Source {
+    fn new() -> Self {
+        Self {
+            version: Cell::new(0),
+            changed: event_listener::Event::new(),
+        }
+    }
+
+    /// Mark the signal as changed.
+    fn bump(&self) {
+        self.version.set(self.version.get().wrapping_add(1));
+        self.changed.notify(usize::MAX);
+    }
+
+    /// Record that the signal was read by the current tracking scope.
+    fn record(self: &Rc<Self>) {
+        Dependency {
+            source: self.clone(),
+            version: self.version.get(),
+        }
+        .record();
+    }
+}
+
+/// A signal read at a specific version.
+#[derive(Clone)]
+struct Dependency {
+    source: Rc<Source>,
+    version: u64,
+}
+
+impl Dependency {
+    fn is_stale(&self) -> bool {
+        self.source.version.get() != self.version
+    }
+
+    fn record(self) {
+        TRACKER.with(|tracker| {
+            if let Some(dependencies) = &mut *tracker.borrow_mut() {
+                if !dependencies
+                    .iter()
+                    .any(|dep| Rc::ptr_eq(&dep.source, &self.source))
+                {
+                    dependencies.push(self);
+                }
+            }
+        });
+    }
+}
+
+/// The signals read while running a closure.
+#[derive(Clone, Default)]
+pub(crate) struct Dependencies {
+    dependencies: Vec<Dependency>,
+}
+
+impl Dependencies {
+    /// Run a closure, collecting the signals that it reads.
+    pub(crate) fn track<R>(f: impl FnOnce() -> R) -> (R, Self) {
+        let outer = TRACKER.with(|tracker| tracker.borrow_mut().replace(Vec::new()));
+        let result = f();
+        let dependencies =
+            TRACKER.with(|tracker| std::mem::replace(&mut *tracker.borrow_mut(), outer));
+
+        (
+            result,
+            Self {
+                dependencies: dependencies.unwrap_or_default(),
+            },
+        )
+    }
+
+    /// Add the signals collected by another call to [`Dependencies::track`].
+    pub(crate) fn extend(&mut self, other: Self) {
+        for dep in other.dependencies {
+            if !self
+                .dependencies
+                .iter()
+                .any(|existing| Rc::ptr_eq(&existing.source, &dep.source))
+            {
+                self.dependencies.push(dep);
+            }
+        }
+    }
+
+    /// Whether any of the signals have changed since they were read.
+    pub(crate) fn is_stale(&self) -> bool {
+        self.dependencies.iter().any(Dependency::is_stale)
+    }
+
+    /// Record these signals as read by the current tracking scope.
+    fn record(&self) {
+        for dep in &self.dependencies {
+            dep.clone().record();
+        }
+    }
+
+    /// Wait until any of the signals change.
+    ///
+    /// This never resolves if there are no signals.
+    pub(crate) fn changed(self) -> Changed {
+        Changed {
+            dependencies: self,
+            listeners: Vec::new(),
+        }
+    }
+}
+
+/// Future that resolves once any signal in a set of [`Dependencies`] has changed.
+pub(crate) struct Changed {
+    /// The signals to wait on.
+    dependencies: Dependencies,
+
+    /// Listeners for every signal.
+    listeners: Vec<EventListener>,
+}
+
+impl Future for Changed {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+
+        loop {
+            if this.dependencies.is_stale() {
+                return Poll::Ready(());
+            }
+
+            if this.listeners.is_empty() {
+                if this.dependencies.dependencies.is_empty() {
+                    return Poll::Pending;
+                }
+
+                // Check the versions again after registering the listeners.
+                this.listeners = this
+                    .dependencies
+                    .dependencies
+                    .iter()
+                    .map(|dep| dep.source.changed.listen())
+                    .collect();
+                continue;
+            }
+
+            let notified = this
+                .listeners
+                .iter_mut()
+                .any(|listener| Pin::new(listener).poll(cx).is_ready());
+
+            if !notified {
+                return Poll::Pending;
+            }
+
+            this.listeners.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_lite::future;
+
+    /// Create a computed value that counts how many times it is computed.
+    fn counted<T: 'static>(compute: impl Fn() -> T + 'static) -> (Computed<T>, Rc<Cell<usize>>) {
+        let count = Rc::new(Cell::new(0));
+        let computed = Computed::new({
+            let count = count.clone();
+            move || {
+                count.set(count.get() + 1);
+                compute()
+            }
+        });
+
+        (computed, count)
+    }
+
+    #[test]
+    fn computed_is_recomputed_when_stale() {
+        let a = Signal::new(2);
+        let b = Signal::new(3);
+        let (product, count) = counted({
+            let (a, b) = (a.clone(), b.clone());
+            move || a.get() * b.get()
+        });
+
+        // Nothing is computed until the value is read, and then only once.
+        assert_eq!(count.get(), 0);
+        assert_eq!(product.get(), 6);
+        assert_eq!(product.get(), 6);
+        assert_eq!(count.get(), 1);
+
+        // Writing to a dependency makes the value stale, even if it is written back the same.
+        b.set(3);
+        assert_eq!(product.get(), 6);
+        assert_eq!(count.get(), 2);
+
+        a.update(|a| *a += 1);
+        assert_eq!(product.get(), 9);
+        assert_eq!(count.get(), 3);
+    }
+
+    #[test]
+    fn computed_only_depends_on_what_it_last_read() {
+        let flag = Signal::new(true);
+        let a = Signal::new("a");
+        let b = Signal::new("b");
+        let (choice, count) = counted({
+            let (flag, a, b) = (flag.clone(), a.clone(), b.clone());
+            move || if flag.get() { a.get() } else { b.get() }
+        });
+
+        assert_eq!(choice.get(), "a");
+        b.set("B");
+        assert_eq!(choice.get(), "a");
+        assert_eq!(count.get(), 1);
+
+        flag.set(false);
+        assert_eq!(choice.get(), "B");
+        a.set("A");
+        assert_eq!(choice.get(), "B");
+        assert_eq!(count.get(), 2);
+    }
+
+    #[test]
+    fn reading_computed_tracks_its_signals() {
+        let signal = Signal::new(1);
+        let doubled = Computed::new({
+            let signal = signal.clone();
+            move || signal.get() * 2
+        });
+
+        let (value, dependencies) = Dependencies::track(|| doubled.get());
+        assert_eq!(value, 2);
+        assert!(!dependencies.is_stale());
+
+        signal.set(5);
+        assert!(dependencies.is_stale());
+    }
+
+    #[test]
+    fn changed_waits_for_a_write() {
+        let signal = Signal::new(0);
+        let doubled = Computed::new({
+            let signal = signal.clone();
+            move || signal.get() * 2
+        });
+        doubled.get();
+
+        let mut changed = Box::pin(signal.changed());
+        let mut computed_changed = Box::pin(doubled.changed());
+        assert!(future::block_on(future::poll_once(&mut changed)).is_none());
+        assert!(future::block_on(future::poll_once(&mut computed_changed)).is_none());
+
+        signal.set(1);
+        assert!(future::block_on(future::poll_once(&mut changed)).is_some());
+        assert!(future::block_on(future::poll_once(&mut computed_changed)).is_some());
+    }
+}
@@ -0,0 +1,374 @@
+/*
+
+`async-gui` is free software: you can redistribute it and/or modify it under the terms of one of
+the following licenses:
+
+- The GNU Affero General Public License as published by the Free Software Foundation, either version
+  3 of the License, or (at your option) any later version.
+- The Patron License at https://github.com/notgull/async-gui/blob/main/LICENSE-PATRON.md, for
+  sponsors and contributors, who can ignore the copyleft provisions of the GNU AGPL for this project.
+
+`async-gui` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+Public License and the Patron License for more details.
+
+You should have received a copy of the GNU Affero General Public License and the corresponding Patron
+License along with `async-gui`. If not, see <https://www.gnu.org/licenses/>.
+
+*/
+
+//! A container that lays out its children one after another.
+
+use crate::{
+    BackResult, CaptureId, Closed, DrawParameters, Drawable, Focus, Mount, PointerCapture, System,
+};
+use sunder::{Backend, BoxConstraints, Rectangle, Size};
+
+use futures_lite::future::{self, Or};
+
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll, Waker};
+use std::time::Instant;
+
+/// The direction that a [`Stack`] lays out its children in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StackDirection {
+    /// Children are placed from top to bottom.
+    Vertical,
+
+    /// Children are placed from left to right.
+    Horizontal,
+}
+
+impl StackDirection {
+    /// Get the length of a size along the main axis.
+    fn main(self, size: Size) -> u32 {
+        match self {
+            StackDirection::Vertical => size.height,
+            StackDirection::Horizontal => size.width,
+        }
+    }
+
+    /// Get the length of a size along the cross axis.
+    fn cross(self, size: Size) -> u32 {
+        match self {
+            StackDirection::Vertical => size.width,
+            StackDirection::Horizontal => size.height,
+        }
+    }
+
+    /// Get the constraints for a child, given the constraints for the whole stack.
+    ///
+    /// Children can be as long as they want along the main axis, but can't be wider than the
+    /// stack along the cross axis.
+    fn child_constraints(self, constraints: BoxConstraints) -> BoxConstraints {
+        BoxConstraints::loose(self.size(u32::MAX, self.cross(constraints.max)))
+    }
+
+    /// Create a size from its main and cross lengths.
+    fn size(self, main: u32, cross: u32) -> Size {
+        match self {
+            StackDirection::Vertical => Size {
+                width: cross,
+                height: main,
+            },
+            StackDirection::Horizontal => Size {
+                width: main,
+                height: cross,
+            },
+        }
+    }
+}
+
+/// A container that lays out its children one after another.
+///
+/// Each child reports its size when it is drawn. If that size changes, every child after it is
+/// asked to redraw in its new position.
+///
+/// The stack is closed to its children once it is unmounted or its own system is closed.
+pub struct Stack<Sys: System> {
+    /// The direction to lay out children in.
+    direction: StackDirection,
+
+    /// Layout information for each child, or `None` if the child has been removed.
+    slots: RefCell<Vec<Option<Slot>>>,
+
+    /// The stack's registration in the system to be drawn into.
+    mount: Mount<Sys>,
+}
+
+/// Layout information for a single child.
+#[derive(Default)]
+pub(crate) struct Slot {
+    /// The last size reported by the child.
+    pub(crate) size: Size,
+
+    /// Whether the child needs to be redrawn because of a layout change.
+    pub(crate) invalidated: bool,
+
+    /// The waker for the child's redraw future.
+    waker: Option<Waker>,
+}
+
+impl Slot {
+    /// Ask the child to redraw because of a layout change.
+    pub(crate) fn invalidate(&mut self) {
+        self.invalidated = true;
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl<Sys: System> Stack<Sys> {
+    /// Create a new stack.
+    pub fn new(system: Sys, direction: StackDirection) -> Self {
+        Self {
+            direction,
+            slots: RefCell::new(Vec::new()),
+            mount: Mount::new(system),
+        }
+    }
+
+    /// Get the direction that this stack lays out its children in.
+    pub fn direction(&self) -> StackDirection {
+        self.direction
+    }
+
+    /// Unmount the stack from its system.
+    ///
+    /// This closes the stack, so that all of its children stop drawing.
+    pub fn unmount(&self) {
+        self.mount.unmount();
+    }
+
+    /// Get the region of a child, relative to the top left corner of the stack.
+    fn child_region(&self, slots: &[Option<Slot>], child: usize) -> Rectangle {
+        let offset = slots[..child].iter().flatten().fold(0u32, |offset, slot| {
+            offset.saturating_add(self.direction.main(slot.size))
+        });
+        let size = slots[child]
+            .as_ref()
+            .map_or_else(Size::default, |slot| slot.size);
+
+        // Children past an unbounded child are pushed as far as they can go.
+        let offset = offset.min(i32::MAX as u32) as i32;
+        let (x, y) = match self.direction {
+            StackDirection::Vertical => (0, offset),
+            StackDirection::Horizontal => (offset, 0),
+        };
+
+        Rectangle {
+            x,
+            y,
+            width: size.width,
+            height: size.height,
+        }
+    }
+
+    /// Get the total size of the stack.
+    fn total_size(&self, slots: &[Option<Slot>]) -> Size {
+        let (main, cross) = slots
+            .iter()
+            .flatten()
+            .fold((0u32, 0), |(main, cross), slot| {
+                (
+                    main.saturating_add(self.direction.main(slot.size)),
+                    cross.max(self.direction.cross(slot.size)),
+                )
+            });
+
+        self.direction.size(main, cross)
+    }
+}
+
+impl<Sys: System> System for Stack<Sys> {
+    type Backend = Sys::Backend;
+    type Listener = Sys::Listener;
+    type RedrawRequested<'a>
+        = Or<Sys::RedrawRequested<'a>, LayoutChanged<'a>>
+    where
+        Sys: 'a;
+    type Closed<'a>
+        = Or<Closed<'a>, Sys::Closed<'a>>
+    where
+        Sys: 'a;
+
+    fn add_child(&self) -> usize {
+        let mut slots = self.slots.borrow_mut();
+        slots.push(Some(Slot::default()));
+        slots.len() - 1
+    }
+
+    fn remove_child(&self, child: usize) {
+        let mut slots = self.slots.borrow_mut();
+
+        if slots.get_mut(child).and_then(Option::take).is_some() {
+            // The stack has shrunk, so every remaining child needs to be redrawn.
+            for slot in slots.iter_mut().flatten() {
+                slot.invalidate();
+            }
+        }
+    }
+
+    fn closed(&self) -> Self::Closed<'_> {
+        self.mount.gone()
+    }
+
+    fn listen(&self) -> Self::Listener {
+        self.mount.system().listen()
+    }
+
+    fn focus(&self) -> &Focus {
+        self.mount.system().focus()
+    }
+
+    fn pointer_capture(&self) -> &PointerCapture {
+        self.mount.system().pointer_capture()
+    }
+
+    fn event_parent(&self) -> Option<CaptureId> {
+        self.mount.system().event_parent()
+    }
+
+    fn draw(
+        &self,
+        child: usize,
+        item: impl Drawable<Self::Backend>,
+    ) -> (BackResult<Self::Backend>, Self::RedrawRequested<'_>) {
+        let (result, wait) = self.mount.system().draw(
+            self.mount.key(),
+            StackItem {
+                stack: self,
+                child,
+                item,
+            },
+        );
+
+        (
+            result,
+            future::or(wait, LayoutChanged::new(&self.slots, child)),
+        )
+    }
+
+    fn invalidate(&self, region: Rectangle) {
+        self.mount.system().invalidate(region)
+    }
+
+    fn request_frame(&self) -> Self::RedrawRequested<'_> {
+        future::or(
+            self.mount.system().request_frame(),
+            LayoutChanged::never(&self.slots),
+        )
+    }
+
+    fn frame_time(&self) -> Instant {
+        self.mount.system().frame_time()
+    }
+
+    fn scroll_into_view(&self, region: Rectangle) {
+        self.mount.system().scroll_into_view(region)
+    }
+}
+
+/// A child being drawn through its stack.
+struct StackItem<'a, Sys: System, D> {
+    /// The stack being drawn into.
+    stack: &'a Stack<Sys>,
+
+    /// The key of the child.
+    child: usize,
+
+    /// The child's item.
+    item: D,
+}
+
+impl<Sys: System, D: Drawable<Sys::Backend>> Drawable<Sys::Backend> for StackItem<'_, Sys, D> {
+    fn measure(
+        &mut self,
+        constraints: BoxConstraints,
+        backend: &mut Sys::Backend,
+    ) -> Result<Size, <Sys::Backend as Backend>::Error> {
+        let child_constraints = self.stack.direction.child_constraints(constraints);
+        let size = self.item.measure(child_constraints, backend)?;
+        let mut slots = self.stack.slots.borrow_mut();
+
+        if let Some(slot) = &mut slots[self.child] {
+            if slot.size != size {
+                slot.size = size;
+
+                // Every child after this one has been moved.
+                for slot in slots[self.child + 1..].iter_mut().flatten() {
+                    slot.invalidate();
+                }
+            }
+        }
+
+        Ok(constraints.constrain(self.stack.total_size(&slots)))
+    }
+
+    fn draw(self, backend: &mut Sys::Backend, params: DrawParameters) -> BackResult<Sys::Backend> {
+        let region = {
+            let mut slots = self.stack.slots.borrow_mut();
+
+            // We are drawing now, so we don't need another redraw for the layout.
+            if let Some(slot) = &mut slots[self.child] {
+                slot.invalidated = false;
+            }
+
+            self.stack.child_region(&slots, self.child)
+        };
+
+        let params = params.child(region);
+        let item = self.item;
+        backend.with_region(region, move |backend| item.draw(backend, params))
+    }
+}
+
+/// Future that resolves when a child of a [`Stack`] has been moved by a layout change.
+pub struct LayoutChanged<'a> {
+    /// The slots of the stack.
+    slots: &'a RefCell<Vec<Option<Slot>>>,
+
+    /// The key of the child.
+    child: usize,
+}
+
+impl<'a> LayoutChanged<'a> {
+    /// Wait for the child with the given key to be moved.
+    pub(crate) fn new(slots: &'a RefCell<Vec<Option<Slot>>>, child: usize) -> Self {
+        Self { slots, child }
+    }
+
+    /// A future that never resolves, for waits that aren't tied to a child.
+    pub(crate) fn never(slots: &'a RefCell<Vec<Option<Slot>>>) -> Self {
+        Self {
+            slots,
+            child: usize::MAX,
+        }
+    }
+}
+
+impl Future for LayoutChanged<'_> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut slots = self.slots.borrow_mut();
+        let slot = match slots.get_mut(self.child) {
+            Some(Some(slot)) => slot,
+
+            // The child has been removed, so it will never be moved again.
+            _ => return Poll::Pending,
+        };
+
+        if slot.invalidated {
+            slot.invalidated = false;
+            Poll::Ready(())
+        } else {
+            slot.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
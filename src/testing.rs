@@ -0,0 +1,913 @@
+/*
+
+`async-gui` is free software: you can redistribute it and/or modify it under the terms of one of
+the following licenses:
+
+- The GNU Affero General Public License as published by the Free Software Foundation, either version
+  3 of the License, or (at your option) any later version.
+- The Patron License at https://github.com/notgull/async-gui/blob/main/LICENSE-PATRON.md, for
+  sponsors and contributors, who can ignore the copyleft provisions of the GNU AGPL for this project.
+
+`async-gui` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+Public License and the Patron License for more details.
+
+You should have received a copy of the GNU Affero General Public License and the corresponding Patron
+License along with `async-gui`. If not, see <https://www.gnu.org/licenses/>.
+
+*/
+
+//! Tools for testing widgets without a display server.
+//!
+//! [`MockSystem`] is a [`System`] that draws into a [`RecordingBackend`] by default, or into any
+//! other backend that the widgets under test can be drawn into. Redraws and input events only
+//! happen when the test asks for them, so frames can be driven deterministically with
+//! `futures_lite::future::block_on`.
+
+use crate::mount::Latch;
+use crate::{
+    BackResult, Closed, DrawParameters, Drawable, Focus, Listener, PointerCapture, System,
+};
+use sunder::{Backend, BoxConstraints, Event, Rectangle, Size};
+
+use event_listener::EventListener;
+use futures_lite::prelude::*;
+
+use std::cell::{Cell, RefCell, RefMut};
+use std::collections::VecDeque;
+use std::fmt;
+use std::pin::Pin;
+use std::rc::{Rc, Weak};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+/// A command recorded by a [`RecordingBackend`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Command {
+    /// A child of the system was drawn, after reporting this size.
+    Draw { child: usize, size: Size },
+
+    /// A child was removed from the system.
+    RemoveChild { child: usize },
+
+    /// The origin was moved to the top left corner of a region, and drawing was clipped to it.
+    PushRegion(Rectangle),
+
+    /// The region before the last [`Command::PushRegion`] was restored.
+    PopRegion,
+
+    /// A widget recorded a command of its own.
+    Custom(String),
+}
+
+/// An error produced by a widget drawing into a [`RecordingBackend`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockError(pub String);
+
+impl fmt::Display for MockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for MockError {}
+
+/// A backend that records what is drawn into it.
+///
+/// Widgets written for testing implement `RenderedWidget<RecordingBackend>` and call
+/// [`RecordingBackend::record`] to describe what they draw. To test the real widgets, draw them
+/// into a [`MockSystem`] with a backend that they support instead.
+#[derive(Debug, Default)]
+pub struct RecordingBackend {
+    /// The commands recorded so far, shared with the system being drawn into.
+    commands: Rc<RefCell<Vec<Command>>>,
+}
+
+impl RecordingBackend {
+    /// Create a new, empty recording backend.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a custom command.
+    pub fn record(&mut self, command: impl Into<String>) {
+        self.commands
+            .borrow_mut()
+            .push(Command::Custom(command.into()));
+    }
+
+    /// Get the commands recorded so far.
+    pub fn commands(&self) -> Vec<Command> {
+        self.commands.borrow().clone()
+    }
+
+    /// Take the commands recorded so far, leaving the backend empty.
+    pub fn take_commands(&mut self) -> Vec<Command> {
+        self.commands.take()
+    }
+}
+
+impl Backend for RecordingBackend {
+    type Error = MockError;
+    type Output = ();
+
+    fn with_region<R>(
+        &mut self,
+        region: Rectangle,
+        f: impl FnOnce(&mut Self) -> Result<R, Self::Error>,
+    ) -> Result<R, Self::Error> {
+        self.commands.borrow_mut().push(Command::PushRegion(region));
+        let result = f(self);
+        self.commands.borrow_mut().push(Command::PopRegion);
+        result
+    }
+}
+
+/// A [`System`] that records draw calls instead of displaying them.
+///
+/// The system records when each child is drawn and removed. By default, it draws into a
+/// [`RecordingBackend`], which adds the commands of the widgets to the same recording. Use
+/// [`MockSystem::with_backend`] to draw into another backend, such as a
+/// `sunder::piet::PietBackend` over a `sunder::piet::testing::RecordingContext`, and inspect it
+/// with [`MockSystem::backend`].
+pub struct MockSystem<B = RecordingBackend> {
+    /// The backend being drawn into.
+    backend: RefCell<B>,
+
+    /// The commands recorded so far.
+    commands: Rc<RefCell<Vec<Command>>>,
+
+    /// The size of the mock window.
+    size: Cell<Size>,
+
+    /// The number of redraws that have been requested.
+    frame: Cell<u64>,
+
+    /// The time reported to animations.
+    time: Cell<Instant>,
+
+    /// Notified when a redraw is requested.
+    redraw: event_listener::Event,
+
+    /// The regions invalidated since they were last taken.
+    invalidated: RefCell<Vec<Rectangle>>,
+
+    /// The event queues of the listeners.
+    listeners: RefCell<Vec<Weak<RefCell<VecDeque<Event>>>>>,
+
+    /// Notified when an event is sent.
+    event_sent: Rc<event_listener::Event>,
+
+    /// The number of children added to this system.
+    children: Cell<usize>,
+
+    /// Set once the mock window is closed.
+    closed: Latch,
+
+    /// The keyboard focus of the mock window.
+    focus: Focus,
+
+    /// The pointer capture of the mock window.
+    capture: PointerCapture,
+}
+
+impl MockSystem {
+    /// Create a new mock system with a window of the given size.
+    pub fn new(size: Size) -> Self {
+        let backend = RecordingBackend::new();
+        let commands = backend.commands.clone();
+
+        Self {
+            commands,
+            ..Self::with_backend(size, backend)
+        }
+    }
+}
+
+impl<B: Backend> MockSystem<B> {
+    /// Create a new mock system with a window of the given size, that draws into a backend.
+    pub fn with_backend(size: Size, backend: B) -> Self {
+        Self {
+            backend: RefCell::new(backend),
+            commands: Rc::default(),
+            size: Cell::new(size),
+            frame: Cell::new(0),
+            time: Cell::new(Instant::now()),
+            redraw: event_listener::Event::new(),
+            invalidated: RefCell::new(Vec::new()),
+            listeners: RefCell::new(Vec::new()),
+            event_sent: Rc::new(event_listener::Event::new()),
+            children: Cell::new(0),
+            closed: Latch::new(),
+            focus: Focus::new(),
+            capture: PointerCapture::new(),
+        }
+    }
+
+    /// Get the size of the mock window.
+    pub fn size(&self) -> Size {
+        self.size.get()
+    }
+
+    /// Resize the mock window.
+    ///
+    /// This does not request a redraw by itself.
+    pub fn set_size(&self, size: Size) {
+        self.size.set(size);
+    }
+
+    /// Get the backend being drawn into.
+    pub fn backend(&self) -> RefMut<'_, B> {
+        self.backend.borrow_mut()
+    }
+
+    /// Wake up every item waiting for a redraw, including every frame that was requested.
+    ///
+    /// This is the only way that widgets are redrawn.
+    pub fn request_redraw(&self) {
+        self.frame.set(self.frame.get() + 1);
+        self.redraw.notify(usize::MAX);
+    }
+
+    /// Take the regions that have been invalidated since this was last called.
+    ///
+    /// Invalidating a region doesn't redraw anything until [`MockSystem::request_redraw`] is
+    /// called.
+    pub fn take_invalidated(&self) -> Vec<Rectangle> {
+        self.invalidated.take()
+    }
+
+    /// Move the clock reported to animations forward.
+    ///
+    /// The mock system's clock only moves when this is called, so animations can be stepped
+    /// through deterministically.
+    pub fn advance(&self, duration: Duration) {
+        self.time.set(self.time.get() + duration);
+    }
+
+    /// Get the number of redraws that have been triggered.
+    pub fn frame(&self) -> u64 {
+        self.frame.get()
+    }
+
+    /// Send an input event to every listener.
+    ///
+    /// The event's position is in window coordinates. It is routed to the widgets of the window
+    /// through its [`PointerCapture`]. Tab and Shift+Tab move focus instead of being sent.
+    pub fn send_event(&self, event: Event) {
+        if self.focus.handle_event(&event) {
+            return;
+        }
+
+        self.capture.dispatch(&event);
+
+        self.listeners.borrow_mut().retain(|queue| match queue.upgrade() {
+            Some(queue) => {
+                queue.borrow_mut().push_back(event.clone());
+                true
+            }
+            None => false,
+        });
+
+        self.event_sent.notify(usize::MAX);
+    }
+
+    /// Close the mock window.
+    ///
+    /// Every widget drawing into the system stops.
+    pub fn close(&self) {
+        self.closed.set();
+    }
+
+    /// Whether the mock window has been closed.
+    pub fn is_closed(&self) -> bool {
+        self.closed.is_set()
+    }
+
+    /// Get the commands recorded so far.
+    pub fn commands(&self) -> Vec<Command> {
+        self.commands.borrow().clone()
+    }
+
+    /// Take the commands recorded so far, leaving the recording empty.
+    pub fn take_commands(&self) -> Vec<Command> {
+        self.commands.take()
+    }
+}
+
+impl<B: Backend + 'static> System for MockSystem<B> {
+    type Backend = B;
+    type Listener = MockListener;
+    type RedrawRequested<'a> = EventListener;
+    type Closed<'a> = Closed<'a>;
+
+    fn add_child(&self) -> usize {
+        let key = self.children.get();
+        self.children.set(key + 1);
+        key
+    }
+
+    fn remove_child(&self, child: usize) {
+        self.commands
+            .borrow_mut()
+            .push(Command::RemoveChild { child });
+    }
+
+    fn closed(&self) -> Self::Closed<'_> {
+        self.closed.wait()
+    }
+
+    fn listen(&self) -> Self::Listener {
+        let queue = Rc::new(RefCell::new(VecDeque::new()));
+        self.listeners.borrow_mut().push(Rc::downgrade(&queue));
+
+        MockListener {
+            queue,
+            event_sent: self.event_sent.clone(),
+        }
+    }
+
+    fn focus(&self) -> &Focus {
+        &self.focus
+    }
+
+    fn pointer_capture(&self) -> &PointerCapture {
+        &self.capture
+    }
+
+    fn invalidate(&self, region: Rectangle) {
+        self.invalidated.borrow_mut().push(region);
+    }
+
+    fn request_frame(&self) -> Self::RedrawRequested<'_> {
+        self.redraw.listen()
+    }
+
+    fn frame_time(&self) -> Instant {
+        self.time.get()
+    }
+
+    fn draw(
+        &self,
+        child: usize,
+        mut item: impl Drawable<Self::Backend>,
+    ) -> (BackResult<Self::Backend>, Self::RedrawRequested<'_>) {
+        let wait = self.redraw.listen();
+
+        let result = (|| {
+            let mut backend = self.backend.borrow_mut();
+            let size = item.measure(BoxConstraints::loose(self.size.get()), &mut backend)?;
+            self.commands
+                .borrow_mut()
+                .push(Command::Draw { child, size });
+            item.draw(&mut backend, DrawParameters::window(self.size.get(), 1.0))
+        })();
+
+        (result, wait)
+    }
+}
+
+/// Listens for events sent through a [`MockSystem`].
+pub struct MockListener {
+    /// The events that have not been received yet.
+    queue: Rc<RefCell<VecDeque<Event>>>,
+
+    /// Notified when an event is sent.
+    event_sent: Rc<event_listener::Event>,
+}
+
+impl Listener<Event> for MockListener {
+    type Stream<'a> = MockEvents<'a>;
+
+    fn events(&mut self) -> Self::Stream<'_> {
+        MockEvents {
+            listener: self,
+            waiting: None,
+        }
+    }
+}
+
+/// The stream of events from a [`MockListener`].
+pub struct MockEvents<'a> {
+    /// The listener being read from.
+    listener: &'a mut MockListener,
+
+    /// Waiting for another event to be sent.
+    waiting: Option<EventListener>,
+}
+
+impl Stream for MockEvents<'_> {
+    type Item = Event;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            let event = self.listener.queue.borrow_mut().pop_front();
+            if let Some(event) = event {
+                self.waiting = None;
+                return Poll::Ready(Some(event));
+            }
+
+            match &mut self.waiting {
+                Some(waiting) => {
+                    futures_lite::ready!(Pin::new(waiting).poll(cx));
+                    self.waiting = None;
+                }
+
+                None => self.waiting = Some(self.listener.event_sent.listen()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Button, Dialog, Inbox, Stack, StackDirection, Widget};
+
+    use futures_lite::future;
+    use sunder::piet::testing::{Op, RecordingContext};
+    use sunder::piet::ui_theme::{Background, Color, Theme, WidgetProperties};
+    use sunder::piet::PietBackend;
+    use sunder::widgets::{Flex, PushButton};
+    use sunder::{
+        Damage, Key, KeyEvent, Modifiers, PointerButton, PointerButtons, PointerEvent, PointerKind,
+        RenderedWidget,
+    };
+
+    /// A widget that records its name when it is drawn.
+    struct Block {
+        name: &'static str,
+        size: Size,
+    }
+
+    impl Block {
+        fn new(name: &'static str, width: u32, height: u32) -> Self {
+            Self {
+                name,
+                size: Size { width, height },
+            }
+        }
+    }
+
+    impl sunder::Widget for Block {
+        type Immediate<'a> = ();
+
+        fn handle_event(&mut self, _immediate: &mut (), _event: Event) -> Damage {
+            Damage::None
+        }
+    }
+
+    impl RenderedWidget<RecordingBackend> for Block {
+        type Cache = ();
+
+        fn measure(
+            &mut self,
+            constraints: BoxConstraints,
+            _cache: &mut (),
+            _backend: &mut RecordingBackend,
+        ) -> Result<Size, MockError> {
+            Ok(constraints.constrain(self.size))
+        }
+
+        fn render(
+            &self,
+            _immediate: &(),
+            _cache: &mut (),
+            backend: &mut RecordingBackend,
+        ) -> Result<(), MockError> {
+            backend.record(self.name);
+            Ok(())
+        }
+    }
+
+    /// A focusable widget that logs the focus and text events it receives.
+    struct Probe {
+        name: &'static str,
+        log: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl sunder::Widget for Probe {
+        type Immediate<'a> = ();
+
+        fn handle_event(&mut self, _immediate: &mut (), event: Event) -> Damage {
+            let entry = match event {
+                Event::FocusIn => "in".to_string(),
+                Event::FocusOut => "out".to_string(),
+                Event::Text(text) => text,
+                _ => return Damage::None,
+            };
+            self.log
+                .borrow_mut()
+                .push(format!("{}:{}", self.name, entry));
+            Damage::None
+        }
+
+        fn is_focusable(&self) -> bool {
+            true
+        }
+    }
+
+    impl RenderedWidget<RecordingBackend> for Probe {
+        type Cache = ();
+
+        fn measure(
+            &mut self,
+            constraints: BoxConstraints,
+            _cache: &mut (),
+            _backend: &mut RecordingBackend,
+        ) -> Result<Size, MockError> {
+            Ok(constraints.constrain(size(10, 10)))
+        }
+
+        fn render(
+            &self,
+            _immediate: &(),
+            _cache: &mut (),
+            _backend: &mut RecordingBackend,
+        ) -> Result<(), MockError> {
+            Ok(())
+        }
+    }
+
+    /// Let every task make as much progress as it can.
+    async fn settle() {
+        for _ in 0..8 {
+            future::yield_now().await;
+        }
+    }
+
+    fn size(width: u32, height: u32) -> Size {
+        Size { width, height }
+    }
+
+    #[test]
+    fn draws_on_redraw() {
+        let system = MockSystem::new(size(100, 100));
+        let block = Widget::new(&system, Block::new("block", 10, 20));
+
+        future::block_on(async {
+            let test = async {
+                settle().await;
+                assert_eq!(
+                    system.take_commands(),
+                    [
+                        Command::Draw {
+                            child: 0,
+                            size: size(10, 20)
+                        },
+                        Command::Custom("block".into())
+                    ]
+                );
+
+                // Nothing is drawn until a redraw is triggered.
+                block.invalidate(Damage::Full);
+                settle().await;
+                assert!(system.take_commands().is_empty());
+                assert_eq!(
+                    system.take_invalidated(),
+                    [Rectangle {
+                        x: 0,
+                        y: 0,
+                        width: 100,
+                        height: 100
+                    }]
+                );
+
+                system.request_redraw();
+                settle().await;
+                assert_eq!(system.take_commands().len(), 2);
+                assert_eq!(system.frame(), 1);
+            };
+
+            test.or(async {
+                block.draw().await.unwrap();
+            })
+            .await;
+        });
+    }
+
+    #[test]
+    fn frames_wait_for_trigger() {
+        let system = MockSystem::new(size(100, 100));
+        let mut frame = Box::pin(system.request_frame());
+
+        future::block_on(async {
+            assert!(future::poll_once(&mut frame).await.is_none());
+            system.request_redraw();
+            assert!(future::poll_once(&mut frame).await.is_some());
+        });
+    }
+
+    #[test]
+    fn removes_and_closes() {
+        let system = MockSystem::new(size(100, 100));
+        let first = Widget::new(&system, Block::new("first", 10, 10));
+        let second = Widget::new(&system, Block::new("second", 10, 10));
+
+        future::block_on(async {
+            let test = async {
+                settle().await;
+                system.take_commands();
+
+                first.unmount();
+                settle().await;
+                assert_eq!(system.take_commands(), [Command::RemoveChild { child: 0 }]);
+
+                system.close();
+                future::pending::<()>().await
+            };
+
+            // Both draw loops end once the system is closed.
+            test.or(async {
+                let (first, second) = future::zip(first.draw(), second.draw()).await;
+                first.unwrap();
+                second.unwrap();
+            })
+            .await;
+        });
+    }
+
+    #[test]
+    fn stacks_unbounded_children() {
+        let system = MockSystem::new(size(100, 100));
+        let stack = Stack::new(&system, StackDirection::Vertical);
+        let tall = Widget::new(&stack, Block::new("tall", 10, u32::MAX));
+        let after = Widget::new(&stack, Block::new("after", 10, 10));
+
+        future::block_on(async {
+            let test = async {
+                settle().await;
+                let commands = system.take_commands();
+                assert!(commands.contains(&Command::Custom("tall".into())));
+                assert!(commands.contains(&Command::Custom("after".into())));
+            };
+
+            test.or(async {
+                tall.draw().or(after.draw()).await.unwrap();
+            })
+            .await;
+        });
+    }
+
+    #[test]
+    fn draws_real_widgets() {
+        // Buttons are only painted as far as the theme styles them.
+        let mut theme = Theme::default();
+        theme.insert_class(
+            "button",
+            WidgetProperties {
+                background: Some(Background::Color(Color {
+                    red: 0xdd,
+                    green: 0xdd,
+                    blue: 0xdd,
+                    alpha: 0xff,
+                })),
+                ..Default::default()
+            },
+        );
+
+        let backend = PietBackend::new(RecordingContext::new(), Rc::new(theme));
+        let system = MockSystem::with_backend(size(100, 100), backend);
+        let button = Button::new(&system, "OK");
+
+        future::block_on(async {
+            let test = async {
+                settle().await;
+                assert!(matches!(
+                    system.take_commands()[..],
+                    [Command::Draw { child: 0, .. }]
+                ));
+
+                let ops = system.backend().context().take_ops();
+                assert!(ops.iter().any(|op| matches!(op, Op::Fill { .. })));
+                assert!(ops.iter().any(|op| matches!(op, Op::Text(_))));
+            };
+
+            test.or(async {
+                button.draw().await.unwrap();
+            })
+            .await;
+        });
+    }
+
+    fn pointer(x: f64, y: f64, held: bool) -> PointerEvent {
+        let mut buttons = PointerButtons::new();
+        if held {
+            buttons.insert(PointerButton::Primary);
+        }
+
+        PointerEvent {
+            button: Some(PointerButton::Primary),
+            buttons,
+            ..PointerEvent::new(x, y)
+        }
+    }
+
+    /// Take the events routed to a widget, as their names and positions.
+    fn drain(inbox: &Inbox<'_>) -> Vec<(&'static str, Option<(f64, f64)>)> {
+        std::iter::from_fn(|| inbox.pop())
+            .map(|event| {
+                let name = match event {
+                    Event::PointerDown(_) => "down",
+                    Event::PointerUp(_) => "up",
+                    Event::PointerCancel(_) => "cancel",
+                    Event::PointerEnter(_) => "enter",
+                    Event::PointerLeave(_) => "leave",
+                    Event::FocusIn => "focus",
+                    _ => "other",
+                };
+                (name, event.position())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn tabs_through_containers() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let probe = |name| Probe {
+            name,
+            log: log.clone(),
+        };
+
+        let system = MockSystem::new(size(100, 100));
+        let row = Widget::new(
+            &system,
+            Flex::row().with_child(probe("a")).with_child(probe("b")),
+        );
+        let after = Widget::new(&system, probe("c"));
+        let tab = |shift| {
+            let modifiers = Modifiers {
+                shift,
+                ..Modifiers::default()
+            };
+            system.send_event(Event::KeyDown(KeyEvent::new(Key::Tab, modifiers)));
+        };
+
+        future::block_on(async {
+            let test = async {
+                // Tab goes into the container, and then through each of its children.
+                tab(false);
+                settle().await;
+                system.send_event(Event::Text("x".into()));
+                tab(false);
+                settle().await;
+                system.send_event(Event::Text("y".into()));
+                settle().await;
+                assert_eq!(log.take(), ["a:in", "a:x", "a:out", "b:in", "b:y"]);
+                assert!(row.is_focused());
+
+                // After the last child, focus leaves the container.
+                tab(false);
+                settle().await;
+                assert_eq!(log.take(), ["b:out", "c:in"]);
+                assert!(after.is_focused());
+
+                // Shift+Tab comes back to the child that had focus, and then goes backwards.
+                tab(true);
+                settle().await;
+                let mut moved = log.take();
+                moved.sort();
+                assert_eq!(moved, ["b:in", "c:out"]);
+                tab(true);
+                settle().await;
+                assert_eq!(log.take(), ["b:out", "a:in"]);
+            };
+
+            test.or(async {
+                row.draw().or(after.draw()).await.unwrap();
+            })
+            .await;
+        });
+    }
+
+    #[test]
+    fn routes_to_topmost_widget() {
+        let capture = PointerCapture::new();
+        let window = Rectangle {
+            x: 0,
+            y: 0,
+            width: 100,
+            height: 100,
+        };
+        let region = Rectangle {
+            x: 10,
+            y: 10,
+            width: 50,
+            height: 50,
+        };
+
+        let view = capture.register(None);
+        let below = capture.register(Some(view));
+        let above = capture.register(Some(view));
+        capture.place(view, window, window);
+        capture.place(below, region, region);
+        capture.place(above, region, region);
+        capture.set_captures_pointer(above, true);
+        let (view_inbox, below_inbox, above_inbox) = (
+            capture.inbox(view),
+            capture.inbox(below),
+            capture.inbox(above),
+        );
+
+        // The press goes to the widget on top, and then bubbles up to its container.
+        let down = Event::PointerDown(pointer(20.0, 20.0, true));
+        capture.dispatch(&down);
+        let (inside, pressed) = (Some((10.0, 10.0)), Some((20.0, 20.0)));
+        assert_eq!(drain(&above_inbox), [("enter", inside), ("down", inside)]);
+        assert_eq!(drain(&view_inbox), [("down", pressed)]);
+        assert!(drain(&below_inbox).is_empty());
+        assert_eq!(capture.owner(PointerKind::Mouse), Some(above));
+
+        // The pointer stays with the widget until it is released.
+        capture.dispatch(&Event::PointerUp(pointer(80.0, 80.0, false)));
+        let outside = Some((70.0, 70.0));
+        assert_eq!(drain(&above_inbox), [("up", outside), ("leave", outside)]);
+        assert_eq!(capture.owner(PointerKind::Mouse), None);
+
+        // Once it is, the mouse is over the container instead.
+        let released = Some((80.0, 80.0));
+        assert_eq!(drain(&view_inbox), [("enter", released), ("up", released)]);
+
+        // Containers take presses on their intercepts first.
+        capture.set_intercepts(view, [region]);
+        capture.dispatch(&down);
+        assert!(drain(&above_inbox).is_empty());
+        assert_eq!(drain(&view_inbox), [("down", pressed)]);
+        assert_eq!(capture.owner(PointerKind::Mouse), Some(view));
+        capture.dispatch(&Event::PointerCancel(pointer(20.0, 20.0, false)));
+        assert_eq!(
+            drain(&view_inbox),
+            [("cancel", pressed), ("leave", pressed)]
+        );
+        assert_eq!(drain(&above_inbox), [("enter", inside)]);
+
+        // Modal overlays take every pointer event, and other events go everywhere.
+        let _modal = capture.modal(below);
+        capture.dispatch(&down);
+        capture.dispatch(&Event::FocusIn);
+        assert_eq!(drain(&below_inbox), [("down", inside), ("focus", None)]);
+        assert_eq!(drain(&above_inbox), [("focus", None)]);
+        assert_eq!(drain(&view_inbox), [("focus", None)]);
+    }
+
+    #[test]
+    fn cancels_captured_pointers() {
+        let capture = PointerCapture::new();
+        let region = Rectangle {
+            x: 10,
+            y: 10,
+            width: 50,
+            height: 50,
+        };
+
+        let widget = capture.register(None);
+        capture.place(widget, region, region);
+        capture.set_captures_pointer(widget, true);
+        let inbox = capture.inbox(widget);
+
+        capture.dispatch(&Event::PointerDown(pointer(20.0, 20.0, true)));
+        drain(&inbox);
+        assert_eq!(capture.owner(PointerKind::Mouse), Some(widget));
+
+        capture.cancel_all();
+        assert!(matches!(drain(&inbox)[..], [("cancel", _)]));
+        assert_eq!(capture.owner(PointerKind::Mouse), None);
+    }
+
+    #[test]
+    fn dialogs_cancel_presses() {
+        let backend = PietBackend::new(RecordingContext::new(), Rc::new(Theme::default()));
+        let system = MockSystem::with_backend(size(100, 100), backend);
+        let button = Widget::new(&system, PushButton::new("OK".to_string()));
+        let dialog = Dialog::new(&system, "Sure?", ["Yes".to_string()]);
+        let open = event_listener::Event::new();
+
+        future::block_on(async {
+            let test = async {
+                settle().await;
+                system.send_event(Event::PointerDown(pointer(5.0, 5.0, true)));
+                settle().await;
+                assert!(button.state().is_pressed());
+
+                // Opening the dialog lets go of the press, without waiting for another event.
+                open.notify(usize::MAX);
+                settle().await;
+                assert!(!button.state().is_pressed());
+                assert_eq!(system.pointer_capture().owner(PointerKind::Mouse), None);
+            };
+
+            let show = async {
+                open.listen().await;
+                dialog.run().await.unwrap();
+                future::pending::<()>().await
+            };
+
+            test.or(show)
+                .or(async {
+                    button.draw().await.unwrap();
+                })
+                .await;
+        });
+    }
+}
@@ -0,0 +1,134 @@
+/*
+
+`async-gui` is free software: you can redistribute it and/or modify it under the terms of one of
+the following licenses:
+
+- The GNU Affero General Public License as published by the Free Software Foundation, either version
+  3 of the License, or (at your option) any later version.
+- The Patron License at https://github.com/notgull/async-gui/blob/main/LICENSE-PATRON.md, for
+  sponsors and contributors, who can ignore the copyleft provisions of the GNU AGPL for this project.
+
+`async-gui` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+Public License and the Patron License for more details.
+
+You should have received a copy of the GNU Affero General Public License and the corresponding Patron
+License along with `async-gui`. If not, see <https://www.gnu.org/licenses/>.
+
+*/
+
+//! Multiple lines of editable text.
+
+use crate::{Error, System, Widget};
+use sunder::widgets::{TextArea as TextAreaWidget, TextPosition};
+use sunder::RenderedWidget;
+
+use std::cell::Cell;
+use std::ops::Range;
+
+/// Multiple lines of editable text, with soft wrapping and scrolling.
+pub struct TextArea<Sys: System>
+where
+    TextAreaWidget: RenderedWidget<Sys::Backend>,
+{
+    /// The underlying widget.
+    widget: Widget<'static, Sys, TextAreaWidget>,
+
+    /// How many times the text has been edited.
+    edits: Cell<u64>,
+
+    /// How many times the text had been edited the last time `changed` returned.
+    seen_edits: Cell<u64>,
+
+    /// Notified when the text is edited.
+    changed: event_listener::Event,
+}
+
+impl<Sys: System> TextArea<Sys>
+where
+    TextAreaWidget: RenderedWidget<Sys::Backend>,
+{
+    /// Create a new text area with the given text.
+    pub fn new(system: Sys, text: impl AsRef<str>) -> Self {
+        Self {
+            widget: Widget::new(system, TextAreaWidget::new(text)),
+            edits: Cell::new(0),
+            seen_edits: Cell::new(0),
+            changed: event_listener::Event::new(),
+        }
+    }
+
+    /// Get the text, with its lines joined by line breaks.
+    pub fn text(&self) -> String {
+        self.widget.widget().text()
+    }
+
+    /// Replace the text, moving the caret to the end of it.
+    ///
+    /// This doesn't count as the text being edited.
+    pub fn set_text(&self, text: impl AsRef<str>) {
+        self.widget.update(|area| area.set_text(text));
+    }
+
+    /// Get the line and column of the caret, counting characters from zero.
+    pub fn line_column(&self) -> (usize, usize) {
+        self.widget.widget().line_column()
+    }
+
+    /// Get the selected range of the text.
+    pub fn selection(&self) -> Range<TextPosition> {
+        self.widget.widget().selection()
+    }
+
+    /// Select a range of the text, scrolling to show the caret.
+    pub fn set_selection(&self, range: Range<TextPosition>) {
+        self.widget.update(|area| area.set_selection(range));
+    }
+
+    /// Wait for the user to edit the text.
+    ///
+    /// This returns straight away if the text was edited since it last returned, so that edits
+    /// made while nothing was waiting aren't missed.
+    pub async fn changed(&self) {
+        loop {
+            let listener = self.changed.listen();
+
+            let edits = self.edits.get();
+            if self.seen_edits.replace(edits) != edits {
+                return;
+            }
+
+            listener.await;
+        }
+    }
+
+    /// Draw the area and handle its input.
+    ///
+    /// This returns once the area is unmounted or its system is closed, or if drawing the area
+    /// fails.
+    pub async fn draw(&self) -> Result<(), Error<Sys::Backend>> {
+        self.widget
+            .draw_with(|state| {
+                if state.take_changed() {
+                    self.edits.set(self.edits.get().wrapping_add(1));
+                    self.changed.notify(usize::MAX);
+                }
+            })
+            .await
+    }
+
+    /// Move keyboard focus to the area.
+    pub fn request_focus(&self) {
+        self.widget.request_focus();
+    }
+
+    /// Wait until the area has keyboard focus.
+    pub async fn focus(&self) {
+        self.widget.focus().await
+    }
+
+    /// Unmount the area from its system.
+    pub fn unmount(&self) {
+        self.widget.unmount();
+    }
+}
@@ -0,0 +1,159 @@
+/*
+
+`async-gui` is free software: you can redistribute it and/or modify it under the terms of one of
+the following licenses:
+
+- The GNU Affero General Public License as published by the Free Software Foundation, either version
+  3 of the License, or (at your option) any later version.
+- The Patron License at https://github.com/notgull/async-gui/blob/main/LICENSE-PATRON.md, for
+  sponsors and contributors, who can ignore the copyleft provisions of the GNU AGPL for this project.
+
+`async-gui` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+Public License and the Patron License for more details.
+
+You should have received a copy of the GNU Affero General Public License and the corresponding Patron
+License along with `async-gui`. If not, see <https://www.gnu.org/licenses/>.
+
+*/
+
+//! A single line of editable text.
+
+use crate::{Error, System, Widget};
+use sunder::widgets::TextInput as TextInputWidget;
+use sunder::RenderedWidget;
+
+use std::cell::Cell;
+use std::ops::Range;
+
+/// A single line of editable text.
+pub struct TextInput<Sys: System>
+where
+    TextInputWidget: RenderedWidget<Sys::Backend>,
+{
+    /// The underlying widget.
+    widget: Widget<'static, Sys, TextInputWidget>,
+
+    /// How many times the text has been edited.
+    edits: Cell<u64>,
+
+    /// How many times the text had been edited the last time `changed` returned.
+    seen_edits: Cell<u64>,
+
+    /// Notified when the text is edited.
+    changed: event_listener::Event,
+
+    /// How many times enter has been pressed.
+    submits: Cell<u64>,
+
+    /// How many times enter had been pressed the last time `submitted` returned.
+    seen_submits: Cell<u64>,
+
+    /// Notified when enter is pressed.
+    submitted: event_listener::Event,
+}
+
+impl<Sys: System> TextInput<Sys>
+where
+    TextInputWidget: RenderedWidget<Sys::Backend>,
+{
+    /// Create a new text input with the given text.
+    pub fn new(system: Sys, text: impl Into<String>) -> Self {
+        Self {
+            widget: Widget::new(system, TextInputWidget::new(text)),
+            edits: Cell::new(0),
+            seen_edits: Cell::new(0),
+            changed: event_listener::Event::new(),
+            submits: Cell::new(0),
+            seen_submits: Cell::new(0),
+            submitted: event_listener::Event::new(),
+        }
+    }
+
+    /// Get the text.
+    pub fn text(&self) -> String {
+        self.widget.widget().text().to_owned()
+    }
+
+    /// Replace the text, moving the caret to the end of it.
+    ///
+    /// This doesn't count as the text being edited.
+    pub fn set_text(&self, text: impl Into<String>) {
+        self.widget.update(|input| input.set_text(text));
+    }
+
+    /// Get the selected range of the text, in bytes.
+    pub fn selection(&self) -> Range<usize> {
+        self.widget.widget().selection()
+    }
+
+    /// Select a range of the text, in bytes.
+    pub fn set_selection(&self, range: Range<usize>) {
+        self.widget.update(|input| input.set_selection(range));
+    }
+
+    /// Wait for the user to edit the text.
+    ///
+    /// This returns straight away if the text was edited since it last returned, so that edits
+    /// made while nothing was waiting aren't missed.
+    pub async fn changed(&self) {
+        wait(&self.edits, &self.seen_edits, &self.changed).await
+    }
+
+    /// Wait for the user to press enter.
+    ///
+    /// Like [`changed`](Self::changed), this returns straight away if enter was pressed since it
+    /// last returned.
+    pub async fn submitted(&self) {
+        wait(&self.submits, &self.seen_submits, &self.submitted).await
+    }
+
+    /// Draw the input and handle its input.
+    ///
+    /// This returns once the input is unmounted or its system is closed, or if drawing the input
+    /// fails.
+    pub async fn draw(&self) -> Result<(), Error<Sys::Backend>> {
+        self.widget
+            .draw_with(|state| {
+                if state.take_changed() {
+                    self.edits.set(self.edits.get().wrapping_add(1));
+                    self.changed.notify(usize::MAX);
+                }
+
+                if state.take_submitted() {
+                    self.submits.set(self.submits.get().wrapping_add(1));
+                    self.submitted.notify(usize::MAX);
+                }
+            })
+            .await
+    }
+
+    /// Move keyboard focus to the input.
+    pub fn request_focus(&self) {
+        self.widget.request_focus();
+    }
+
+    /// Wait until the input has keyboard focus.
+    pub async fn focus(&self) {
+        self.widget.focus().await
+    }
+
+    /// Unmount the input from its system.
+    pub fn unmount(&self) {
+        self.widget.unmount();
+    }
+}
+
+/// Wait until `count` is different from what it was the last time this returned.
+async fn wait(count: &Cell<u64>, seen: &Cell<u64>, event: &event_listener::Event) {
+    loop {
+        let listener = event.listen();
+
+        let current = count.get();
+        if seen.replace(current) != current {
+            return;
+        }
+
+        listener.await;
+    }
+}
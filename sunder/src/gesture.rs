@@ -0,0 +1,226 @@
+/*
+
+`sunder` is free software: you can redistribute it and/or modify it under the terms of one of
+the following licenses:
+
+- The GNU Affero General Public License as published by the Free Software Foundation, either version
+  3 of the License, or (at your option) any later version.
+- The Patron License at https://github.com/notgull/sunder/blob/main/LICENSE-PATRON.md, for
+  sponsors and contributors, who can ignore the copyleft provisions of the GNU AGPL for this project.
+
+`sunder` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+Public License and the Patron License for more details.
+
+You should have received a copy of the GNU Affero General Public License and the corresponding Patron
+License along with `sunder`. If not, see <https://www.gnu.org/licenses/>.
+
+*/
+
+//! Recognizing pointer gestures that depend on timing, such as double clicks.
+//!
+//! Backends use this to fill in [`PointerEvent::count`], and to decide when to send
+//! [`Event::LongPress`](crate::Event::LongPress) and [`Event::Hover`](crate::Event::Hover), so
+//! that widgets never have to keep time themselves.
+
+use crate::{PointerButton, PointerEvent, PointerKind};
+
+use core::time::Duration;
+
+/// How far apart in time and place pointer events can be for gestures.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GestureSettings {
+    /// The longest time between two presses for them to count as being in a row.
+    pub multi_click_time: Duration,
+
+    /// The furthest apart two presses in a row can be along either axis, in logical pixels.
+    ///
+    /// A pointer that is held down stops being a long press once it moves further than this.
+    pub multi_click_distance: f64,
+
+    /// How long a pointer has to be held down in place to send
+    /// [`Event::LongPress`](crate::Event::LongPress).
+    pub long_press_time: Duration,
+
+    /// How long the pointer has to rest in place to send [`Event::Hover`](crate::Event::Hover).
+    pub hover_time: Duration,
+}
+
+impl GestureSettings {
+    /// Get the default settings of the platform being compiled for.
+    ///
+    /// These are the platform's defaults, not the user's own preferences.
+    pub const fn platform() -> Self {
+        if cfg!(windows) {
+            Self {
+                multi_click_time: Duration::from_millis(500),
+                multi_click_distance: 4.0,
+                long_press_time: Duration::from_millis(500),
+                hover_time: Duration::from_millis(400),
+            }
+        } else if cfg!(any(target_os = "macos", target_os = "ios")) {
+            Self {
+                multi_click_time: Duration::from_millis(500),
+                multi_click_distance: 4.0,
+                long_press_time: Duration::from_millis(500),
+                hover_time: Duration::from_millis(500),
+            }
+        } else {
+            Self {
+                multi_click_time: Duration::from_millis(400),
+                multi_click_distance: 5.0,
+                long_press_time: Duration::from_millis(500),
+                hover_time: Duration::from_millis(500),
+            }
+        }
+    }
+
+    /// Whether a pointer that moved by this much is still close enough to where it started.
+    pub fn is_near(&self, dx: f64, dy: f64) -> bool {
+        dx.abs() <= self.multi_click_distance && dy.abs() <= self.multi_click_distance
+    }
+}
+
+impl Default for GestureSettings {
+    fn default() -> Self {
+        Self::platform()
+    }
+}
+
+/// Counts the presses of a pointer that happen in a row.
+///
+/// Presses are in a row when they come from the same pointer and button, and each one happens
+/// close to the last one in both time and place, according to the [`GestureSettings`].
+#[derive(Debug, Default, Clone)]
+pub struct ClickCounter {
+    /// The last press that was counted.
+    last: Option<Press>,
+}
+
+/// A press counted by a [`ClickCounter`].
+#[derive(Debug, Clone, Copy)]
+struct Press {
+    x: f64,
+    y: f64,
+    kind: PointerKind,
+    button: Option<PointerButton>,
+
+    /// When the press happened.
+    time: Duration,
+
+    /// The number of presses in a row, including this one.
+    count: u32,
+}
+
+impl ClickCounter {
+    /// Create a new click counter.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a press, returning the number of presses in a row that it is part of.
+    ///
+    /// `time` is when the press happened. It can be measured from any point in time, as long as
+    /// it is the same point for every press.
+    pub fn press(
+        &mut self,
+        settings: &GestureSettings,
+        pointer: &PointerEvent,
+        time: Duration,
+    ) -> u32 {
+        let count = match self.last {
+            Some(last)
+                if last.kind == pointer.kind
+                    && last.button == pointer.button
+                    && time
+                        .checked_sub(last.time)
+                        .is_some_and(|elapsed| elapsed <= settings.multi_click_time)
+                    && settings.is_near(pointer.x - last.x, pointer.y - last.y) =>
+            {
+                last.count.saturating_add(1)
+            }
+            _ => 1,
+        };
+
+        self.last = Some(Press {
+            x: pointer.x,
+            y: pointer.y,
+            kind: pointer.kind,
+            button: pointer.button,
+            time,
+            count,
+        });
+
+        count
+    }
+
+    /// Forget the last press, so that the next press starts counting again.
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SETTINGS: GestureSettings = GestureSettings {
+        multi_click_time: Duration::from_millis(400),
+        multi_click_distance: 5.0,
+        long_press_time: Duration::from_millis(500),
+        hover_time: Duration::from_millis(500),
+    };
+
+    fn press(x: f64, y: f64) -> PointerEvent {
+        let mut pointer = PointerEvent::new(x, y);
+        pointer.button = Some(PointerButton::Primary);
+        pointer
+    }
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn counts_presses_in_time() {
+        let mut counter = ClickCounter::new();
+        assert_eq!(counter.press(&SETTINGS, &press(10.0, 10.0), ms(1000)), 1);
+        assert_eq!(counter.press(&SETTINGS, &press(10.0, 10.0), ms(1400)), 2);
+        assert_eq!(counter.press(&SETTINGS, &press(10.0, 10.0), ms(1700)), 3);
+
+        // Each press is timed from the one before it, not from the first.
+        assert_eq!(counter.press(&SETTINGS, &press(10.0, 10.0), ms(2101)), 1);
+
+        // Time going backwards doesn't count as a press in a row.
+        assert_eq!(counter.press(&SETTINGS, &press(10.0, 10.0), ms(2000)), 1);
+    }
+
+    #[test]
+    fn counts_presses_in_place() {
+        let mut counter = ClickCounter::new();
+        counter.press(&SETTINGS, &press(10.0, 10.0), ms(0));
+        assert_eq!(counter.press(&SETTINGS, &press(15.0, 5.0), ms(100)), 2);
+
+        // Too far along either axis starts again.
+        assert_eq!(counter.press(&SETTINGS, &press(15.0, 10.5), ms(200)), 1);
+        assert_eq!(counter.press(&SETTINGS, &press(9.0, 10.5), ms(300)), 1);
+    }
+
+    #[test]
+    fn counts_presses_of_one_button() {
+        let mut counter = ClickCounter::new();
+        counter.press(&SETTINGS, &press(10.0, 10.0), ms(0));
+
+        let mut secondary = press(10.0, 10.0);
+        secondary.button = Some(PointerButton::Secondary);
+        assert_eq!(counter.press(&SETTINGS, &secondary, ms(100)), 1);
+
+        let mut touch = press(10.0, 10.0);
+        touch.kind = PointerKind::Touch { id: 1 };
+        assert_eq!(counter.press(&SETTINGS, &touch, ms(200)), 1);
+        assert_eq!(counter.press(&SETTINGS, &touch, ms(300)), 2);
+
+        counter.reset();
+        assert_eq!(counter.press(&SETTINGS, &touch, ms(400)), 1);
+    }
+}
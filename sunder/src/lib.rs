@@ -32,6 +32,9 @@ License along with `sunder`. If not, see <https://www.gnu.org/licenses/>.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+extern crate alloc;
+
+use alloc::string::String;
 use core::fmt;
 
 #[cfg(feature = "piet")]
@@ -59,8 +62,6 @@ macro_rules! cfg_web {
 }
 
 cfg_piet! {
-    extern crate alloc;
-
     pub mod piet;
 }
 
@@ -68,6 +69,8 @@ cfg_web! {
     pub mod web;
 }
 
+pub mod gesture;
+pub mod tree;
 pub mod widgets;
 
 /// The backend for rendering widgets.
@@ -77,6 +80,15 @@ pub trait Backend {
 
     /// The result of a rendering operation.
     type Output;
+
+    /// Run a closure with the origin moved to the top left corner of `region`.
+    ///
+    /// Anything drawn inside of the closure is clipped to the bounds of `region`.
+    fn with_region<R>(
+        &mut self,
+        region: Rectangle,
+        f: impl FnOnce(&mut Self) -> Result<R, Self::Error>,
+    ) -> Result<R, Self::Error>;
 }
 
 /// The widget information independent of the backend.
@@ -91,8 +103,33 @@ pub trait Widget {
 
     /// Reply to an event in the immediate state.
     ///
-    /// Returns true if this change means that the widget needs to be redrawn.
-    fn handle_event(&mut self, immediate: &mut Self::Immediate<'_>, event: Event) -> bool;
+    /// Returns the part of the widget that needs to be redrawn because of this change.
+    fn handle_event(&mut self, immediate: &mut Self::Immediate<'_>, event: Event) -> Damage;
+
+    /// Whether this widget can receive keyboard focus.
+    ///
+    /// Only focusable widgets receive [`Event::FocusIn`], [`Event::FocusOut`] and keyboard events.
+    fn is_focusable(&self) -> bool {
+        false
+    }
+
+    /// Whether pressing Tab, or Shift+Tab if `forwards` is `false`, moves keyboard focus to
+    /// another part of this widget rather than out of it.
+    ///
+    /// Containers use this to step through their focusable children. This is `false` by default.
+    fn steps_focus(&self, forwards: bool) -> bool {
+        let _ = forwards;
+        false
+    }
+
+    /// Whether this widget captures pointers that are pressed on it.
+    ///
+    /// A captured pointer keeps going to the widget until it is released, even once it moves
+    /// outside of the widget. Widgets that can be dragged, such as scrollbars, should capture
+    /// pointers so that the drag doesn't stop at their edges.
+    fn captures_pointer(&self) -> bool {
+        false
+    }
 }
 
 /// The whole point.
@@ -102,10 +139,41 @@ pub trait RenderedWidget<B: Backend>: Widget {
     /// This can be used in some cases as a cache to avoid recomputing the widget's properties.
     type Cache: Default;
 
-    /// Get the rectangle that this widget is defined by.
+    /// Measure the size that this widget wants to take up, within the given constraints.
     ///
-    /// Widgets are drawn at (0, 0).
-    fn rectangle(&mut self, cache: &mut Self::Cache, backend: &mut B) -> Result<Size, B::Error>;
+    /// This is the first layout pass. It can be called several times with different constraints
+    /// before the widget is arranged. The returned size should satisfy `constraints`.
+    fn measure(
+        &mut self,
+        constraints: BoxConstraints,
+        cache: &mut Self::Cache,
+        backend: &mut B,
+    ) -> Result<Size, B::Error>;
+
+    /// Place the widget in the region that it has been given.
+    ///
+    /// This is the second layout pass, called after [`RenderedWidget::measure`] and before
+    /// [`RenderedWidget::render`]. The position of the region is chosen by the widget's container,
+    /// and the widget itself is still drawn at (0, 0). The region's size might differ from the
+    /// measured size.
+    fn arrange(
+        &mut self,
+        region: Rectangle,
+        cache: &mut Self::Cache,
+        backend: &mut B,
+    ) -> Result<(), B::Error> {
+        let _ = (region, cache, backend);
+        Ok(())
+    }
+
+    /// Get the distance from the top of the widget to the baseline of its first line of text.
+    ///
+    /// This is used to line up text in widgets placed next to each other. It reflects the last
+    /// call to [`RenderedWidget::measure`]. Widgets without text return `None`.
+    fn baseline(&self, cache: &Self::Cache) -> Option<u32> {
+        let _ = cache;
+        None
+    }
 
     /// Render the widget.
     fn render(
@@ -120,12 +188,433 @@ pub trait RenderedWidget<B: Backend>: Widget {
 #[non_exhaustive]
 #[derive(Debug, Clone)]
 pub enum Event {
-    /// Where the mouse is, relative to the top left corner of this widget.
-    Mouse { x: f64, y: f64 },
+    /// The pointer moved to this position, relative to the top left corner of this widget.
+    PointerMove(PointerEvent),
+
+    /// A pointer button was pressed, or a finger touched the screen.
+    PointerDown(PointerEvent),
+
+    /// A pointer button was released, or a finger was lifted from the screen.
+    PointerUp(PointerEvent),
+
+    /// The pointer entered the window.
+    PointerEnter(PointerEvent),
+
+    /// The pointer left the window.
+    PointerLeave(PointerEvent),
+
+    /// The system took the pointer away, such as when a touch turns into a gesture.
+    ///
+    /// Anything started by the matching [`Event::PointerDown`] should be abandoned without
+    /// taking effect.
+    PointerCancel(PointerEvent),
+
+    /// A pointer has been held down in place for a while.
+    ///
+    /// This is usually how touch screens open context menus. The pointer is still down, and its
+    /// [`Event::PointerUp`] is delivered as usual.
+    LongPress(PointerEvent),
+
+    /// The pointer has rested over the window for a while without moving or pressing anything.
+    ///
+    /// This is usually when tooltips are shown.
+    Hover(PointerEvent),
+
+    /// The mouse wheel was scrolled with the cursor at this position.
+    ///
+    /// The deltas are in pixels. Positive deltas move the view towards the bottom and right of
+    /// the content.
+    Scroll {
+        x: f64,
+        y: f64,
+        delta_x: f64,
+        delta_y: f64,
+        modifiers: Modifiers,
+    },
+
+    /// A key was pressed while this widget has keyboard focus.
+    KeyDown(KeyEvent),
+
+    /// A key was released while this widget has keyboard focus.
+    KeyUp(KeyEvent),
+
+    /// Text was typed while this widget has keyboard focus.
+    ///
+    /// This is what text fields should insert, rather than interpreting [`Event::KeyDown`] on
+    /// their own. It takes the keyboard layout and dead keys into account, and never contains
+    /// control characters.
+    Text(String),
+
+    /// This widget gained keyboard focus.
+    FocusIn,
+
+    /// This widget lost keyboard focus.
+    FocusOut,
+}
+
+impl Event {
+    /// Move the position of this event by the given offset.
+    pub fn translate(self, dx: f64, dy: f64) -> Self {
+        match self {
+            Event::PointerMove(pointer) => Event::PointerMove(pointer.translate(dx, dy)),
+            Event::PointerDown(pointer) => Event::PointerDown(pointer.translate(dx, dy)),
+            Event::PointerUp(pointer) => Event::PointerUp(pointer.translate(dx, dy)),
+            Event::PointerEnter(pointer) => Event::PointerEnter(pointer.translate(dx, dy)),
+            Event::PointerLeave(pointer) => Event::PointerLeave(pointer.translate(dx, dy)),
+            Event::PointerCancel(pointer) => Event::PointerCancel(pointer.translate(dx, dy)),
+            Event::LongPress(pointer) => Event::LongPress(pointer.translate(dx, dy)),
+            Event::Hover(pointer) => Event::Hover(pointer.translate(dx, dy)),
+            Event::Scroll {
+                x,
+                y,
+                delta_x,
+                delta_y,
+                modifiers,
+            } => Event::Scroll {
+                x: x + dx,
+                y: y + dy,
+                delta_x,
+                delta_y,
+                modifiers,
+            },
+            event => event,
+        }
+    }
+
+    /// Get the pointer involved in this event, if there is one.
+    pub fn pointer(&self) -> Option<&PointerEvent> {
+        match self {
+            Event::PointerMove(pointer)
+            | Event::PointerDown(pointer)
+            | Event::PointerUp(pointer)
+            | Event::PointerEnter(pointer)
+            | Event::PointerLeave(pointer)
+            | Event::PointerCancel(pointer)
+            | Event::LongPress(pointer)
+            | Event::Hover(pointer) => Some(pointer),
+            _ => None,
+        }
+    }
+
+    /// Get the position that this event happened at, if it has one.
+    pub fn position(&self) -> Option<(f64, f64)> {
+        match *self {
+            Event::Scroll { x, y, .. } => Some((x, y)),
+            _ => self.pointer().map(|pointer| (pointer.x, pointer.y)),
+        }
+    }
+
+    /// Whether this event comes from the keyboard.
+    ///
+    /// Keyboard events are only delivered to the widget with keyboard focus.
+    pub fn is_keyboard(&self) -> bool {
+        matches!(self, Event::KeyDown(_) | Event::KeyUp(_) | Event::Text(_))
+    }
+}
+
+/// A pointer moving, pressing or releasing.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct PointerEvent {
+    /// The x coordinate of the pointer, relative to the top left corner of this widget.
+    pub x: f64,
+
+    /// The y coordinate of the pointer, relative to the top left corner of this widget.
+    pub y: f64,
+
+    /// The kind of device that the pointer belongs to.
+    pub kind: PointerKind,
+
+    /// The button that was pressed or released, for [`Event::PointerDown`] and
+    /// [`Event::PointerUp`].
+    ///
+    /// Touches act as the primary button.
+    pub button: Option<PointerButton>,
+
+    /// The buttons held down after this event.
+    pub buttons: PointerButtons,
+
+    /// The modifier keys held down.
+    pub modifiers: Modifiers,
+
+    /// How hard the pointer is being pressed, from 0 to 1.
+    ///
+    /// Devices that can't tell report 0.5 while a button is held down and 0 otherwise.
+    pub pressure: f64,
+
+    /// For [`Event::PointerDown`], the number of presses in a row that this press is part of.
+    ///
+    /// This is 1 for a single click, 2 for a double click, 3 for a triple click, and so on. It is
+    /// 0 for every other event. See [`gesture::ClickCounter`] for when presses count as being in a row.
+    pub count: u32,
+}
+
+impl PointerEvent {
+    /// Create a pointer event for the mouse at this position, with nothing held down.
+    pub fn new(x: f64, y: f64) -> Self {
+        PointerEvent {
+            x,
+            y,
+            ..Default::default()
+        }
+    }
+
+    /// Whether this event presses or releases the primary button.
+    pub fn is_primary(&self) -> bool {
+        self.button == Some(PointerButton::Primary)
+    }
+
+    /// Move the position of this event by the given offset.
+    fn translate(self, dx: f64, dy: f64) -> Self {
+        PointerEvent {
+            x: self.x + dx,
+            y: self.y + dy,
+            ..self
+        }
+    }
+}
+
+/// The kind of device that a pointer belongs to.
+#[non_exhaustive]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PointerKind {
+    /// A mouse or touchpad.
+    #[default]
+    Mouse,
+
+    /// A finger on a touch screen.
+    ///
+    /// Each finger gets its own ID for as long as it touches the screen.
+    Touch { id: u64 },
+}
+
+/// A button on a pointer.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PointerButton {
+    /// The primary button, usually the left mouse button.
+    Primary,
+
+    /// The secondary button, usually the right mouse button.
+    Secondary,
+
+    /// The auxiliary button, usually the middle mouse button or the wheel.
+    Auxiliary,
+
+    /// Some other button.
+    Other(u16),
+}
+
+impl PointerButton {
+    /// Get the bit that represents this button in [`PointerButtons`].
+    fn bit(self) -> u32 {
+        match self {
+            PointerButton::Primary => 1,
+            PointerButton::Secondary => 1 << 1,
+            PointerButton::Auxiliary => 1 << 2,
+            PointerButton::Other(other) => 1u32.checked_shl(3 + other as u32).unwrap_or(0),
+        }
+    }
+}
+
+/// A set of pointer buttons.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PointerButtons(u32);
+
+impl PointerButtons {
+    /// Create an empty set of buttons.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether no buttons are held down.
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Whether this button is held down.
+    pub fn contains(self, button: PointerButton) -> bool {
+        let bit = button.bit();
+        bit != 0 && self.0 & bit != 0
+    }
+
+    /// Add a button to the set.
+    pub fn insert(&mut self, button: PointerButton) {
+        self.0 |= button.bit();
+    }
+
+    /// Remove a button from the set.
+    pub fn remove(&mut self, button: PointerButton) {
+        self.0 &= !button.bit();
+    }
+}
+
+/// A key being pressed or released.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyEvent {
+    /// The logical key, which depends on the keyboard layout.
+    pub key: Key,
+
+    /// The platform-specific scan code of the physical key, which doesn't depend on the layout.
+    ///
+    /// This is zero for events that don't come from a real keyboard.
+    pub scancode: u32,
+
+    /// The modifier keys held down.
+    pub modifiers: Modifiers,
+
+    /// Whether this press comes from the key being held down, rather than from a new press.
+    pub repeat: bool,
+}
+
+impl KeyEvent {
+    /// Create a key event that isn't tied to a physical key.
+    pub fn new(key: Key, modifiers: Modifiers) -> Self {
+        KeyEvent {
+            key,
+            scancode: 0,
+            modifiers,
+            repeat: false,
+        }
+    }
+}
+
+/// The part of a widget that needs to be redrawn.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Damage {
+    /// Nothing needs to be redrawn.
+    #[default]
+    None,
+
+    /// Only this region needs to be redrawn, relative to the top left corner of the widget.
+    Region(Rectangle),
+
+    /// The whole widget needs to be redrawn.
+    Full,
+}
+
+impl Damage {
+    /// Whether nothing needs to be redrawn.
+    pub fn is_none(self) -> bool {
+        matches!(self, Damage::None)
+    }
+
+    /// Combine this damage with `other`, covering both of them.
+    pub fn union(self, other: Damage) -> Damage {
+        match (self, other) {
+            (Damage::Full, _) | (_, Damage::Full) => Damage::Full,
+            (Damage::None, damage) | (damage, Damage::None) => damage,
+            (Damage::Region(a), Damage::Region(b)) => Damage::Region(a.union(b)),
+        }
+    }
+
+    /// Get the damage to a container, given the region that this widget takes up inside of it.
+    ///
+    /// The damage is moved into the container's coordinates and limited to `region`.
+    pub fn within(self, region: Rectangle) -> Damage {
+        match self {
+            Damage::None => Damage::None,
+            Damage::Full => Damage::Region(region),
+            Damage::Region(damage) => Damage::Region(
+                Rectangle {
+                    x: damage.x + region.x,
+                    y: damage.y + region.y,
+                    ..damage
+                }
+                .intersect(region),
+            ),
+        }
+    }
+}
+
+impl From<bool> for Damage {
+    fn from(redraw: bool) -> Self {
+        if redraw {
+            Damage::Full
+        } else {
+            Damage::None
+        }
+    }
+}
+
+/// A key on the keyboard.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Key {
+    /// The tab key.
+    Tab,
+
+    /// The enter or return key.
+    Enter,
+
+    /// The escape key.
+    Escape,
+
+    /// The backspace key.
+    Backspace,
+
+    /// The delete key.
+    Delete,
+
+    /// The space bar.
+    Space,
+
+    /// The left arrow key.
+    Left,
+
+    /// The right arrow key.
+    Right,
+
+    /// The up arrow key.
+    Up,
+
+    /// The down arrow key.
+    Down,
+
+    /// The home key.
+    Home,
+
+    /// The end key.
+    End,
+
+    /// The page up key.
+    PageUp,
+
+    /// The page down key.
+    PageDown,
+
+    /// The insert key.
+    Insert,
+
+    /// A function key, such as F1.
+    Function(u8),
+
+    /// A key that produces a character, such as a letter or a digit.
+    Character(char),
+
+    /// A key that this crate doesn't know about.
+    ///
+    /// The physical key can still be told apart through [`KeyEvent::scancode`].
+    Unidentified,
+}
+
+/// The modifier keys held down during an event.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Modifiers {
+    /// Whether shift is held down.
+    pub shift: bool,
+
+    /// Whether control is held down.
+    pub control: bool,
+
+    /// Whether alt is held down.
+    pub alt: bool,
+
+    /// Whether the logo key (Windows or Command) is held down.
+    pub logo: bool,
 }
 
 /// Two dimensional rectangle.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Rectangle {
     /// The x coordinate of the top left corner.
     pub x: i32,
@@ -140,8 +629,86 @@ pub struct Rectangle {
     pub height: u32,
 }
 
+impl Rectangle {
+    /// Whether a point is inside of this rectangle.
+    pub fn contains(self, x: f64, y: f64) -> bool {
+        x >= self.x as f64
+            && y >= self.y as f64
+            && x < self.right() as f64
+            && y < self.bottom() as f64
+    }
+
+    /// Whether this rectangle covers no area.
+    pub fn is_empty(self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    /// Get the smallest rectangle that covers both this rectangle and `other`.
+    ///
+    /// Rectangles with no area are ignored.
+    pub fn union(self, other: Rectangle) -> Rectangle {
+        if self.is_empty() {
+            return other;
+        } else if other.is_empty() {
+            return self;
+        }
+
+        let left = self.x.min(other.x);
+        let top = self.y.min(other.y);
+        let right = self.right().max(other.right());
+        let bottom = self.bottom().max(other.bottom());
+
+        // The right and bottom edges are never before the left and top ones.
+        Rectangle {
+            x: left,
+            y: top,
+            width: right.abs_diff(left),
+            height: bottom.abs_diff(top),
+        }
+    }
+
+    /// Get the area covered by both this rectangle and `other`.
+    ///
+    /// If they do not overlap, this returns a rectangle with no area.
+    pub fn intersect(self, other: Rectangle) -> Rectangle {
+        let left = self.x.max(other.x);
+        let top = self.y.max(other.y);
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+
+        Rectangle {
+            x: left,
+            y: top,
+            width: if right > left {
+                right.abs_diff(left)
+            } else {
+                0
+            },
+            height: if bottom > top {
+                bottom.abs_diff(top)
+            } else {
+                0
+            },
+        }
+    }
+
+    /// Get the x coordinate of the right edge.
+    ///
+    /// Edges past the largest coordinate are clamped to it.
+    fn right(self) -> i32 {
+        i32::try_from(i64::from(self.x) + i64::from(self.width)).unwrap_or(i32::MAX)
+    }
+
+    /// Get the y coordinate of the bottom edge.
+    ///
+    /// Edges past the largest coordinate are clamped to it.
+    fn bottom(self) -> i32 {
+        i32::try_from(i64::from(self.y) + i64::from(self.height)).unwrap_or(i32::MAX)
+    }
+}
+
 /// Two dimensional size.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Size {
     /// The width of the size.
     pub width: u32,
@@ -149,3 +716,157 @@ pub struct Size {
     /// The height of the size.
     pub height: u32,
 }
+
+/// The range of sizes that a widget is allowed to take up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BoxConstraints {
+    /// The smallest allowed size.
+    pub min: Size,
+
+    /// The largest allowed size.
+    ///
+    /// A length of `u32::MAX` means that the widget can be as large as it wants along that axis.
+    pub max: Size,
+}
+
+impl Default for BoxConstraints {
+    fn default() -> Self {
+        Self::UNBOUNDED
+    }
+}
+
+impl BoxConstraints {
+    /// Constraints that allow any size.
+    pub const UNBOUNDED: BoxConstraints = BoxConstraints {
+        min: Size {
+            width: 0,
+            height: 0,
+        },
+        max: Size {
+            width: u32::MAX,
+            height: u32::MAX,
+        },
+    };
+
+    /// Constraints that only allow exactly the given size.
+    pub fn tight(size: Size) -> Self {
+        Self {
+            min: size,
+            max: size,
+        }
+    }
+
+    /// Constraints that allow any size up to the given size.
+    pub fn loose(max: Size) -> Self {
+        Self {
+            min: Size::default(),
+            max,
+        }
+    }
+
+    /// Whether only a single size is allowed.
+    pub fn is_tight(&self) -> bool {
+        self.min == self.max
+    }
+
+    /// Whether the width is limited.
+    pub fn has_bounded_width(&self) -> bool {
+        self.max.width != u32::MAX
+    }
+
+    /// Whether the height is limited.
+    pub fn has_bounded_height(&self) -> bool {
+        self.max.height != u32::MAX
+    }
+
+    /// Get the allowed size closest to the given size.
+    pub fn constrain(&self, size: Size) -> Size {
+        Size {
+            width: size
+                .width
+                .clamp(self.min.width, self.max.width.max(self.min.width)),
+            height: size
+                .height
+                .clamp(self.min.height, self.max.height.max(self.min.height)),
+        }
+    }
+
+    /// Remove the minimum size, so that any size up to the maximum is allowed.
+    pub fn loosen(self) -> Self {
+        Self::loose(self.max)
+    }
+
+    /// Shrink the constraints by the given amount on each axis.
+    ///
+    /// This is used to get the constraints for the content inside of padding.
+    pub fn deflate(self, width: u32, height: u32) -> Self {
+        let shrink = |length: u32, by: u32| {
+            if length == u32::MAX {
+                length
+            } else {
+                length.saturating_sub(by)
+            }
+        };
+
+        Self {
+            min: Size {
+                width: self.min.width.saturating_sub(width),
+                height: self.min.height.saturating_sub(height),
+            },
+            max: Size {
+                width: shrink(self.max.width, width),
+                height: shrink(self.max.height, height),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: i32, y: i32, width: u32, height: u32) -> Rectangle {
+        Rectangle {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn unions_cover_both_rectangles() {
+        assert_eq!(
+            rect(0, 0, 10, 10).union(rect(20, -5, 5, 5)),
+            rect(0, -5, 25, 15)
+        );
+        assert_eq!(rect(0, 0, 0, 10).union(rect(5, 5, 1, 1)), rect(5, 5, 1, 1));
+
+        // Unbounded rectangles stop at the largest coordinate.
+        let huge = rect(-10, 0, u32::MAX, 10);
+        assert_eq!(huge.right(), i32::MAX);
+        assert_eq!(
+            huge.union(rect(0, 0, 1, 1)),
+            rect(-10, 0, i32::MAX as u32 + 10, 10)
+        );
+        assert_eq!(
+            rect(i32::MIN, 0, 1, 1).union(huge),
+            rect(i32::MIN, 0, u32::MAX, 10)
+        );
+    }
+
+    #[test]
+    fn intersections_cover_the_overlap() {
+        assert_eq!(
+            rect(0, 0, 10, 10).intersect(rect(5, -5, 10, 10)),
+            rect(5, 0, 5, 5)
+        );
+        assert!(rect(0, 0, 10, 10).intersect(rect(20, 0, 5, 5)).is_empty());
+
+        // Unbounded rectangles stop at the largest coordinate.
+        let huge = rect(-10, -10, u32::MAX, u32::MAX);
+        assert_eq!(huge.right(), i32::MAX);
+        assert_eq!(huge.intersect(rect(0, 0, 5, 5)), rect(0, 0, 5, 5));
+        assert!(huge.contains(1e6, 1e6));
+    }
+}
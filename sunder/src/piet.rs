@@ -19,46 +19,631 @@ License along with `sunder`. If not, see <https://www.gnu.org/licenses/>.
 
 //! A backend for rendering widgets to a `piet` context.
 
-use super::Backend;
-use ui_theme::Theme;
+use super::{Backend, Rectangle};
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use piet::kurbo::{Affine, BezPath, Insets, Rect, RoundedRect, RoundedRectRadii, Shape, Vec2};
+use piet::{Color, GradientStop, LinearGradient, PaintBrush, UnitPoint};
+use ui_theme::{Background, Theme, WidgetProperties};
 
 pub use piet;
+pub use ui_theme;
+
+pub mod testing;
 
 /// A backend oriented around a [`piet::RenderContext`].
 ///
+/// The backend owns its context, so that it can be named without any lifetimes.
+///
 /// [`piet::RenderContext`]: https://docs.rs/piet/latest/piet/trait.RenderContext.html
-pub struct PietBackend<'a, C: ?Sized> {
-    context: &'a mut C,
-    theme: &'a Theme,
+pub struct PietBackend<C> {
+    context: C,
+    theme: Rc<Theme>,
 }
 
-impl<'a, C: piet::RenderContext + ?Sized> PietBackend<'a, C> {
+impl<C: piet::RenderContext> PietBackend<C> {
     /// Create a new backend from a `piet` context.
-    pub fn new(context: &'a mut C, theme: &'a Theme) -> Self {
+    pub fn new(context: C, theme: Rc<Theme>) -> Self {
         Self { context, theme }
     }
 
     /// Get the underlying `piet` context.
     pub fn context(&mut self) -> &mut C {
-        self.context
+        &mut self.context
     }
 
     /// Get the underlying theme.
     pub fn theme(&self) -> &Theme {
-        self.theme
+        &self.theme
+    }
+
+    /// Take the underlying `piet` context out of this backend.
+    pub fn into_context(self) -> C {
+        self.context
     }
 }
 
-impl<C: piet::RenderContext + ?Sized> Backend for PietBackend<'_, C> {
+impl<C: piet::RenderContext> Backend for PietBackend<C> {
     type Error = piet::Error;
     type Output = ();
+
+    fn with_region<T>(
+        &mut self,
+        region: Rectangle,
+        f: impl FnOnce(&mut Self) -> Result<T, Self::Error>,
+    ) -> Result<T, Self::Error> {
+        self.context.save()?;
+        self.context
+            .transform(Affine::translate((region.x as f64, region.y as f64)));
+        self.context.clip(Rect::new(
+            0.0,
+            0.0,
+            region.width as f64,
+            region.height as f64,
+        ));
+
+        let result = f(self);
+        self.context.restore()?;
+        result
+    }
+}
+
+/// How to paint a box, such as the body of a button.
+///
+/// A box is painted from the bottom up: first its shadow, then its background, and then its
+/// border. Whatever goes inside of the box is drawn within its border and padding.
+#[derive(Debug, Clone, Default)]
+pub struct BoxStyle {
+    /// What to fill the box with, if anything.
+    ///
+    /// Linear and radial gradients are placed relative to the outer edge of the box, so they
+    /// stretch with it. Fixed gradients are used as they are, in the coordinates of the context.
+    pub background: Option<PaintBrush>,
+
+    /// The border along each side of the box.
+    ///
+    /// Borders are drawn inside of the box, over its background.
+    pub border: Sides<Border>,
+
+    /// The radius of each corner of the outer edge of the box.
+    pub radii: RoundedRectRadii,
+
+    /// The shadow that the box casts, if any.
+    pub shadow: Option<Shadow>,
+
+    /// The space between the border and whatever goes inside of the box.
+    pub padding: Insets,
+}
+
+impl BoxStyle {
+    /// Style a box with the first of these theme classes that the theme has.
+    ///
+    /// Classes go from the most to the least specific, such as `"button:pressed"` before
+    /// `"button"`. If the theme has none of them, the box is left unpainted.
+    pub fn from_classes<'a>(theme: &Theme, classes: impl IntoIterator<Item = &'a str>) -> Self {
+        classes
+            .into_iter()
+            .find_map(|class| theme.class(class))
+            .map(Self::from)
+            .unwrap_or_default()
+    }
+
+    /// The space taken up by the border and padding, between the edge of the box and whatever
+    /// goes inside of it.
+    pub fn insets(&self) -> Insets {
+        let (border, padding) = (&self.border, &self.padding);
+        Insets::new(
+            border.left.width + padding.x0,
+            border.top.width + padding.y0,
+            border.right.width + padding.x1,
+            border.bottom.width + padding.y1,
+        )
+    }
+}
+
+impl From<&WidgetProperties> for BoxStyle {
+    fn from(properties: &WidgetProperties) -> Self {
+        let side = |side: &ui_theme::BorderSide| Border {
+            width: side.width,
+            color: color(side.color),
+        };
+        let border = &properties.border;
+        let radius = &properties.radius;
+        let padding = &properties.padding;
+
+        Self {
+            background: properties.background.as_ref().map(brush),
+            border: Sides {
+                top: side(&border.top),
+                right: side(&border.right),
+                bottom: side(&border.bottom),
+                left: side(&border.left),
+            },
+            radii: RoundedRectRadii::new(
+                radius.top_left,
+                radius.top_right,
+                radius.bottom_right,
+                radius.bottom_left,
+            ),
+            shadow: properties.shadow.as_ref().map(|shadow| Shadow {
+                offset: shadow.offset.into(),
+                blur: shadow.blur,
+                spread: shadow.spread,
+                color: color(shadow.color),
+            }),
+            padding: Insets::new(padding.left, padding.top, padding.right, padding.bottom),
+        }
+    }
+}
+
+/// Convert a theme background into a `piet` brush.
+fn brush(background: &Background) -> PaintBrush {
+    match background {
+        Background::Color(fill) => color(*fill).into(),
+        Background::LinearGradient { start, end, stops } => {
+            let stops = stops
+                .iter()
+                .map(|&(pos, stop)| GradientStop {
+                    pos,
+                    color: color(stop),
+                })
+                .collect::<Vec<_>>();
+            LinearGradient::new(
+                UnitPoint::new(start.0, start.1),
+                UnitPoint::new(end.0, end.1),
+                stops,
+            )
+            .into()
+        }
+    }
+}
+
+/// Convert a theme color into a `piet` color.
+fn color(color: ui_theme::Color) -> Color {
+    Color::rgba8(color.red, color.green, color.blue, color.alpha)
+}
+
+/// A value for each side of a box.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Sides<T> {
+    pub top: T,
+    pub right: T,
+    pub bottom: T,
+    pub left: T,
+}
+
+impl<T: Copy> Sides<T> {
+    /// Use the same value for every side.
+    pub fn all(value: T) -> Self {
+        Self {
+            top: value,
+            right: value,
+            bottom: value,
+            left: value,
+        }
+    }
+}
+
+/// The border along one side of a box.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Border {
+    /// How thick the border is, in pixels.
+    pub width: f64,
+
+    /// The color of the border.
+    pub color: Color,
+}
+
+impl Default for Border {
+    fn default() -> Self {
+        Self {
+            width: 0.0,
+            color: Color::TRANSPARENT,
+        }
+    }
+}
+
+/// A blurred shadow cast by a box.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Shadow {
+    /// How far the shadow is moved from the box.
+    pub offset: Vec2,
+
+    /// How far the edges of the shadow are blurred.
+    pub blur: f64,
+
+    /// How much bigger the shadow is than the box, on each side.
+    pub spread: f64,
+
+    /// The color of the shadow.
+    pub color: Color,
+}
+
+/// The accuracy that curves are flattened to, in pixels.
+const TOLERANCE: f64 = 0.1;
+
+/// Paint a box styled by a theme class, returning the area inside of its border and padding.
+///
+/// See [`draw_box`] for how the box is painted.
+pub fn draw_rectangle(
+    rc: &mut impl piet::RenderContext,
+    properties: &WidgetProperties,
+    rectangle: Rectangle,
+) -> Result<Rectangle, piet::Error> {
+    draw_box(rc, &properties.into(), rectangle)
 }
 
-/// Utility function for drawing a box based off of a theme class.
-pub(crate) fn draw_rectangle(
-    rc: &mut (impl piet::RenderContext + ?Sized),
-    theme: &ui_theme::WidgetProperties,
-    rectangle: crate::Rectangle
-) -> Result<(), piet::Error> {
-    todo!()
+/// Paint a box, returning the area inside of its border and padding.
+///
+/// The shadow is drawn outside of `rectangle`, so it is cut off if `rectangle` fills the region
+/// that is being drawn into.
+pub fn draw_box(
+    rc: &mut impl piet::RenderContext,
+    style: &BoxStyle,
+    rectangle: Rectangle,
+) -> Result<Rectangle, piet::Error> {
+    let outer = Rect::new(
+        rectangle.x as f64,
+        rectangle.y as f64,
+        rectangle.x as f64 + rectangle.width as f64,
+        rectangle.y as f64 + rectangle.height as f64,
+    );
+    let border = &style.border;
+
+    if let Some(shadow) = &style.shadow {
+        let brush = rc.solid_brush(shadow.color);
+        let shadow_rect = (outer + shadow.offset).inflate(shadow.spread, shadow.spread);
+        rc.blurred_rect(shadow_rect, shadow.blur, &brush);
+    }
+
+    let outer_shape = RoundedRect::from_rect(outer, style.radii);
+    if let Some(background) = &style.background {
+        rc.fill(outer_shape, background);
+    }
+
+    // The inner edge of the border is rounded less, so that the border keeps its width around
+    // each corner.
+    let x0 = (outer.x0 + border.left.width).min(outer.x1);
+    let y0 = (outer.y0 + border.top.width).min(outer.y1);
+    let inner = Rect::new(
+        x0,
+        y0,
+        (outer.x1 - border.right.width).max(x0),
+        (outer.y1 - border.bottom.width).max(y0),
+    );
+    let radii = outer_shape.radii();
+    let shrink = |radius: f64, a: Border, b: Border| (radius - a.width.max(b.width)).max(0.0);
+    let inner_shape = RoundedRect::from_rect(
+        inner,
+        RoundedRectRadii {
+            top_left: shrink(radii.top_left, border.top, border.left),
+            top_right: shrink(radii.top_right, border.top, border.right),
+            bottom_right: shrink(radii.bottom_right, border.bottom, border.right),
+            bottom_left: shrink(radii.bottom_left, border.bottom, border.left),
+        },
+    );
+
+    // The border is the ring between the outer and inner edges.
+    let mut ring = outer_shape.to_path(TOLERANCE);
+    ring.extend(inner_shape.path_elements(TOLERANCE));
+
+    if border.top == border.right && border.top == border.bottom && border.top == border.left {
+        if border.top.width > 0.0 {
+            let brush = rc.solid_brush(border.top.color);
+            rc.fill_even_odd(&ring, &brush);
+        }
+    } else {
+        // Each side gets the part of the ring between the lines joining the outer and inner
+        // corners on either side of it.
+        let (o, i) = (outer, inner);
+        let sides = [
+            (
+                border.top,
+                [(o.x0, o.y0), (o.x1, o.y0), (i.x1, i.y0), (i.x0, i.y0)],
+            ),
+            (
+                border.right,
+                [(o.x1, o.y0), (o.x1, o.y1), (i.x1, i.y1), (i.x1, i.y0)],
+            ),
+            (
+                border.bottom,
+                [(o.x1, o.y1), (o.x0, o.y1), (i.x0, i.y1), (i.x1, i.y1)],
+            ),
+            (
+                border.left,
+                [(o.x0, o.y1), (o.x0, o.y0), (i.x0, i.y0), (i.x0, i.y1)],
+            ),
+        ];
+
+        for (side, corners) in sides {
+            if side.width <= 0.0 {
+                continue;
+            }
+
+            let mut wedge = BezPath::new();
+            wedge.move_to(corners[0]);
+            for &corner in &corners[1..] {
+                wedge.line_to(corner);
+            }
+            wedge.close_path();
+
+            let brush = rc.solid_brush(side.color);
+            rc.save()?;
+            rc.clip(wedge);
+            rc.fill_even_odd(&ring, &brush);
+            rc.restore()?;
+        }
+    }
+
+    // Round inwards, so that the contents never overlap the border.
+    let content = inner - style.padding;
+    let (x0, y0) = (content.x0.ceil(), content.y0.ceil());
+    let (x1, y1) = (content.x1.floor().max(x0), content.y1.floor().max(y0));
+
+    Ok(Rectangle {
+        x: x0 as i32,
+        y: y0 as i32,
+        width: (x1 - x0) as u32,
+        height: (y1 - y0) as u32,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::testing::{Brush, Op, RecordingContext};
+    use super::*;
+
+    use alloc::vec;
+    use piet::kurbo::Point;
+    use piet::FixedGradient;
+    use ui_theme::{BorderSide, BoxShadow, Corners, Edges};
+
+    fn rgb(red: u8, green: u8, blue: u8) -> ui_theme::Color {
+        ui_theme::Color {
+            red,
+            green,
+            blue,
+            alpha: 0xff,
+        }
+    }
+
+    /// Whether a shape filled with the even-odd rule covers a point.
+    fn covers(shape: &BezPath, point: (f64, f64)) -> bool {
+        shape.winding(point.into()) % 2 != 0
+    }
+
+    /// Get the color of a solid brush.
+    fn solid(brush: &Brush) -> Color {
+        match brush {
+            Brush::Solid(color) => *color,
+            brush => panic!("expected a solid brush, got {:?}", brush),
+        }
+    }
+
+    #[test]
+    fn paints_themed_box() {
+        let properties = WidgetProperties {
+            background: Some(Background::Color(rgb(0xee, 0xee, 0xee))),
+            border: Edges {
+                top: BorderSide {
+                    width: 2.0,
+                    color: rgb(0xff, 0, 0),
+                },
+                right: BorderSide {
+                    width: 2.0,
+                    color: rgb(0xff, 0, 0),
+                },
+                bottom: BorderSide {
+                    width: 2.0,
+                    color: rgb(0xff, 0, 0),
+                },
+                left: BorderSide {
+                    width: 2.0,
+                    color: rgb(0xff, 0, 0),
+                },
+            },
+            radius: Corners {
+                top_left: 4.0,
+                top_right: 4.0,
+                bottom_right: 4.0,
+                bottom_left: 4.0,
+            },
+            shadow: Some(BoxShadow {
+                offset: (1.0, 2.0),
+                blur: 3.0,
+                spread: 1.0,
+                color: rgb(0, 0, 0),
+            }),
+            padding: Edges {
+                top: 3.0,
+                right: 3.0,
+                bottom: 3.0,
+                left: 3.0,
+            },
+        };
+
+        let mut rc = RecordingContext::new();
+        let content = draw_rectangle(
+            &mut rc,
+            &properties,
+            Rectangle {
+                x: 10,
+                y: 20,
+                width: 100,
+                height: 50,
+            },
+        )
+        .unwrap();
+
+        // The content is inside of the border and the padding.
+        assert_eq!(
+            content,
+            Rectangle {
+                x: 15,
+                y: 25,
+                width: 90,
+                height: 40,
+            }
+        );
+
+        let ops = rc.take_ops();
+        assert_eq!(ops.len(), 3, "{:?}", ops);
+
+        // The shadow is offset and spread out from the box.
+        match &ops[0] {
+            Op::BlurredRect {
+                rect,
+                blur_radius,
+                brush,
+            } => {
+                assert_eq!(*rect, Rect::new(10.0, 21.0, 112.0, 73.0));
+                assert_eq!(*blur_radius, 3.0);
+                assert_eq!(solid(brush), Color::BLACK);
+            }
+            op => panic!("expected a shadow, got {:?}", op),
+        }
+
+        // The background fills the box, except for its rounded corners.
+        match &ops[1] {
+            Op::Fill { shape, brush, .. } => {
+                assert_eq!(solid(brush), Color::rgb8(0xee, 0xee, 0xee));
+                assert_eq!(
+                    shape.bounding_box().round(),
+                    Rect::new(10.0, 20.0, 110.0, 70.0)
+                );
+                assert!(covers(shape, (60.0, 45.0)));
+                assert!(covers(shape, (14.0, 20.5)));
+                assert!(!covers(shape, (10.5, 20.5)));
+            }
+            op => panic!("expected the background, got {:?}", op),
+        }
+
+        // The border is a ring around the inside of the box.
+        match &ops[2] {
+            Op::Fill {
+                shape,
+                brush,
+                even_odd: true,
+            } => {
+                assert_eq!(solid(brush), Color::rgb8(0xff, 0, 0));
+                assert!(covers(shape, (11.0, 45.0)));
+                assert!(covers(shape, (60.0, 69.0)));
+                assert!(!covers(shape, (13.0, 45.0)));
+                assert!(!covers(shape, (60.0, 45.0)));
+                assert!(!covers(shape, (10.5, 20.5)));
+            }
+            op => panic!("expected the border, got {:?}", op),
+        }
+    }
+
+    #[test]
+    fn paints_each_side_of_border() {
+        let side = |width, color| BorderSide { width, color };
+        let properties = WidgetProperties {
+            border: Edges {
+                top: side(1.0, rgb(0xff, 0, 0)),
+                right: side(2.0, rgb(0, 0xff, 0)),
+                bottom: side(3.0, rgb(0, 0, 0xff)),
+                left: side(0.0, rgb(0, 0, 0)),
+            },
+            radius: Corners {
+                top_left: 1.0,
+                top_right: 2.0,
+                bottom_right: 3.0,
+                bottom_left: 4.0,
+            },
+            ..Default::default()
+        };
+
+        let style = BoxStyle::from(&properties);
+        assert_eq!(style.radii, RoundedRectRadii::new(1.0, 2.0, 3.0, 4.0));
+
+        let mut rc = RecordingContext::new();
+        let content = draw_box(
+            &mut rc,
+            &style,
+            Rectangle {
+                x: 0,
+                y: 0,
+                width: 40,
+                height: 30,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            content,
+            Rectangle {
+                x: 0,
+                y: 1,
+                width: 38,
+                height: 26,
+            }
+        );
+
+        // Each side with a border is clipped out of the ring and filled with its own color, and
+        // there is no background or shadow.
+        let ops = rc.take_ops();
+        let sides = ops
+            .chunks(4)
+            .map(|chunk| match chunk {
+                [Op::Save, Op::Clip(wedge), Op::Fill { shape, brush, .. }, Op::Restore] => {
+                    (wedge.clone(), shape.clone(), solid(brush))
+                }
+                ops => panic!("expected a side of the border, got {:?}", ops),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(sides.len(), 3);
+
+        let expected = [
+            ((20.0, 0.5), Color::rgb8(0xff, 0, 0)),
+            ((39.0, 15.0), Color::rgb8(0, 0xff, 0)),
+            ((20.0, 28.5), Color::rgb8(0, 0, 0xff)),
+        ];
+        for ((wedge, ring, color), (point, expected)) in sides.iter().zip(expected) {
+            assert_eq!(*color, expected);
+            assert!(wedge.contains(point.into()));
+            assert!(covers(ring, point));
+            assert!(!covers(ring, (20.0, 15.0)));
+        }
+    }
+
+    #[test]
+    fn gradients_cover_the_box() {
+        let properties = WidgetProperties {
+            background: Some(Background::LinearGradient {
+                start: (0.0, 0.5),
+                end: (1.0, 0.5),
+                stops: vec![(0.0, rgb(0xff, 0xff, 0xff)), (1.0, rgb(0, 0, 0))],
+            }),
+            ..Default::default()
+        };
+
+        let mut rc = RecordingContext::new();
+        draw_rectangle(
+            &mut rc,
+            &properties,
+            Rectangle {
+                x: 10,
+                y: 20,
+                width: 100,
+                height: 50,
+            },
+        )
+        .unwrap();
+
+        // The gradient runs across the outer edge of the box.
+        match rc.ops() {
+            [Op::Fill {
+                brush: Brush::Gradient(FixedGradient::Linear(gradient)),
+                ..
+            }] => {
+                assert_eq!(gradient.start, Point::new(10.0, 45.0));
+                assert_eq!(gradient.end, Point::new(110.0, 45.0));
+                assert_eq!(gradient.stops.len(), 2);
+                assert_eq!(gradient.stops[0].color, Color::WHITE);
+                assert_eq!(gradient.stops[1].color, Color::BLACK);
+            }
+            ops => panic!("expected a gradient fill, got {:?}", ops),
+        }
+    }
 }
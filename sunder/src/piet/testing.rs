@@ -0,0 +1,480 @@
+/*
+
+`sunder` is free software: you can redistribute it and/or modify it under the terms of one of
+the following licenses:
+
+- The GNU Affero General Public License as published by the Free Software Foundation, either version
+  3 of the License, or (at your option) any later version.
+- The Patron License at https://github.com/notgull/sunder/blob/main/LICENSE-PATRON.md, for
+  sponsors and contributors, who can ignore the copyleft provisions of the GNU AGPL for this project.
+
+`sunder` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+Public License and the Patron License for more details.
+
+You should have received a copy of the GNU Affero General Public License and the corresponding Patron
+License along with `sunder`. If not, see <https://www.gnu.org/licenses/>.
+
+*/
+
+//! A `piet` context that records what is drawn into it, for testing.
+
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::{Range, RangeBounds};
+
+use piet::kurbo::{Affine, BezPath, Point, Rect, Shape, Size};
+use piet::{
+    Color, Error, FixedGradient, FontFamily, HitTestPoint, HitTestPosition, ImageFormat,
+    InterpolationMode, IntoBrush, LineMetric, NullImage, RenderContext, StrokeStyle, Text,
+    TextAlignment, TextAttribute, TextLayout, TextLayoutBuilder, TextStorage,
+};
+
+/// The width of every character laid out by a [`RecordingContext`].
+pub const CHAR_WIDTH: f64 = 10.0;
+
+/// The height of every line laid out by a [`RecordingContext`].
+pub const LINE_HEIGHT: f64 = 20.0;
+
+/// The distance from the top of a line laid out by a [`RecordingContext`] to its baseline.
+pub const BASELINE: f64 = 15.0;
+
+/// A [`RenderContext`] that records every drawing operation instead of drawing it.
+///
+/// Shapes are recorded in the coordinates of the whole context, after the current transform has
+/// been applied. Images are not drawn, and text is only recorded by where it is drawn.
+///
+/// Text is laid out as if every character were [`CHAR_WIDTH`] wide and every line were
+/// [`LINE_HEIGHT`] tall, so that widgets which work with text layouts can be tested.
+pub struct RecordingContext {
+    /// The operations recorded so far.
+    ops: Vec<Op>,
+
+    /// The current transform.
+    transform: Affine,
+
+    /// The transforms saved by [`RenderContext::save`].
+    saved: Vec<Affine>,
+
+    /// Where text layouts come from.
+    text: MonoText,
+}
+
+/// An operation recorded by a [`RecordingContext`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum Op {
+    /// A shape was filled.
+    Fill {
+        shape: BezPath,
+        brush: Brush,
+        even_odd: bool,
+    },
+
+    /// The outline of a shape was drawn.
+    Stroke {
+        shape: BezPath,
+        brush: Brush,
+        width: f64,
+    },
+
+    /// Drawing was clipped to a shape.
+    Clip(BezPath),
+
+    /// A blurred rectangle was drawn.
+    BlurredRect {
+        rect: Rect,
+        blur_radius: f64,
+        brush: Brush,
+    },
+
+    /// A region was cleared to a color, or the whole context if there is no region.
+    Clear(Option<Rect>, Color),
+
+    /// Text was drawn with its top left corner at a point.
+    Text(Point),
+
+    /// The state of the context was saved.
+    Save,
+
+    /// The last saved state of the context was restored.
+    Restore,
+}
+
+/// A brush used by a [`RecordingContext`].
+#[derive(Debug, Clone)]
+pub enum Brush {
+    /// A solid color.
+    Solid(Color),
+
+    /// A gradient, in the coordinates of the shape it was used with.
+    Gradient(FixedGradient),
+}
+
+impl RecordingContext {
+    /// Create a new, empty recording context.
+    pub fn new() -> Self {
+        Self {
+            ops: Vec::new(),
+            transform: Affine::IDENTITY,
+            saved: Vec::new(),
+            text: MonoText::default(),
+        }
+    }
+
+    /// Get the operations recorded so far.
+    pub fn ops(&self) -> &[Op] {
+        &self.ops
+    }
+
+    /// Take the operations recorded so far, leaving the context empty.
+    pub fn take_ops(&mut self) -> Vec<Op> {
+        core::mem::take(&mut self.ops)
+    }
+
+    /// Get the number of text layouts that have been made so far.
+    pub fn layouts_made(&self) -> usize {
+        self.text.layouts
+    }
+
+    /// Move a shape into the coordinates of the whole context.
+    fn place(&self, shape: impl Shape) -> BezPath {
+        self.transform * shape.into_path(0.1)
+    }
+
+    /// Get the brush that something converts into.
+    fn brush(&mut self, brush: &impl IntoBrush<Self>, bbox: Rect) -> Brush {
+        brush.make_brush(self, || bbox).into_owned()
+    }
+}
+
+impl Default for RecordingContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IntoBrush<RecordingContext> for Brush {
+    fn make_brush<'a>(
+        &'a self,
+        _piet: &mut RecordingContext,
+        _bbox: impl FnOnce() -> Rect,
+    ) -> Cow<'a, Brush> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl RenderContext for RecordingContext {
+    type Brush = Brush;
+    type Text = MonoText;
+    type TextLayout = MonoTextLayout;
+    type Image = NullImage;
+
+    fn status(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn solid_brush(&mut self, color: Color) -> Brush {
+        Brush::Solid(color)
+    }
+
+    fn gradient(&mut self, gradient: impl Into<FixedGradient>) -> Result<Brush, Error> {
+        Ok(Brush::Gradient(gradient.into()))
+    }
+
+    fn clear(&mut self, region: impl Into<Option<Rect>>, color: Color) {
+        self.ops.push(Op::Clear(region.into(), color));
+    }
+
+    fn stroke(&mut self, shape: impl Shape, brush: &impl IntoBrush<Self>, width: f64) {
+        let brush = self.brush(brush, shape.bounding_box());
+        let shape = self.place(shape);
+        self.ops.push(Op::Stroke {
+            shape,
+            brush,
+            width,
+        });
+    }
+
+    fn stroke_styled(
+        &mut self,
+        shape: impl Shape,
+        brush: &impl IntoBrush<Self>,
+        width: f64,
+        _style: &StrokeStyle,
+    ) {
+        self.stroke(shape, brush, width);
+    }
+
+    fn fill(&mut self, shape: impl Shape, brush: &impl IntoBrush<Self>) {
+        let brush = self.brush(brush, shape.bounding_box());
+        let shape = self.place(shape);
+        self.ops.push(Op::Fill {
+            shape,
+            brush,
+            even_odd: false,
+        });
+    }
+
+    fn fill_even_odd(&mut self, shape: impl Shape, brush: &impl IntoBrush<Self>) {
+        let brush = self.brush(brush, shape.bounding_box());
+        let shape = self.place(shape);
+        self.ops.push(Op::Fill {
+            shape,
+            brush,
+            even_odd: true,
+        });
+    }
+
+    fn clip(&mut self, shape: impl Shape) {
+        let shape = self.place(shape);
+        self.ops.push(Op::Clip(shape));
+    }
+
+    fn text(&mut self) -> &mut MonoText {
+        &mut self.text
+    }
+
+    fn draw_text(&mut self, _layout: &MonoTextLayout, pos: impl Into<Point>) {
+        let pos = self.transform * pos.into();
+        self.ops.push(Op::Text(pos));
+    }
+
+    fn save(&mut self) -> Result<(), Error> {
+        self.saved.push(self.transform);
+        self.ops.push(Op::Save);
+        Ok(())
+    }
+
+    fn restore(&mut self) -> Result<(), Error> {
+        self.transform = self.saved.pop().ok_or(Error::StackUnbalance)?;
+        self.ops.push(Op::Restore);
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn transform(&mut self, transform: Affine) {
+        self.transform *= transform;
+    }
+
+    fn make_image_with_stride(
+        &mut self,
+        _width: usize,
+        _height: usize,
+        _stride: usize,
+        _buf: &[u8],
+        _format: ImageFormat,
+    ) -> Result<NullImage, Error> {
+        Ok(NullImage)
+    }
+
+    fn draw_image(
+        &mut self,
+        _image: &NullImage,
+        _dst_rect: impl Into<Rect>,
+        _interp: InterpolationMode,
+    ) {
+    }
+
+    fn draw_image_area(
+        &mut self,
+        _image: &NullImage,
+        _src_rect: impl Into<Rect>,
+        _dst_rect: impl Into<Rect>,
+        _interp: InterpolationMode,
+    ) {
+    }
+
+    fn capture_image_area(&mut self, _src_rect: impl Into<Rect>) -> Result<NullImage, Error> {
+        Ok(NullImage)
+    }
+
+    fn blurred_rect(&mut self, rect: Rect, blur_radius: f64, brush: &impl IntoBrush<Self>) {
+        let brush = self.brush(brush, rect);
+        let rect = self.place(rect).bounding_box();
+        self.ops.push(Op::BlurredRect {
+            rect,
+            blur_radius,
+            brush,
+        });
+    }
+
+    fn current_transform(&self) -> Affine {
+        self.transform
+    }
+}
+
+/// Lays out text for a [`RecordingContext`], giving every character the same size.
+#[derive(Debug, Clone, Default)]
+pub struct MonoText {
+    /// The number of layouts made so far.
+    layouts: usize,
+}
+
+/// Builds a [`MonoTextLayout`].
+pub struct MonoTextLayoutBuilder {
+    /// The text to lay out.
+    text: String,
+
+    /// The width to wrap lines at.
+    max_width: f64,
+}
+
+/// Text laid out by a [`MonoText`].
+///
+/// Lines are wrapped at the first character that doesn't fit, rather than between words.
+#[derive(Debug, Clone)]
+pub struct MonoTextLayout {
+    /// The text that was laid out.
+    text: String,
+
+    /// The range of the text on each wrapped line.
+    lines: Vec<Range<usize>>,
+}
+
+impl Text for MonoText {
+    type TextLayoutBuilder = MonoTextLayoutBuilder;
+    type TextLayout = MonoTextLayout;
+
+    fn font_family(&mut self, _family_name: &str) -> Option<FontFamily> {
+        Some(FontFamily::default())
+    }
+
+    fn load_font(&mut self, _data: &[u8]) -> Result<FontFamily, Error> {
+        Ok(FontFamily::default())
+    }
+
+    fn new_text_layout(&mut self, text: impl TextStorage) -> MonoTextLayoutBuilder {
+        self.layouts += 1;
+        MonoTextLayoutBuilder {
+            text: text.as_str().into(),
+            max_width: f64::INFINITY,
+        }
+    }
+}
+
+impl TextLayoutBuilder for MonoTextLayoutBuilder {
+    type Out = MonoTextLayout;
+
+    fn max_width(self, max_width: f64) -> Self {
+        Self { max_width, ..self }
+    }
+
+    fn alignment(self, _alignment: TextAlignment) -> Self {
+        self
+    }
+
+    fn default_attribute(self, _attribute: impl Into<TextAttribute>) -> Self {
+        self
+    }
+
+    fn range_attribute(
+        self,
+        _range: impl RangeBounds<usize>,
+        _attribute: impl Into<TextAttribute>,
+    ) -> Self {
+        self
+    }
+
+    fn build(self) -> Result<MonoTextLayout, Error> {
+        // Always fit at least one character on a line.
+        let fits = ((self.max_width / CHAR_WIDTH).floor() as usize).max(1);
+
+        let mut lines = Vec::new();
+        let (mut start, mut count) = (0, 0);
+        for (index, _) in self.text.char_indices() {
+            if count == fits {
+                lines.push(start..index);
+                (start, count) = (index, 0);
+            }
+            count += 1;
+        }
+        lines.push(start..self.text.len());
+
+        Ok(MonoTextLayout {
+            text: self.text,
+            lines,
+        })
+    }
+}
+
+impl MonoTextLayout {
+    /// Get the horizontal position of a text position on a wrapped line.
+    fn x(&self, line: usize, position: usize) -> f64 {
+        let start = self.lines[line].start;
+        self.text[start..position].chars().count() as f64 * CHAR_WIDTH
+    }
+}
+
+impl TextLayout for MonoTextLayout {
+    fn size(&self) -> Size {
+        let width = (0..self.lines.len())
+            .map(|line| self.x(line, self.lines[line].end))
+            .fold(0.0, f64::max);
+
+        Size::new(width, self.lines.len() as f64 * LINE_HEIGHT)
+    }
+
+    fn trailing_whitespace_width(&self) -> f64 {
+        self.size().width
+    }
+
+    fn image_bounds(&self) -> Rect {
+        self.size().to_rect()
+    }
+
+    fn text(&self) -> &str {
+        &self.text
+    }
+
+    fn line_text(&self, line_number: usize) -> Option<&str> {
+        let range = self.lines.get(line_number)?;
+        Some(&self.text[range.clone()])
+    }
+
+    fn line_metric(&self, line_number: usize) -> Option<LineMetric> {
+        let range = self.lines.get(line_number)?;
+        Some(LineMetric {
+            start_offset: range.start,
+            end_offset: range.end,
+            trailing_whitespace: 0,
+            baseline: BASELINE,
+            height: LINE_HEIGHT,
+            y_offset: line_number as f64 * LINE_HEIGHT,
+        })
+    }
+
+    fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    fn hit_test_point(&self, point: Point) -> HitTestPoint {
+        let last = self.lines.len() - 1;
+        let line = ((point.y / LINE_HEIGHT).floor().max(0.0) as usize).min(last);
+        let range = self.lines[line].clone();
+
+        // Go to the nearest edge of a character.
+        let column = (point.x / CHAR_WIDTH).round().max(0.0) as usize;
+        let idx = self.text[range.clone()]
+            .char_indices()
+            .nth(column)
+            .map_or(range.end, |(index, _)| range.start + index);
+
+        let size = self.size();
+        let inside =
+            point.x >= 0.0 && point.y >= 0.0 && point.x < size.width && point.y < size.height;
+        HitTestPoint::new(idx, inside)
+    }
+
+    fn hit_test_text_position(&self, idx: usize) -> HitTestPosition {
+        // A position where a line wraps is at the start of the next line.
+        let idx = idx.min(self.text.len());
+        let line = self.lines[1..].partition_point(|range| range.start <= idx);
+        let point = Point::new(self.x(line, idx), line as f64 * LINE_HEIGHT + BASELINE);
+        HitTestPosition::new(point, line)
+    }
+}
@@ -0,0 +1,473 @@
+/*
+
+`sunder` is free software: you can redistribute it and/or modify it under the terms of one of
+the following licenses:
+
+- The GNU Affero General Public License as published by the Free Software Foundation, either version
+  3 of the License, or (at your option) any later version.
+- The Patron License at https://github.com/notgull/sunder/blob/main/LICENSE-PATRON.md, for
+  sponsors and contributors, who can ignore the copyleft provisions of the GNU AGPL for this project.
+
+`sunder` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+Public License and the Patron License for more details.
+
+You should have received a copy of the GNU Affero General Public License and the corresponding Patron
+License along with `sunder`. If not, see <https://www.gnu.org/licenses/>.
+
+*/
+
+//! Routing events through a tree of widgets.
+//!
+//! A container implements [`Container`] to describe where its children are, and then passes its
+//! events to [`dispatch`]. Pointer events go to the topmost child under the pointer, translated
+//! into that child's coordinates. Focus and keyboard events go to the child with keyboard focus,
+//! which the [`Router`] remembers. Tab and Shift+Tab move focus between the focusable children,
+//! and pressing a focusable child moves focus to it.
+//!
+//! A child that [captures pointers](crate::Widget::captures_pointer) keeps receiving a pointer
+//! that was pressed on it until every button is released or the pointer is cancelled, even once
+//! the pointer moves off of it. Containers can also [capture](Router::capture) pointers for a
+//! child themselves.
+//!
+//! Each event goes through two phases. In the capture phase, the container sees the event before
+//! its children, and can keep it from them. In the bubble phase, the container sees the event
+//! after the child under the pointer has handled it.
+
+use crate::{Damage, Event, Key, KeyEvent, PointerKind, Rectangle};
+use alloc::vec::Vec;
+
+/// A widget that routes events to its children.
+pub trait Container {
+    /// Get the number of children.
+    fn child_count(&self) -> usize;
+
+    /// Get the region of a child, relative to the container.
+    ///
+    /// Children later in the list are drawn on top of earlier ones.
+    fn child_region(&self, index: usize) -> Rectangle;
+
+    /// Get the part of a child that can be seen, relative to the container.
+    ///
+    /// Pointer events outside of this part are never routed to the child, and redraws are limited
+    /// to it. This is the whole child by default.
+    fn child_clip(&self, index: usize) -> Rectangle {
+        self.child_region(index)
+    }
+
+    /// Deliver an event to a child, already translated into the child's coordinates.
+    fn child_event(&mut self, index: usize, event: Event) -> Damage;
+
+    /// Whether a child captures pointers that are pressed on it.
+    ///
+    /// This is `false` by default.
+    fn child_captures_pointer(&self, index: usize) -> bool {
+        let _ = index;
+        false
+    }
+
+    /// Whether a child can receive keyboard focus.
+    ///
+    /// This is `false` by default.
+    fn child_is_focusable(&self, index: usize) -> bool {
+        let _ = index;
+        false
+    }
+
+    /// Whether pressing Tab, or Shift+Tab if `forwards` is `false`, moves focus within a child
+    /// rather than out of it.
+    ///
+    /// This is `false` by default.
+    fn child_steps_focus(&self, index: usize, forwards: bool) -> bool {
+        let _ = (index, forwards);
+        false
+    }
+
+    /// Get the router that remembers where pointers and focus are for this container.
+    fn router(&mut self) -> &mut Router;
+
+    /// Look at an event before any child does.
+    ///
+    /// Returning `Some` stops the event here, and the damage is what needs to be redrawn.
+    fn capture(&mut self, event: &Event) -> Option<Damage> {
+        let _ = event;
+        None
+    }
+
+    /// Look at an event after the child it was routed to, if any, has handled it.
+    fn bubble(&mut self, event: &Event, target: Option<usize>) -> Damage {
+        let _ = (event, target);
+        Damage::None
+    }
+}
+
+/// Remembers which children have captured pointers, which one the mouse is over, and which one
+/// has keyboard focus.
+#[derive(Debug, Default, Clone)]
+pub struct Router {
+    /// The children that have captured each pointer.
+    captured: Vec<(PointerKind, usize)>,
+
+    /// The child that the mouse is over.
+    hovered: Option<usize>,
+
+    /// The child that has keyboard focus, or had it when the container last lost focus.
+    focused: Option<usize>,
+
+    /// Whether the container has keyboard focus.
+    has_focus: bool,
+}
+
+/// Where a single event goes.
+#[derive(Debug, Default)]
+struct Route {
+    /// The child that the mouse just left.
+    leave: Option<usize>,
+
+    /// The child that the mouse just entered.
+    enter: Option<usize>,
+
+    /// The child that gets the event itself.
+    target: Option<usize>,
+}
+
+impl Router {
+    /// Create a router where no pointers are captured or hovering.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the child that the mouse is over.
+    pub fn hovered(&self) -> Option<usize> {
+        self.hovered
+    }
+
+    /// Get the child that has keyboard focus while the container does.
+    ///
+    /// Once the container loses focus, this is the child that gets focus back when it returns.
+    pub fn focused(&self) -> Option<usize> {
+        self.focused
+    }
+
+    /// Get the child that has captured a pointer.
+    pub fn captured(&self, kind: PointerKind) -> Option<usize> {
+        self.captured
+            .iter()
+            .find(|&&(captured, _)| captured == kind)
+            .map(|&(_, index)| index)
+    }
+
+    /// Get every pointer that has been captured, and the child that captured it.
+    pub fn captured_pointers(&self) -> impl Iterator<Item = (PointerKind, usize)> + '_ {
+        self.captured.iter().copied()
+    }
+
+    /// Send every event for a pointer to a child, until the pointer is released or cancelled.
+    ///
+    /// This takes the pointer from any child that had captured it before.
+    pub fn capture(&mut self, kind: PointerKind, index: usize) {
+        self.release(kind);
+        self.captured.push((kind, index));
+    }
+
+    /// Go back to routing a pointer by its position.
+    pub fn release(&mut self, kind: PointerKind) {
+        self.captured.retain(|&(captured, _)| captured != kind);
+    }
+
+    /// Go back to routing every pointer that a child has captured by its position.
+    pub fn release_all(&mut self, index: usize) {
+        self.captured.retain(|&(_, captured)| captured != index);
+    }
+
+    /// Forget about every pointer, and about which child has focus.
+    ///
+    /// This should be called if children are removed or reordered.
+    pub fn reset(&mut self) {
+        self.captured.clear();
+        self.hovered = None;
+        self.focused = None;
+    }
+
+    /// Move the mouse over a new child.
+    fn hover(&mut self, route: &mut Route, hit: Option<usize>) {
+        if self.hovered != hit {
+            route.leave = self.hovered;
+            route.enter = hit;
+            self.hovered = hit;
+        }
+    }
+
+    /// Decide where an event goes, given the topmost child under it and whether that child
+    /// captures pointers.
+    fn route(&mut self, event: &Event, hit: Option<usize>, captures: bool) -> Route {
+        let mut route = Route::default();
+
+        match event {
+            Event::PointerDown(pointer) => match self.captured(pointer.kind) {
+                Some(captured) => route.target = Some(captured),
+                None => {
+                    if pointer.kind == PointerKind::Mouse {
+                        self.hover(&mut route, hit);
+                    }
+                    match hit {
+                        Some(target) if captures && !pointer.buttons.is_empty() => {
+                            self.capture(pointer.kind, target)
+                        }
+                        _ => {}
+                    }
+                    route.target = hit;
+                }
+            },
+
+            Event::PointerMove(pointer) => match self.captured(pointer.kind) {
+                Some(captured) => route.target = Some(captured),
+                None => {
+                    if pointer.kind == PointerKind::Mouse {
+                        self.hover(&mut route, hit);
+                    }
+                    route.target = hit;
+                }
+            },
+
+            Event::PointerUp(pointer) | Event::PointerCancel(pointer) => {
+                route.target = self.captured(pointer.kind).or(hit);
+
+                let cancelled = matches!(event, Event::PointerCancel(_));
+                if cancelled || pointer.buttons.is_empty() {
+                    self.release(pointer.kind);
+
+                    // The mouse might have been dragged onto another child.
+                    if pointer.kind == PointerKind::Mouse {
+                        self.hover(&mut route, hit);
+                    }
+                }
+            }
+
+            Event::PointerEnter(pointer) => {
+                route.target = hit;
+                if pointer.kind == PointerKind::Mouse {
+                    self.hovered = hit;
+                }
+            }
+
+            Event::PointerLeave(pointer) => {
+                route.target = self.captured(pointer.kind).or(self.hovered);
+                if pointer.kind == PointerKind::Mouse {
+                    self.hovered = None;
+                }
+            }
+
+            Event::LongPress(pointer) => route.target = self.captured(pointer.kind).or(hit),
+
+            Event::Scroll { .. } | Event::Hover(_) => route.target = hit,
+
+            _ => {}
+        }
+
+        route
+    }
+
+    /// Forget about pointers that an event intercepted in the capture phase would have let go.
+    fn intercepted(&mut self, event: &Event) {
+        match event {
+            Event::PointerCancel(pointer) => self.release(pointer.kind),
+            Event::PointerUp(pointer) if pointer.buttons.is_empty() => self.release(pointer.kind),
+            Event::PointerLeave(pointer) if pointer.kind == PointerKind::Mouse => {
+                self.hovered = None
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Find the topmost child of a container at a point.
+pub fn hit_test<C: Container + ?Sized>(container: &C, x: f64, y: f64) -> Option<usize> {
+    (0..container.child_count())
+        .rev()
+        .find(|&index| container.child_clip(index).contains(x, y))
+}
+
+/// Whether any child of a container can receive keyboard focus.
+///
+/// Containers with a focusable child should be focusable themselves, so that focus can reach it.
+pub fn is_focusable<C: Container + ?Sized>(container: &C) -> bool {
+    (0..container.child_count()).any(|index| container.child_is_focusable(index))
+}
+
+/// Whether pressing Tab, or Shift+Tab if `forwards` is `false`, moves focus within the focused
+/// child of a container or on to another child, rather than out of the container.
+pub fn steps_focus<C: Container + ?Sized>(container: &C, router: &Router, forwards: bool) -> bool {
+    let count = container.child_count();
+    let focused = router.focused.filter(|&index| index < count);
+    focused.is_some_and(|index| {
+        container.child_steps_focus(index, forwards)
+            || next_focusable(container, Some(index), forwards).is_some()
+    })
+}
+
+/// Move keyboard focus to a child of a container.
+///
+/// If the container has focus, the child that had it is sent [`Event::FocusOut`] and the new child
+/// is sent [`Event::FocusIn`]. Otherwise, the child gets focus once the container does. Returns the
+/// part of the container that needs to be redrawn.
+pub fn focus_child<C: Container + ?Sized>(container: &mut C, index: usize) -> Damage {
+    let router = container.router();
+    let old = router.focused.replace(index);
+    if old == Some(index) || !router.has_focus {
+        return Damage::None;
+    }
+
+    let damage = match old.filter(|&old| old < container.child_count()) {
+        Some(old) => deliver(container, old, Event::FocusOut),
+        None => Damage::None,
+    };
+    damage.union(deliver(container, index, Event::FocusIn))
+}
+
+/// Route an event through a container to its children.
+///
+/// Returns the part of the container that needs to be redrawn.
+pub fn dispatch<C: Container + ?Sized>(container: &mut C, event: Event) -> Damage {
+    if let Some(damage) = container.capture(&event) {
+        container.router().intercepted(&event);
+        return damage;
+    }
+
+    if event.is_keyboard() || matches!(event, Event::FocusIn | Event::FocusOut) {
+        let (damage, target) = route_focus(container, &event);
+        return damage.union(container.bubble(&event, target));
+    }
+
+    let hit = event
+        .position()
+        .and_then(|(x, y)| hit_test(container, x, y));
+    let captures = hit.is_some_and(|index| container.child_captures_pointer(index));
+    let route = container.router().route(&event, hit, captures);
+
+    // Children might have been removed since the router last saw them.
+    let count = container.child_count();
+    let route = Route {
+        leave: route.leave.filter(|&index| index < count),
+        enter: route.enter.filter(|&index| index < count),
+        target: route.target.filter(|&index| index < count),
+    };
+
+    // A released pointer only moves onto another child after it has been let go of.
+    let released = matches!(event, Event::PointerUp(_) | Event::PointerCancel(_));
+    let mut damage = Damage::None;
+    if released {
+        damage = damage.union(deliver_target(container, route.target, &event));
+    }
+
+    if let Some(pointer) = event.pointer().copied() {
+        if let Some(index) = route.leave {
+            damage = damage.union(deliver(container, index, Event::PointerLeave(pointer)));
+        }
+        if let Some(index) = route.enter {
+            damage = damage.union(deliver(container, index, Event::PointerEnter(pointer)));
+        }
+    }
+
+    if !released {
+        damage = damage.union(deliver_target(container, route.target, &event));
+    }
+
+    // Pressing a focusable child moves focus to it.
+    if let (Event::PointerDown(_), Some(index)) = (&event, route.target) {
+        if container.child_is_focusable(index) {
+            damage = damage.union(focus_child(container, index));
+        }
+    }
+
+    damage.union(container.bubble(&event, route.target))
+}
+
+/// Route a focus or keyboard event to the child with focus, moving focus between children on Tab.
+///
+/// Returns the damage, along with the child that the event was delivered to, if any.
+fn route_focus<C: Container + ?Sized>(container: &mut C, event: &Event) -> (Damage, Option<usize>) {
+    let count = container.child_count();
+    let focused = container.router().focused.filter(|&index| index < count);
+
+    match event {
+        // Focus goes back to the child that had it last, or else to the first one that can take it.
+        Event::FocusIn => {
+            let target = focused
+                .filter(|&index| container.child_is_focusable(index))
+                .or_else(|| next_focusable(container, None, true));
+            let router = container.router();
+            router.has_focus = true;
+            router.focused = target;
+            (deliver_target(container, target, event), target)
+        }
+
+        Event::FocusOut => {
+            container.router().has_focus = false;
+            (deliver_target(container, focused, event), focused)
+        }
+
+        Event::KeyDown(KeyEvent {
+            key: Key::Tab,
+            modifiers,
+            ..
+        }) => {
+            let forwards = !modifiers.shift;
+            if focused.is_some_and(|index| container.child_steps_focus(index, forwards)) {
+                return (deliver_target(container, focused, event), focused);
+            }
+
+            match next_focusable(container, focused, forwards) {
+                Some(next) => (focus_child(container, next), None),
+                None => (Damage::None, None),
+            }
+        }
+
+        _ => (deliver_target(container, focused, event), focused),
+    }
+}
+
+/// Find the first focusable child after `from`, or the last one before it if `forwards` is
+/// `false`.
+///
+/// If `from` is `None`, this finds the first or last focusable child of the container.
+fn next_focusable<C: Container + ?Sized>(
+    container: &C,
+    from: Option<usize>,
+    forwards: bool,
+) -> Option<usize> {
+    let count = container.child_count();
+    let focusable = |&index: &usize| container.child_is_focusable(index);
+
+    if forwards {
+        let start = from.map_or(0, |index| index + 1);
+        (start..count).find(focusable)
+    } else {
+        let end = from.unwrap_or(count).min(count);
+        (0..end).rev().find(focusable)
+    }
+}
+
+/// Deliver an event to the child it was routed to, if any.
+fn deliver_target<C: Container + ?Sized>(
+    container: &mut C,
+    target: Option<usize>,
+    event: &Event,
+) -> Damage {
+    match target {
+        Some(index) => deliver(container, index, event.clone()),
+        None => Damage::None,
+    }
+}
+
+/// Deliver an event to a child, and get the damage to the container.
+fn deliver<C: Container + ?Sized>(container: &mut C, index: usize, event: Event) -> Damage {
+    let region = container.child_region(index);
+    let clip = container.child_clip(index);
+    let event = event.translate(-region.x as f64, -region.y as f64);
+
+    match container.child_event(index, event).within(region) {
+        Damage::Region(damage) => Damage::Region(damage.intersect(clip)),
+        damage => damage,
+    }
+}
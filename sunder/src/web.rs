@@ -19,15 +19,19 @@ License along with `sunder`. If not, see <https://www.gnu.org/licenses/>.
 
 //! A backend for rendering widgets to HTML elements.
 
-use super::Backend;
+use super::{Backend, Rectangle};
 
-use web_sys::Document;
+use web_sys::{Document, Element};
 
-use core::fmt;
+use alloc::format;
+use core::{fmt, mem};
 
 /// The HTML backend.
 pub struct HtmlBackend {
     document: Document,
+
+    /// The element that new elements are added to.
+    parent: Element,
 }
 
 /// An error for the HTML backend.
@@ -50,17 +54,63 @@ impl fmt::Display for Error {
 
 impl HtmlBackend {
     /// Create a new HTML backend.
+    ///
+    /// Elements are added to the body of the document.
     pub fn new() -> Result<Self, Error> {
+        let document = web_sys::window()
+            .ok_or(Error(Repr::Msg("no window")))?
+            .document()
+            .ok_or(Error(Repr::Msg("no document")))?;
+        let body = document.body().ok_or(Error(Repr::Msg("no body")))?;
+
         Ok(Self {
-            document: web_sys::window()
-                .ok_or(Error(Repr::Msg("no window")))?
-                .document()
-                .ok_or(Error(Repr::Msg("no document")))?,
+            document,
+            parent: body.into(),
         })
     }
+
+    /// Get the document that elements are created in.
+    pub fn document(&self) -> &Document {
+        &self.document
+    }
+
+    /// Get the element that elements being drawn should be added to.
+    ///
+    /// Inside of [`Backend::with_region`], this is positioned over the region and clips to it.
+    pub fn parent(&self) -> &Element {
+        &self.parent
+    }
 }
 
 impl Backend for HtmlBackend {
     type Error = Error;
     type Output = web_sys::Element;
+
+    fn with_region<R>(
+        &mut self,
+        region: Rectangle,
+        f: impl FnOnce(&mut Self) -> Result<R, Self::Error>,
+    ) -> Result<R, Self::Error> {
+        // Elements added inside of the region go into a wrapper that is positioned over it and
+        // clips them to it.
+        let wrapper = self
+            .document
+            .create_element("div")
+            .map_err(|_| Error(Repr::Msg("failed to create an element")))?;
+        let style = format!(
+            "position: absolute; left: {}px; top: {}px; width: {}px; height: {}px; overflow: hidden;",
+            region.x, region.y, region.width, region.height
+        );
+        wrapper
+            .set_attribute("style", &style)
+            .map_err(|_| Error(Repr::Msg("failed to position an element")))?;
+        self.parent
+            .append_child(&wrapper)
+            .map_err(|_| Error(Repr::Msg("failed to add an element")))?;
+
+        let parent = mem::replace(&mut self.parent, wrapper);
+        let result = f(self);
+        self.parent = parent;
+        result
+    }
 }
@@ -0,0 +1,153 @@
+/*
+
+`sunder` is free software: you can redistribute it and/or modify it under the terms of one of
+the following licenses:
+
+- The GNU Affero General Public License as published by the Free Software Foundation, either version
+  3 of the License, or (at your option) any later version.
+- The Patron License at https://github.com/notgull/sunder/blob/main/LICENSE-PATRON.md, for
+  sponsors and contributors, who can ignore the copyleft provisions of the GNU AGPL for this project.
+
+`sunder` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+Public License and the Patron License for more details.
+
+You should have received a copy of the GNU Affero General Public License and the corresponding Patron
+License along with `sunder`. If not, see <https://www.gnu.org/licenses/>.
+
+*/
+
+//! A widget with its state bundled alongside it, so that it can be stored with other kinds of
+//! widgets.
+
+use crate::{Backend, BoxConstraints, Damage, Event, Rectangle, RenderedWidget, Size};
+use alloc::boxed::Box;
+use core::cell::RefCell;
+
+/// A widget of any type, stored with its immediate state and cache.
+///
+/// Containers use this to hold children of different types. The container is in charge of
+/// delivering events to the widget, since nothing else can reach its immediate state.
+pub struct BoxedWidget<'a, B: Backend> {
+    inner: Box<dyn ErasedWidget<B> + 'a>,
+}
+
+impl<'a, B: Backend> BoxedWidget<'a, B> {
+    /// Box up a widget.
+    pub fn new<W: RenderedWidget<B> + 'a>(widget: W) -> Self
+    where
+        B: 'a,
+    {
+        Self {
+            inner: Box::new(Stateful {
+                immediate: <W::Immediate<'a>>::default(),
+                cache: RefCell::new(W::Cache::default()),
+                widget,
+            }),
+        }
+    }
+
+    /// Reply to an event.
+    ///
+    /// Returns the part of the widget that needs to be redrawn.
+    pub fn handle_event(&mut self, event: Event) -> Damage {
+        self.inner.handle_event(event)
+    }
+
+    /// Whether the widget can receive keyboard focus.
+    pub fn is_focusable(&self) -> bool {
+        self.inner.is_focusable()
+    }
+
+    /// Whether pressing Tab, or Shift+Tab if `forwards` is `false`, moves focus within the widget.
+    pub fn steps_focus(&self, forwards: bool) -> bool {
+        self.inner.steps_focus(forwards)
+    }
+
+    /// Whether the widget captures pointers that are pressed on it.
+    pub fn captures_pointer(&self) -> bool {
+        self.inner.captures_pointer()
+    }
+
+    /// Measure the size that the widget wants to take up.
+    pub fn measure(
+        &mut self,
+        constraints: BoxConstraints,
+        backend: &mut B,
+    ) -> Result<Size, B::Error> {
+        self.inner.measure(constraints, backend)
+    }
+
+    /// Place the widget in its region.
+    pub fn arrange(&mut self, region: Rectangle, backend: &mut B) -> Result<(), B::Error> {
+        self.inner.arrange(region, backend)
+    }
+
+    /// Get the distance from the top of the widget to its text baseline.
+    pub fn baseline(&self) -> Option<u32> {
+        self.inner.baseline()
+    }
+
+    /// Render the widget.
+    pub fn render(&self, backend: &mut B) -> Result<B::Output, B::Error> {
+        self.inner.render(backend)
+    }
+}
+
+/// The object-safe parts of a widget.
+trait ErasedWidget<B: Backend> {
+    fn handle_event(&mut self, event: Event) -> Damage;
+    fn is_focusable(&self) -> bool;
+    fn steps_focus(&self, forwards: bool) -> bool;
+    fn captures_pointer(&self) -> bool;
+    fn measure(&mut self, constraints: BoxConstraints, backend: &mut B) -> Result<Size, B::Error>;
+    fn arrange(&mut self, region: Rectangle, backend: &mut B) -> Result<(), B::Error>;
+    fn baseline(&self) -> Option<u32>;
+    fn render(&self, backend: &mut B) -> Result<B::Output, B::Error>;
+}
+
+/// A widget along with its state.
+struct Stateful<'a, B: Backend, W: RenderedWidget<B>> {
+    immediate: W::Immediate<'a>,
+
+    /// Rendering only has shared access to the widget, but needs to update the cache.
+    cache: RefCell<W::Cache>,
+
+    widget: W,
+}
+
+impl<'a, B: Backend, W: RenderedWidget<B>> ErasedWidget<B> for Stateful<'a, B, W> {
+    fn handle_event(&mut self, event: Event) -> Damage {
+        self.widget.handle_event(&mut self.immediate, event)
+    }
+
+    fn is_focusable(&self) -> bool {
+        self.widget.is_focusable()
+    }
+
+    fn steps_focus(&self, forwards: bool) -> bool {
+        self.widget.steps_focus(forwards)
+    }
+
+    fn captures_pointer(&self) -> bool {
+        self.widget.captures_pointer()
+    }
+
+    fn measure(&mut self, constraints: BoxConstraints, backend: &mut B) -> Result<Size, B::Error> {
+        self.widget
+            .measure(constraints, self.cache.get_mut(), backend)
+    }
+
+    fn arrange(&mut self, region: Rectangle, backend: &mut B) -> Result<(), B::Error> {
+        self.widget.arrange(region, self.cache.get_mut(), backend)
+    }
+
+    fn baseline(&self) -> Option<u32> {
+        self.widget.baseline(&self.cache.borrow())
+    }
+
+    fn render(&self, backend: &mut B) -> Result<B::Output, B::Error> {
+        self.widget
+            .render(&self.immediate, &mut self.cache.borrow_mut(), backend)
+    }
+}
@@ -20,17 +20,23 @@ License along with `sunder`. If not, see <https://www.gnu.org/licenses/>.
 //! A push button with text inside.
 
 use super::text_view::TextView;
-use crate::{RenderedWidget, Size, Widget};
+use crate::{Damage, Event, Key, KeyEvent, Size, Widget};
+use alloc::borrow::Cow;
 
 cfg_piet! {
-  use crate::piet::PietBackend;
+  use crate::piet::{draw_box, BoxStyle, PietBackend, Sides};
+  use crate::piet::ui_theme::Theme;
+  use crate::{Backend, BoxConstraints, Rectangle, RenderedWidget};
   use super::text_view::PietCache as TextPietCache;
   use piet::RenderContext;
 }
 
 /// A push button with text inside.
 pub struct PushButton<'a> {
+    /// The text inside of the button.
     text: TextView<'a>,
+
+    /// The size that the button was last arranged at.
     size: Size,
 }
 
@@ -38,48 +44,225 @@ pub struct PushButton<'a> {
 pub struct ImmediateState {
     /// Whether the button is currently pressed.
     pressed: bool,
+
+    /// Whether the button has been clicked since this was last checked.
+    clicked: bool,
+
+    /// Whether the button has keyboard focus.
+    focused: bool,
+}
+
+impl ImmediateState {
+    /// Whether the button is currently pressed.
+    pub fn is_pressed(&self) -> bool {
+        self.pressed
+    }
+
+    /// Whether the button has keyboard focus.
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    /// Check whether the button has been clicked, resetting the flag.
+    ///
+    /// A click is a press followed by a release, both inside of the button, or the enter or space
+    /// key being pressed while the button has focus.
+    pub fn take_clicked(&mut self) -> bool {
+        core::mem::take(&mut self.clicked)
+    }
 }
 
 cfg_piet! {
   pub struct PietCache<R: RenderContext + ?Sized> {
     text: TextPietCache<R>,
+
+    /// The whole pixels between the edges of the button and its text, as of the last measurement.
+    insets: Sides<u32>,
   }
 
   impl<R: RenderContext + ?Sized> Default for PietCache<R> {
     fn default() -> Self {
       Self {
         text: Default::default(),
+        insets: Sides::default(),
       }
     }
   }
+
+  /// The theme classes that can style the body of the button, from the most to the least
+  /// specific.
+  fn body_classes(immediate: &ImmediateState) -> impl Iterator<Item = &'static str> {
+    let pressed = immediate.pressed.then_some("button:pressed");
+    let focused = immediate.focused.then_some("button:focus");
+    pressed.into_iter().chain(focused).chain(Some("button"))
+  }
+
+  /// How the body of the button is painted, using the most specific class that the theme has.
+  ///
+  /// If the theme doesn't style buttons at all, only the text is drawn.
+  fn body_style(theme: &Theme, immediate: &ImmediateState) -> BoxStyle {
+    BoxStyle::from_classes(theme, body_classes(immediate))
+  }
+}
+
+impl<'a> PushButton<'a> {
+    /// Create a new push button.
+    pub fn new(text: impl Into<Cow<'a, str>>) -> Self {
+        Self {
+            text: TextView::new(text),
+            size: Size::default(),
+        }
+    }
+
+    /// Get the text of this button.
+    pub fn text(&self) -> &str {
+        self.text.text()
+    }
+
+    /// Whether a point is inside of the button.
+    fn contains(&self, x: f64, y: f64) -> bool {
+        x >= 0.0 && y >= 0.0 && x < self.size.width as f64 && y < self.size.height as f64
+    }
 }
 
 impl Widget for PushButton<'_> {
     type Immediate<'a> = ImmediateState;
 
-    fn handle_event(&mut self, immediate: &mut Self::Immediate<'_>, event: crate::Event) -> bool {
-        todo!()
+    fn handle_event(&mut self, immediate: &mut Self::Immediate<'_>, event: Event) -> Damage {
+        match event {
+            Event::PointerDown(pointer) if pointer.is_primary() => {
+                if self.contains(pointer.x, pointer.y) && !immediate.pressed {
+                    immediate.pressed = true;
+                    return Damage::Full;
+                }
+
+                Damage::None
+            }
+
+            Event::PointerUp(pointer) if pointer.is_primary() => {
+                if immediate.pressed {
+                    immediate.pressed = false;
+                    immediate.clicked = self.contains(pointer.x, pointer.y);
+                    return Damage::Full;
+                }
+
+                Damage::None
+            }
+
+            Event::PointerCancel(_) => {
+                if immediate.pressed {
+                    immediate.pressed = false;
+                    return Damage::Full;
+                }
+
+                Damage::None
+            }
+
+            // Holding the key down only clicks once.
+            Event::KeyDown(KeyEvent {
+                key: Key::Enter | Key::Space,
+                repeat: false,
+                ..
+            }) => {
+                immediate.clicked = true;
+                Damage::None
+            }
+
+            Event::FocusIn => {
+                immediate.focused = true;
+                Damage::Full
+            }
+
+            Event::FocusOut => {
+                immediate.focused = false;
+                immediate.pressed = false;
+                Damage::Full
+            }
+
+            _ => Damage::None,
+        }
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn captures_pointer(&self) -> bool {
+        true
     }
 }
 
 #[cfg(feature = "piet")]
-impl<R: RenderContext + ?Sized> RenderedWidget<PietBackend<'_, R>> for PushButton<'_> {
+impl<R: RenderContext> RenderedWidget<PietBackend<R>> for PushButton<'_> {
     type Cache = PietCache<R>;
 
-    fn rectangle(
+    fn measure(
         &mut self,
+        constraints: BoxConstraints,
         cache: &mut Self::Cache,
-        backend: &mut PietBackend<'_, R>,
-    ) -> Result<Size, <PietBackend<'_, R> as crate::Backend>::Error> {
-        todo!()
+        backend: &mut PietBackend<R>,
+    ) -> Result<Size, <PietBackend<R> as crate::Backend>::Error> {
+        // Leave room for the border and padding of the button at rest.
+        let insets = body_style(backend.theme(), &ImmediateState::default()).insets();
+        let insets = Sides {
+            top: insets.y0.ceil() as u32,
+            right: insets.x1.ceil() as u32,
+            bottom: insets.y1.ceil() as u32,
+            left: insets.x0.ceil() as u32,
+        };
+        cache.insets = insets;
+
+        let (horizontal, vertical) = (insets.left + insets.right, insets.top + insets.bottom);
+        let text = self.text.measure(
+            constraints.loosen().deflate(horizontal, vertical),
+            &mut cache.text,
+            backend,
+        )?;
+
+        Ok(constraints.constrain(Size {
+            width: text.width + horizontal,
+            height: text.height + vertical,
+        }))
+    }
+
+    fn arrange(
+        &mut self,
+        region: Rectangle,
+        _cache: &mut Self::Cache,
+        _backend: &mut PietBackend<R>,
+    ) -> Result<(), piet::Error> {
+        self.size = Size {
+            width: region.width,
+            height: region.height,
+        };
+        Ok(())
+    }
+
+    fn baseline(&self, cache: &Self::Cache) -> Option<u32> {
+        self.text
+            .baseline(&cache.text)
+            .map(|baseline| baseline + cache.insets.top)
     }
 
     fn render(
         &self,
         immediate: &Self::Immediate<'_>,
         cache: &mut Self::Cache,
-        backend: &mut PietBackend<'_, R>,
+        backend: &mut PietBackend<R>,
     ) -> Result<(), piet::Error> {
-        todo!()
+        // Draw the body of the button.
+        let body = Rectangle {
+            x: 0,
+            y: 0,
+            width: self.size.width,
+            height: self.size.height,
+        };
+        let style = body_style(backend.theme(), immediate);
+        let text_region = draw_box(backend.context(), &style, body)?;
+
+        // Draw the text inside of the padding.
+        backend.with_region(text_region, |backend| {
+            self.text.render(&(), &mut cache.text, backend)
+        })
     }
 }
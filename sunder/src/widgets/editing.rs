@@ -0,0 +1,103 @@
+/*
+
+`sunder` is free software: you can redistribute it and/or modify it under the terms of one of
+the following licenses:
+
+- The GNU Affero General Public License as published by the Free Software Foundation, either version
+  3 of the License, or (at your option) any later version.
+- The Patron License at https://github.com/notgull/sunder/blob/main/LICENSE-PATRON.md, for
+  sponsors and contributors, who can ignore the copyleft provisions of the GNU AGPL for this project.
+
+`sunder` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+Public License and the Patron License for more details.
+
+You should have received a copy of the GNU Affero General Public License and the corresponding Patron
+License along with `sunder`. If not, see <https://www.gnu.org/licenses/>.
+
+*/
+
+//! Moving through text that is being edited.
+//!
+//! Positions are byte offsets, and always lie on character boundaries.
+
+/// Whether a character is part of a word.
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Get the largest character boundary at or before a position.
+pub(crate) fn floor_boundary(text: &str, position: usize) -> usize {
+    let mut position = position.min(text.len());
+    while !text.is_char_boundary(position) {
+        position -= 1;
+    }
+    position
+}
+
+/// Get the position of the next character after a position.
+pub(crate) fn next_char(text: &str, position: usize) -> usize {
+    text[position..]
+        .chars()
+        .next()
+        .map_or(position, |c| position + c.len_utf8())
+}
+
+/// Get the position of the character before a position.
+pub(crate) fn previous_char(text: &str, position: usize) -> usize {
+    text[..position]
+        .chars()
+        .next_back()
+        .map_or(position, |c| position - c.len_utf8())
+}
+
+/// Get the position of the end of the next word after a position.
+pub(crate) fn next_word(text: &str, position: usize) -> usize {
+    let rest = &text[position..];
+    let start = rest.find(is_word).unwrap_or(rest.len());
+    let end = rest[start..]
+        .find(|c| !is_word(c))
+        .map_or(rest.len(), |end| start + end);
+
+    position + end
+}
+
+/// Get the position of the start of the word before a position.
+pub(crate) fn previous_word(text: &str, position: usize) -> usize {
+    let before = &text[..position];
+    let end = before.rfind(is_word).map_or(0, |i| next_char(before, i));
+    before[..end]
+        .rfind(|c| !is_word(c))
+        .map_or(0, |i| next_char(before, i))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn boundaries() {
+        let text = "aé😀";
+        assert_eq!(floor_boundary(text, 2), 1);
+        assert_eq!(floor_boundary(text, 5), 3);
+        assert_eq!(floor_boundary(text, 100), text.len());
+
+        assert_eq!(next_char(text, 1), 3);
+        assert_eq!(next_char(text, 3), 7);
+        assert_eq!(next_char(text, 7), 7);
+        assert_eq!(previous_char(text, 7), 3);
+        assert_eq!(previous_char(text, 0), 0);
+    }
+
+    #[test]
+    fn words() {
+        let text = "  foo_bar, baz!";
+        assert_eq!(next_word(text, 0), 9);
+        assert_eq!(next_word(text, 9), 14);
+        assert_eq!(next_word(text, 14), text.len());
+
+        assert_eq!(previous_word(text, text.len()), 11);
+        assert_eq!(previous_word(text, 11), 2);
+        assert_eq!(previous_word(text, 2), 0);
+    }
+}
@@ -0,0 +1,694 @@
+/*
+
+`sunder` is free software: you can redistribute it and/or modify it under the terms of one of
+the following licenses:
+
+- The GNU Affero General Public License as published by the Free Software Foundation, either version
+  3 of the License, or (at your option) any later version.
+- The Patron License at https://github.com/notgull/sunder/blob/main/LICENSE-PATRON.md, for
+  sponsors and contributors, who can ignore the copyleft provisions of the GNU AGPL for this project.
+
+`sunder` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+Public License and the Patron License for more details.
+
+You should have received a copy of the GNU Affero General Public License and the corresponding Patron
+License along with `sunder`. If not, see <https://www.gnu.org/licenses/>.
+
+*/
+
+//! A container that lays out its children along an axis, sharing out spare space.
+
+use super::boxed::BoxedWidget;
+use crate::tree::{self, Container, Router};
+use crate::{Backend, BoxConstraints, Damage, Event, Rectangle, RenderedWidget, Size, Widget};
+use alloc::vec::Vec;
+
+/// The axis that a [`Flex`] lays out its children along.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Axis {
+    /// Children are placed from left to right.
+    Horizontal,
+
+    /// Children are placed from top to bottom.
+    Vertical,
+}
+
+impl Axis {
+    /// Get the length of a size along this axis.
+    fn main(self, size: Size) -> u32 {
+        match self {
+            Axis::Horizontal => size.width,
+            Axis::Vertical => size.height,
+        }
+    }
+
+    /// Get the length of a size across this axis.
+    fn cross(self, size: Size) -> u32 {
+        match self {
+            Axis::Horizontal => size.height,
+            Axis::Vertical => size.width,
+        }
+    }
+
+    /// Create a size from its lengths along and across this axis.
+    fn size(self, main: u32, cross: u32) -> Size {
+        match self {
+            Axis::Horizontal => Size {
+                width: main,
+                height: cross,
+            },
+            Axis::Vertical => Size {
+                width: cross,
+                height: main,
+            },
+        }
+    }
+
+    /// Create a rectangle from its positions and lengths along and across this axis.
+    fn rectangle(self, main: i32, cross: i32, size: Size) -> Rectangle {
+        let (x, y) = match self {
+            Axis::Horizontal => (main, cross),
+            Axis::Vertical => (cross, main),
+        };
+
+        Rectangle {
+            x,
+            y,
+            width: size.width,
+            height: size.height,
+        }
+    }
+}
+
+/// How children are placed across the axis of a [`Flex`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CrossAlignment {
+    /// Children are placed at the top or left.
+    #[default]
+    Start,
+
+    /// Children are centered.
+    Center,
+
+    /// Children are placed at the bottom or right.
+    End,
+
+    /// Children are stretched to fill the container.
+    Stretch,
+
+    /// The first lines of text in each child line up.
+    ///
+    /// This only applies to horizontal containers; vertical ones treat it like
+    /// [`CrossAlignment::Start`]. Children without text are aligned by their bottom edge.
+    Baseline,
+}
+
+/// How spare space along the axis of a [`Flex`] is shared out between its children.
+///
+/// Spare space is only left over if no child can grow to take it up.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MainAlignment {
+    /// Children are packed at the start.
+    #[default]
+    Start,
+
+    /// Children are packed in the center.
+    Center,
+
+    /// Children are packed at the end.
+    End,
+
+    /// Spare space goes between children, with none at either end.
+    SpaceBetween,
+
+    /// Each child gets an equal share of spare space, split evenly on either side.
+    SpaceAround,
+
+    /// Spare space is split evenly between children and both ends.
+    SpaceEvenly,
+}
+
+/// A child of a [`Flex`] container.
+pub struct FlexItem<'a, B: Backend> {
+    /// The child widget.
+    widget: BoxedWidget<'a, B>,
+
+    /// How much of the spare space the child takes up.
+    grow: f64,
+
+    /// How much the child gives up when there isn't enough space.
+    shrink: f64,
+
+    /// The size that the child wants when it isn't grown or shrunk.
+    natural: Size,
+
+    /// The size that the child was given.
+    size: Size,
+
+    /// The region that the child was arranged in.
+    region: Rectangle,
+}
+
+impl<'a, B: Backend + 'a> FlexItem<'a, B> {
+    /// Create a new child that doesn't grow, but can shrink.
+    pub fn new(widget: impl RenderedWidget<B> + 'a) -> Self {
+        Self {
+            widget: BoxedWidget::new(widget),
+            grow: 0.0,
+            shrink: 1.0,
+            natural: Size::default(),
+            size: Size::default(),
+            region: Rectangle::default(),
+        }
+    }
+
+    /// Set how much of the spare space this child takes up, relative to other children.
+    ///
+    /// A child with a grow factor of zero keeps its natural size.
+    pub fn with_grow(mut self, grow: f64) -> Self {
+        self.grow = grow.max(0.0);
+        self
+    }
+
+    /// Set how much this child shrinks, relative to other children, when there isn't enough space.
+    ///
+    /// The amount a child shrinks is also proportional to its natural size. A child with a shrink
+    /// factor of zero never shrinks.
+    pub fn with_shrink(mut self, shrink: f64) -> Self {
+        self.shrink = shrink.max(0.0);
+        self
+    }
+}
+
+/// A container that lays out its children along an axis.
+///
+/// Children take up their natural size, and then share out any spare space according to their
+/// grow factors. If there isn't enough space, they shrink according to their shrink factors.
+pub struct Flex<'a, B: Backend> {
+    /// The children of the container.
+    children: Vec<FlexItem<'a, B>>,
+
+    /// The axis to lay out children along.
+    axis: Axis,
+
+    /// How children are placed across the axis.
+    cross_alignment: CrossAlignment,
+
+    /// How spare space along the axis is shared out.
+    main_alignment: MainAlignment,
+
+    /// The gap between each child.
+    spacing: u32,
+
+    /// The length along the axis that children were last sized for.
+    main_length: Option<u32>,
+
+    /// The largest length across the axis that children are allowed.
+    cross_max: u32,
+
+    /// Routes events to the children.
+    router: Router,
+}
+
+impl<'a, B: Backend + 'a> Flex<'a, B> {
+    /// Create a new, empty container.
+    pub fn new(axis: Axis) -> Self {
+        Self {
+            children: Vec::new(),
+            axis,
+            cross_alignment: CrossAlignment::default(),
+            main_alignment: MainAlignment::default(),
+            spacing: 0,
+            main_length: None,
+            cross_max: u32::MAX,
+            router: Router::new(),
+        }
+    }
+
+    /// Create a new container that places children from left to right.
+    pub fn row() -> Self {
+        Self::new(Axis::Horizontal)
+    }
+
+    /// Create a new container that places children from top to bottom.
+    pub fn column() -> Self {
+        Self::new(Axis::Vertical)
+    }
+
+    /// Add a child that keeps its natural size.
+    pub fn with_child(self, widget: impl RenderedWidget<B> + 'a) -> Self {
+        self.with_item(FlexItem::new(widget))
+    }
+
+    /// Add a child that takes up a share of the spare space.
+    pub fn with_flex_child(self, widget: impl RenderedWidget<B> + 'a, grow: f64) -> Self {
+        self.with_item(FlexItem::new(widget).with_grow(grow))
+    }
+
+    /// Add a child.
+    pub fn with_item(mut self, item: FlexItem<'a, B>) -> Self {
+        self.push(item);
+        self
+    }
+
+    /// Set how children are placed across the axis.
+    pub fn with_cross_alignment(mut self, alignment: CrossAlignment) -> Self {
+        self.cross_alignment = alignment;
+        self
+    }
+
+    /// Set how spare space along the axis is shared out.
+    pub fn with_main_alignment(mut self, alignment: MainAlignment) -> Self {
+        self.main_alignment = alignment;
+        self
+    }
+
+    /// Set the gap between each child.
+    pub fn with_spacing(mut self, spacing: u32) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    /// Add a child to the end of the container.
+    pub fn push(&mut self, item: FlexItem<'a, B>) {
+        self.children.push(item);
+        self.main_length = None;
+    }
+
+    /// Get the axis that children are laid out along.
+    pub fn axis(&self) -> Axis {
+        self.axis
+    }
+
+    /// Get the number of children.
+    pub fn len(&self) -> usize {
+        self.children.len()
+    }
+
+    /// Whether there are no children.
+    pub fn is_empty(&self) -> bool {
+        self.children.is_empty()
+    }
+}
+
+impl<'a, B: Backend> Flex<'a, B> {
+    /// Get the total length of the gaps between children.
+    fn total_spacing(&self) -> u32 {
+        self.spacing
+            .saturating_mul(self.children.len().saturating_sub(1) as u32)
+    }
+
+    /// Get the total length along the axis of every child and the gaps between them.
+    ///
+    /// This saturates rather than overflowing when a child fills an unbounded axis.
+    fn total_length(&self, size: impl Fn(&FlexItem<'a, B>) -> Size) -> u32 {
+        self.children
+            .iter()
+            .fold(self.total_spacing(), |total, child| {
+                total.saturating_add(self.axis.main(size(child)))
+            })
+    }
+
+    /// Get the constraints that children are measured at before they grow or shrink.
+    fn natural_constraints(&self) -> BoxConstraints {
+        BoxConstraints::loose(self.axis.size(u32::MAX, self.cross_max))
+    }
+
+    /// Size every child to fit a length along the axis, growing or shrinking them as needed.
+    ///
+    /// If `length` is `None`, children keep their natural size.
+    fn distribute(&mut self, length: Option<u32>, backend: &mut B) -> Result<(), B::Error> {
+        let axis = self.axis;
+        let natural: u32 = self.total_length(|child| child.natural);
+
+        let free = length.map_or(0, |length| length as i64 - natural as i64);
+        let total_grow: f64 = self.children.iter().map(|child| child.grow).sum();
+        let total_shrink: f64 = self
+            .children
+            .iter()
+            .map(|child| child.shrink * axis.main(child.natural) as f64)
+            .sum();
+
+        // Round to whole pixels as we go, so that the rounding errors don't add up.
+        // `f64::round` isn't available without `std`.
+        let round = |x: f64| {
+            if x < 0.0 {
+                -((-x + 0.5) as i64)
+            } else {
+                (x + 0.5) as i64
+            }
+        };
+        let mut shared = 0.0;
+
+        let cross_max = self.cross_max;
+        for child in &mut self.children {
+            let main = axis.main(child.natural);
+
+            let delta = if free > 0 && total_grow > 0.0 {
+                free as f64 * child.grow / total_grow
+            } else if free < 0 && total_shrink > 0.0 {
+                free as f64 * child.shrink * main as f64 / total_shrink
+            } else {
+                0.0
+            };
+            let start = round(shared);
+            shared += delta;
+            let target = (main as i64 + round(shared) - start).max(0) as u32;
+
+            child.size = if target == axis.main(child.natural) {
+                child.natural
+            } else {
+                // Measure the child again, forcing its length along the axis.
+                let constraints = BoxConstraints {
+                    min: axis.size(target, 0),
+                    max: axis.size(target, cross_max),
+                };
+                let measured = child.widget.measure(constraints, backend)?;
+                axis.size(target, axis.cross(measured))
+            };
+        }
+
+        self.main_length = length;
+        Ok(())
+    }
+
+    /// Get the length across the axis that children take up.
+    fn cross_length(&self) -> u32 {
+        let axis = self.axis;
+        let tallest = self
+            .children
+            .iter()
+            .map(|child| axis.cross(child.size))
+            .max()
+            .unwrap_or(0);
+
+        if self.uses_baseline() {
+            let (above, below) = self.children.iter().fold((0, 0), |(above, below), child| {
+                let height = axis.cross(child.size);
+                let baseline = child.widget.baseline().unwrap_or(height).min(height);
+                (above.max(baseline), below.max(height - baseline))
+            });
+
+            tallest.max(above.saturating_add(below))
+        } else {
+            tallest
+        }
+    }
+
+    /// Whether children are lined up by their baselines.
+    fn uses_baseline(&self) -> bool {
+        self.cross_alignment == CrossAlignment::Baseline && self.axis == Axis::Horizontal
+    }
+}
+
+impl<B: Backend> Container for Flex<'_, B> {
+    fn child_count(&self) -> usize {
+        self.children.len()
+    }
+
+    fn child_region(&self, index: usize) -> Rectangle {
+        self.children[index].region
+    }
+
+    fn child_event(&mut self, index: usize, event: Event) -> Damage {
+        self.children[index].widget.handle_event(event)
+    }
+
+    fn child_captures_pointer(&self, index: usize) -> bool {
+        self.children[index].widget.captures_pointer()
+    }
+
+    fn child_is_focusable(&self, index: usize) -> bool {
+        self.children[index].widget.is_focusable()
+    }
+
+    fn child_steps_focus(&self, index: usize, forwards: bool) -> bool {
+        self.children[index].widget.steps_focus(forwards)
+    }
+
+    fn router(&mut self) -> &mut Router {
+        &mut self.router
+    }
+}
+
+impl<B: Backend> Widget for Flex<'_, B> {
+    type Immediate<'a> = ();
+
+    fn handle_event(&mut self, _immediate: &mut Self::Immediate<'_>, event: Event) -> Damage {
+        tree::dispatch(self, event)
+    }
+
+    fn is_focusable(&self) -> bool {
+        tree::is_focusable(self)
+    }
+
+    fn steps_focus(&self, forwards: bool) -> bool {
+        tree::steps_focus(self, &self.router, forwards)
+    }
+
+    fn captures_pointer(&self) -> bool {
+        self.children
+            .iter()
+            .any(|child| child.widget.captures_pointer())
+    }
+}
+
+impl<'a, B: Backend<Output = ()>> RenderedWidget<B> for Flex<'a, B> {
+    type Cache = ();
+
+    fn measure(
+        &mut self,
+        constraints: BoxConstraints,
+        _cache: &mut Self::Cache,
+        backend: &mut B,
+    ) -> Result<Size, B::Error> {
+        let axis = self.axis;
+        self.cross_max = axis.cross(constraints.max);
+
+        let natural_constraints = self.natural_constraints();
+        for child in &mut self.children {
+            child.natural = child.widget.measure(natural_constraints, backend)?;
+        }
+
+        // Take up all of the space along the axis if there's a child to grow into it.
+        let bounded = axis.main(constraints.max) != u32::MAX;
+        let grows = self.children.iter().any(|child| child.grow > 0.0);
+        let natural = self.total_length(|child| child.natural);
+
+        let length = if bounded && (grows || natural > axis.main(constraints.max)) {
+            Some(axis.main(constraints.max))
+        } else {
+            None
+        };
+        self.distribute(length, backend)?;
+
+        let main = length.unwrap_or(natural);
+        Ok(constraints.constrain(axis.size(main, self.cross_length())))
+    }
+
+    fn arrange(
+        &mut self,
+        region: Rectangle,
+        _cache: &mut Self::Cache,
+        backend: &mut B,
+    ) -> Result<(), B::Error> {
+        let axis = self.axis;
+        let size = Size {
+            width: region.width,
+            height: region.height,
+        };
+        let length = axis.main(size);
+        let cross = axis.cross(size);
+
+        // The parent might have given us a different length than we asked for.
+        if self.main_length != Some(length) {
+            self.distribute(Some(length), backend)?;
+        }
+
+        // Work out where the first child goes, and how much space goes between each.
+        let used = self.total_length(|child| child.size);
+        let spare = length.saturating_sub(used) as f64;
+        let count = self.children.len() as f64;
+
+        let (start, extra) = match self.main_alignment {
+            MainAlignment::Start => (0.0, 0.0),
+            MainAlignment::Center => (spare / 2.0, 0.0),
+            MainAlignment::End => (spare, 0.0),
+            MainAlignment::SpaceBetween if count > 1.0 => (0.0, spare / (count - 1.0)),
+            MainAlignment::SpaceBetween => (0.0, 0.0),
+            MainAlignment::SpaceAround => (spare / count / 2.0, spare / count),
+            MainAlignment::SpaceEvenly => (spare / (count + 1.0), spare / (count + 1.0)),
+        };
+
+        let baseline = if self.uses_baseline() {
+            self.children
+                .iter()
+                .map(|child| {
+                    let height = axis.cross(child.size);
+                    child.widget.baseline().unwrap_or(height).min(height)
+                })
+                .max()
+                .unwrap_or(0)
+        } else {
+            0
+        };
+
+        let mut position = start;
+        for child in &mut self.children {
+            let child_cross = axis.cross(child.size);
+
+            let (offset, child_cross) = match self.cross_alignment {
+                CrossAlignment::Start => (0, child_cross),
+                CrossAlignment::Center => (cross.saturating_sub(child_cross) / 2, child_cross),
+                CrossAlignment::End => (cross.saturating_sub(child_cross), child_cross),
+                CrossAlignment::Stretch => (0, cross),
+                CrossAlignment::Baseline if axis == Axis::Horizontal => {
+                    let child_baseline = child.widget.baseline().unwrap_or(child_cross);
+                    (baseline.saturating_sub(child_baseline), child_cross)
+                }
+                CrossAlignment::Baseline => (0, child_cross),
+            };
+
+            child.region = axis.rectangle(
+                (position + 0.5) as i32,
+                offset as i32,
+                axis.size(axis.main(child.size), child_cross),
+            );
+            child.widget.arrange(child.region, backend)?;
+
+            position += axis.main(child.size) as f64 + self.spacing as f64 + extra;
+        }
+
+        Ok(())
+    }
+
+    fn render(
+        &self,
+        _immediate: &Self::Immediate<'_>,
+        _cache: &mut Self::Cache,
+        backend: &mut B,
+    ) -> Result<(), B::Error> {
+        for child in &self.children {
+            backend.with_region(child.region, |backend| child.widget.render(backend))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widgets::testing::{Fixed, NullBackend};
+
+    /// Measure and arrange a container within a size, returning its size and its children's
+    /// regions.
+    fn lay_out(flex: &mut Flex<'_, NullBackend>, max: Size) -> (Size, Vec<Rectangle>) {
+        let backend = &mut NullBackend;
+        let measured = flex
+            .measure(BoxConstraints::loose(max), &mut (), backend)
+            .unwrap();
+        let region = rect(0, 0, measured.width, measured.height);
+        flex.arrange(region, &mut (), backend).unwrap();
+
+        let regions = flex.children.iter().map(|child| child.region).collect();
+        (measured, regions)
+    }
+
+    fn size(width: u32, height: u32) -> Size {
+        Size { width, height }
+    }
+
+    fn rect(x: i32, y: i32, width: u32, height: u32) -> Rectangle {
+        Rectangle {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn grows_into_spare_space() {
+        let mut flex = Flex::row()
+            .with_child(Fixed::new(10, 10))
+            .with_flex_child(Fixed::new(10, 10), 1.0)
+            .with_flex_child(Fixed::new(10, 20), 2.0);
+
+        let (measured, regions) = lay_out(&mut flex, size(100, 50));
+        assert_eq!(measured, size(100, 20));
+        assert_eq!(
+            regions,
+            [rect(0, 0, 10, 10), rect(10, 0, 33, 10), rect(43, 0, 57, 20)]
+        );
+    }
+
+    #[test]
+    fn shrinks_to_fit() {
+        let mut flex = Flex::column()
+            .with_spacing(5)
+            .with_child(Fixed::new(10, 40))
+            .with_item(FlexItem::new(Fixed::new(10, 40)).with_shrink(0.0))
+            .with_item(FlexItem::new(Fixed::new(10, 20)).with_shrink(2.0));
+
+        // The first and last children give up space in proportion to their shrink factors and
+        // their natural lengths.
+        let (measured, regions) = lay_out(&mut flex, size(50, 90));
+        assert_eq!(measured, size(10, 90));
+        assert_eq!(
+            regions,
+            [rect(0, 0, 10, 30), rect(0, 35, 10, 40), rect(0, 80, 10, 10)]
+        );
+    }
+
+    #[test]
+    fn keeps_natural_size_without_bounds() {
+        let mut flex = Flex::row()
+            .with_spacing(2)
+            .with_flex_child(Fixed::new(10, 10), 1.0)
+            .with_child(Fixed::new(20, 10));
+
+        let (measured, regions) = lay_out(&mut flex, size(u32::MAX, 50));
+        assert_eq!(measured, size(32, 10));
+        assert_eq!(regions, [rect(0, 0, 10, 10), rect(12, 0, 20, 10)]);
+    }
+
+    #[test]
+    fn lines_up_baselines() {
+        let mut flex = Flex::row()
+            .with_cross_alignment(CrossAlignment::Baseline)
+            .with_child(Fixed::new(10, 20).with_baseline(15))
+            .with_child(Fixed::new(10, 10).with_baseline(5))
+            .with_child(Fixed::new(10, 8));
+
+        // Children without a baseline line up by their bottom edge.
+        let (measured, regions) = lay_out(&mut flex, size(100, 100));
+        assert_eq!(measured, size(30, 20));
+        assert_eq!(
+            regions,
+            [rect(0, 0, 10, 20), rect(10, 10, 10, 10), rect(20, 7, 10, 8)]
+        );
+    }
+
+    #[test]
+    fn shares_out_spare_space() {
+        let mut flex = Flex::row()
+            .with_main_alignment(MainAlignment::SpaceBetween)
+            .with_cross_alignment(CrossAlignment::Center)
+            .with_child(Fixed::new(10, 10))
+            .with_child(Fixed::new(10, 20))
+            .with_child(Fixed::new(10, 10));
+
+        let backend = &mut NullBackend;
+        flex.measure(BoxConstraints::loose(size(100, 100)), &mut (), backend)
+            .unwrap();
+        flex.arrange(rect(0, 0, 100, 20), &mut (), backend).unwrap();
+
+        let regions: Vec<_> = flex.children.iter().map(|child| child.region).collect();
+        assert_eq!(
+            regions,
+            [rect(0, 5, 10, 10), rect(45, 0, 10, 20), rect(90, 5, 10, 10)]
+        );
+    }
+}
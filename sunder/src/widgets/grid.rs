@@ -0,0 +1,644 @@
+/*
+
+`sunder` is free software: you can redistribute it and/or modify it under the terms of one of
+the following licenses:
+
+- The GNU Affero General Public License as published by the Free Software Foundation, either version
+  3 of the License, or (at your option) any later version.
+- The Patron License at https://github.com/notgull/sunder/blob/main/LICENSE-PATRON.md, for
+  sponsors and contributors, who can ignore the copyleft provisions of the GNU AGPL for this project.
+
+`sunder` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+Public License and the Patron License for more details.
+
+You should have received a copy of the GNU Affero General Public License and the corresponding Patron
+License along with `sunder`. If not, see <https://www.gnu.org/licenses/>.
+
+*/
+
+//! A container that lays out its children in rows and columns.
+
+use super::boxed::BoxedWidget;
+use crate::tree::{self, Container, Router};
+use crate::{Backend, BoxConstraints, Damage, Event, Rectangle, RenderedWidget, Size, Widget};
+use alloc::vec::Vec;
+
+/// The size of a row or column in a [`Grid`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Track {
+    /// A fixed length, in pixels.
+    Fixed(u32),
+
+    /// Just large enough to fit the cells in the track.
+    Auto,
+
+    /// A share of the space left over after the fixed and auto tracks, like CSS `fr` units.
+    ///
+    /// If the grid can be as large as it wants, these tracks are sized like [`Track::Auto`].
+    Fraction(f64),
+}
+
+impl Track {
+    /// Create a fractional track.
+    pub fn fr(fraction: f64) -> Self {
+        Track::Fraction(fraction)
+    }
+}
+
+/// How a child is placed inside of its cell in a [`Grid`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Alignment {
+    /// The child is placed at the top or left of the cell.
+    Start,
+
+    /// The child is centered in the cell.
+    Center,
+
+    /// The child is placed at the bottom or right of the cell.
+    End,
+
+    /// The child is stretched to fill the cell.
+    #[default]
+    Stretch,
+}
+
+impl Alignment {
+    /// Place a length of `child` inside of a length of `cell`, returning the offset and length.
+    fn place(self, child: u32, cell: u32) -> (u32, u32) {
+        let child = child.min(cell);
+
+        match self {
+            Alignment::Start => (0, child),
+            Alignment::Center => ((cell - child) / 2, child),
+            Alignment::End => (cell - child, child),
+            Alignment::Stretch => (0, cell),
+        }
+    }
+}
+
+/// A child of a [`Grid`], placed in one or more cells.
+pub struct GridItem<'a, B: Backend> {
+    /// The child widget.
+    widget: BoxedWidget<'a, B>,
+
+    /// The first row that the child covers.
+    row: usize,
+
+    /// The first column that the child covers.
+    column: usize,
+
+    /// The number of rows that the child covers.
+    row_span: usize,
+
+    /// The number of columns that the child covers.
+    column_span: usize,
+
+    /// How the child is placed horizontally inside of its cells.
+    horizontal: Alignment,
+
+    /// How the child is placed vertically inside of its cells.
+    vertical: Alignment,
+
+    /// The size that the child wants when it isn't limited.
+    natural: Size,
+
+    /// The size that the child wants within the width of its columns.
+    size: Size,
+
+    /// The region that the child was arranged in.
+    region: Rectangle,
+}
+
+impl<'a, B: Backend + 'a> GridItem<'a, B> {
+    /// Create a new child in the cell at the given row and column, counting from zero.
+    pub fn new(widget: impl RenderedWidget<B> + 'a, row: usize, column: usize) -> Self {
+        Self {
+            widget: BoxedWidget::new(widget),
+            row,
+            column,
+            row_span: 1,
+            column_span: 1,
+            horizontal: Alignment::default(),
+            vertical: Alignment::default(),
+            natural: Size::default(),
+            size: Size::default(),
+            region: Rectangle::default(),
+        }
+    }
+
+    /// Make the child cover more than one row or column.
+    pub fn with_span(mut self, rows: usize, columns: usize) -> Self {
+        self.row_span = rows.max(1);
+        self.column_span = columns.max(1);
+        self
+    }
+
+    /// Set how the child is placed inside of its cells.
+    pub fn with_alignment(mut self, horizontal: Alignment, vertical: Alignment) -> Self {
+        self.horizontal = horizontal;
+        self.vertical = vertical;
+        self
+    }
+}
+
+/// A container that lays out its children in rows and columns.
+///
+/// Children that are placed past the last defined row or column get extra [`Track::Auto`]
+/// tracks.
+pub struct Grid<'a, B: Backend> {
+    /// The children of the grid.
+    items: Vec<GridItem<'a, B>>,
+
+    /// The definitions of the columns.
+    columns: Vec<Track>,
+
+    /// The definitions of the rows.
+    rows: Vec<Track>,
+
+    /// The gap between each column.
+    column_gap: u32,
+
+    /// The gap between each row.
+    row_gap: u32,
+
+    /// The width of each column, from the last layout.
+    column_widths: Vec<u32>,
+
+    /// The height of each row, from the last layout.
+    row_heights: Vec<u32>,
+
+    /// The size of the grid, from the last layout.
+    size: Size,
+
+    /// Routes events to the children.
+    router: Router,
+}
+
+impl<'a, B: Backend + 'a> Grid<'a, B> {
+    /// Create a new, empty grid with the given columns and rows.
+    pub fn new(columns: impl Into<Vec<Track>>, rows: impl Into<Vec<Track>>) -> Self {
+        Self {
+            items: Vec::new(),
+            columns: columns.into(),
+            rows: rows.into(),
+            column_gap: 0,
+            row_gap: 0,
+            column_widths: Vec::new(),
+            row_heights: Vec::new(),
+            size: Size::default(),
+            router: Router::new(),
+        }
+    }
+
+    /// Add a child that covers a single cell.
+    pub fn with_child(
+        self,
+        widget: impl RenderedWidget<B> + 'a,
+        row: usize,
+        column: usize,
+    ) -> Self {
+        self.with_item(GridItem::new(widget, row, column))
+    }
+
+    /// Add a child.
+    pub fn with_item(mut self, item: GridItem<'a, B>) -> Self {
+        self.push(item);
+        self
+    }
+
+    /// Set the gaps between rows and between columns.
+    pub fn with_gaps(mut self, row_gap: u32, column_gap: u32) -> Self {
+        self.row_gap = row_gap;
+        self.column_gap = column_gap;
+        self
+    }
+
+    /// Add a child to the grid.
+    pub fn push(&mut self, item: GridItem<'a, B>) {
+        self.items.push(item);
+    }
+
+    /// Get the number of children.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Whether there are no children.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+}
+
+impl<'a, B: Backend> Grid<'a, B> {
+    /// Work out the size of each track, then measure every child to fit its cells.
+    ///
+    /// Each child's natural size must already be known. If a length is `None`, fractional tracks
+    /// along that axis are sized like auto tracks.
+    fn layout(
+        &mut self,
+        width: Option<u32>,
+        height: Option<u32>,
+        backend: &mut B,
+    ) -> Result<Size, B::Error> {
+        let column_count = self
+            .items
+            .iter()
+            .map(|item| item.column + item.column_span)
+            .max()
+            .unwrap_or(0)
+            .max(self.columns.len());
+        let row_count = self
+            .items
+            .iter()
+            .map(|item| item.row + item.row_span)
+            .max()
+            .unwrap_or(0)
+            .max(self.rows.len());
+
+        let columns: Vec<_> = self
+            .items
+            .iter()
+            .map(|item| (item.column, item.column_span, item.natural.width))
+            .collect();
+        self.column_widths = resolve(
+            &self.columns,
+            column_count,
+            &columns,
+            width,
+            self.column_gap,
+        );
+
+        // Now that we know how wide each child is, we know how tall it wants to be.
+        for item in &mut self.items {
+            let width = span(
+                &self.column_widths,
+                item.column,
+                item.column_span,
+                self.column_gap,
+            );
+            item.size = item.widget.measure(
+                BoxConstraints::loose(Size {
+                    width,
+                    height: u32::MAX,
+                }),
+                backend,
+            )?;
+        }
+
+        let rows: Vec<_> = self
+            .items
+            .iter()
+            .map(|item| (item.row, item.row_span, item.size.height))
+            .collect();
+        self.row_heights = resolve(&self.rows, row_count, &rows, height, self.row_gap);
+
+        self.size = Size {
+            width: span(&self.column_widths, 0, column_count, self.column_gap),
+            height: span(&self.row_heights, 0, row_count, self.row_gap),
+        };
+        Ok(self.size)
+    }
+}
+
+/// Work out the length of each track along one axis.
+///
+/// `items` holds the first track, the number of tracks and the wanted length of each child.
+fn resolve(
+    tracks: &[Track],
+    count: usize,
+    items: &[(usize, usize, u32)],
+    available: Option<u32>,
+    gap: u32,
+) -> Vec<u32> {
+    let track = |i: usize| tracks.get(i).copied().unwrap_or(Track::Auto);
+    let sized_by_content = |i: usize| match track(i) {
+        Track::Fixed(_) => false,
+        Track::Auto => true,
+        Track::Fraction(_) => available.is_none(),
+    };
+
+    let mut lengths: Vec<u32> = (0..count)
+        .map(|i| match track(i) {
+            Track::Fixed(length) => length,
+            _ => 0,
+        })
+        .collect();
+
+    // Fit children covering a single track.
+    for &(start, _, length) in items.iter().filter(|item| item.1 == 1) {
+        if sized_by_content(start) {
+            lengths[start] = lengths[start].max(length);
+        }
+    }
+
+    // Grow the content-sized tracks under children covering several tracks, if they don't fit.
+    for &(start, tracks, length) in items.iter().filter(|item| item.1 > 1) {
+        let growable: Vec<usize> = (start..start + tracks)
+            .filter(|&i| sized_by_content(i))
+            .collect();
+        if growable.is_empty() {
+            continue;
+        }
+
+        let current = span(&lengths, start, tracks, gap);
+        if length > current {
+            let extra = length - current;
+            let share = extra / growable.len() as u32;
+            let remainder = extra as usize % growable.len();
+
+            for (n, &i) in growable.iter().enumerate() {
+                lengths[i] = lengths[i].saturating_add(share + (n < remainder) as u32);
+            }
+        }
+    }
+
+    // Share out the remaining space between the fractional tracks.
+    if let Some(available) = available {
+        let free = available.saturating_sub(span(&lengths, 0, count, gap)) as f64;
+        let total: f64 = (0..count)
+            .filter_map(|i| match track(i) {
+                Track::Fraction(fraction) => Some(fraction.max(0.0)),
+                _ => None,
+            })
+            .sum();
+
+        if total > 0.0 {
+            // Round as we go, so the rounding errors don't add up.
+            let mut shared = 0.0;
+            for (i, length) in lengths.iter_mut().enumerate() {
+                if let Track::Fraction(fraction) = track(i) {
+                    let start = (shared + 0.5) as u32;
+                    shared += free * fraction.max(0.0) / total;
+                    *length = (shared + 0.5) as u32 - start;
+                }
+            }
+        }
+    }
+
+    lengths
+}
+
+/// Get the total length of a run of tracks, including the gaps between them.
+fn span(lengths: &[u32], start: usize, count: usize, gap: u32) -> u32 {
+    let end = (start + count).min(lengths.len());
+    let start = start.min(end);
+
+    let gaps = gap.saturating_mul((end - start).saturating_sub(1) as u32);
+    lengths[start..end]
+        .iter()
+        .fold(gaps, |total, &length| total.saturating_add(length))
+}
+
+impl<B: Backend> Container for Grid<'_, B> {
+    fn child_count(&self) -> usize {
+        self.items.len()
+    }
+
+    fn child_region(&self, index: usize) -> Rectangle {
+        self.items[index].region
+    }
+
+    fn child_event(&mut self, index: usize, event: Event) -> Damage {
+        self.items[index].widget.handle_event(event)
+    }
+
+    fn child_captures_pointer(&self, index: usize) -> bool {
+        self.items[index].widget.captures_pointer()
+    }
+
+    fn child_is_focusable(&self, index: usize) -> bool {
+        self.items[index].widget.is_focusable()
+    }
+
+    fn child_steps_focus(&self, index: usize, forwards: bool) -> bool {
+        self.items[index].widget.steps_focus(forwards)
+    }
+
+    fn router(&mut self) -> &mut Router {
+        &mut self.router
+    }
+}
+
+impl<B: Backend> Widget for Grid<'_, B> {
+    type Immediate<'a> = ();
+
+    fn handle_event(&mut self, _immediate: &mut Self::Immediate<'_>, event: Event) -> Damage {
+        tree::dispatch(self, event)
+    }
+
+    fn is_focusable(&self) -> bool {
+        tree::is_focusable(self)
+    }
+
+    fn steps_focus(&self, forwards: bool) -> bool {
+        tree::steps_focus(self, &self.router, forwards)
+    }
+
+    fn captures_pointer(&self) -> bool {
+        self.items
+            .iter()
+            .any(|child| child.widget.captures_pointer())
+    }
+}
+
+impl<'a, B: Backend<Output = ()>> RenderedWidget<B> for Grid<'a, B> {
+    type Cache = ();
+
+    fn measure(
+        &mut self,
+        constraints: BoxConstraints,
+        _cache: &mut Self::Cache,
+        backend: &mut B,
+    ) -> Result<Size, B::Error> {
+        for item in &mut self.items {
+            item.natural = item.widget.measure(BoxConstraints::UNBOUNDED, backend)?;
+        }
+
+        let width = Some(constraints.max.width).filter(|_| constraints.has_bounded_width());
+        let height = Some(constraints.max.height).filter(|_| constraints.has_bounded_height());
+        let size = self.layout(width, height, backend)?;
+
+        Ok(constraints.constrain(size))
+    }
+
+    fn arrange(
+        &mut self,
+        region: Rectangle,
+        _cache: &mut Self::Cache,
+        backend: &mut B,
+    ) -> Result<(), B::Error> {
+        // The parent might have given us a different size than we asked for.
+        if self.size.width != region.width || self.size.height != region.height {
+            self.layout(Some(region.width), Some(region.height), backend)?;
+        }
+
+        // Track positions start after every track and gap before them.
+        let offsets = |lengths: &[u32], gap: u32| {
+            lengths
+                .iter()
+                .scan(0u32, |position, &length| {
+                    let start = *position;
+                    *position = position.saturating_add(length).saturating_add(gap);
+                    Some(start)
+                })
+                .collect::<Vec<u32>>()
+        };
+        let column_offsets = offsets(&self.column_widths, self.column_gap);
+        let row_offsets = offsets(&self.row_heights, self.row_gap);
+
+        // Tracks past an unbounded track are pushed as far as they can go.
+        let offset = |offset: u32, position: u32| {
+            offset.saturating_add(position).min(i32::MAX as u32) as i32
+        };
+
+        for item in &mut self.items {
+            let cell_width = span(
+                &self.column_widths,
+                item.column,
+                item.column_span,
+                self.column_gap,
+            );
+            let cell_height = span(&self.row_heights, item.row, item.row_span, self.row_gap);
+
+            let (x, width) = item.horizontal.place(item.size.width, cell_width);
+            let (y, height) = item.vertical.place(item.size.height, cell_height);
+
+            item.region = Rectangle {
+                x: offset(column_offsets[item.column], x),
+                y: offset(row_offsets[item.row], y),
+                width,
+                height,
+            };
+            item.widget.arrange(item.region, backend)?;
+        }
+
+        Ok(())
+    }
+
+    fn render(
+        &self,
+        _immediate: &Self::Immediate<'_>,
+        _cache: &mut Self::Cache,
+        backend: &mut B,
+    ) -> Result<(), B::Error> {
+        for item in &self.items {
+            backend.with_region(item.region, |backend| item.widget.render(backend))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widgets::testing::{Fixed, NullBackend};
+
+    #[test]
+    fn shares_space_between_fractions() {
+        let tracks = [
+            Track::Fixed(50),
+            Track::Auto,
+            Track::fr(1.0),
+            Track::fr(3.0),
+        ];
+        let items = [(1, 1, 30), (2, 1, 10)];
+
+        let lengths = resolve(&tracks, 4, &items, Some(250), 10);
+        assert_eq!(lengths, [50, 30, 35, 105]);
+
+        // Without a length to fill, fractions fit their content.
+        let lengths = resolve(&tracks, 4, &items, None, 10);
+        assert_eq!(lengths, [50, 30, 10, 0]);
+    }
+
+    #[test]
+    fn grows_auto_tracks_under_spans() {
+        let tracks = [Track::Auto, Track::Auto, Track::Fixed(20)];
+
+        // The spanning child needs more room than the tracks and gaps it covers, which only the
+        // auto tracks can make.
+        let lengths = resolve(&tracks, 3, &[(0, 1, 10), (0, 3, 100)], None, 5);
+        assert_eq!(lengths, [40, 30, 20]);
+
+        let lengths = resolve(&tracks, 3, &[(0, 1, 10), (0, 3, 101)], None, 5);
+        assert_eq!(lengths, [41, 30, 20]);
+
+        // Children that already fit don't grow anything.
+        let lengths = resolve(&tracks, 3, &[(0, 1, 10), (0, 2, 12)], None, 5);
+        assert_eq!(lengths, [10, 0, 20]);
+    }
+
+    #[test]
+    fn adds_auto_tracks_past_the_end() {
+        let lengths = resolve(&[Track::Fixed(20)], 3, &[(2, 1, 15)], Some(100), 0);
+        assert_eq!(lengths, [20, 0, 15]);
+    }
+
+    #[test]
+    fn places_children_in_cells() {
+        let mut grid = Grid::new(
+            [Track::Fixed(40), Track::fr(1.0)],
+            [Track::Auto, Track::Auto],
+        )
+        .with_gaps(5, 10)
+        .with_child(Fixed::new(30, 20), 0, 0)
+        .with_item(
+            GridItem::new(Fixed::new(10, 10), 0, 1)
+                .with_alignment(Alignment::Center, Alignment::Center),
+        )
+        .with_item(GridItem::new(Fixed::new(200, 15), 1, 0).with_span(1, 2));
+
+        let backend = &mut NullBackend;
+        let max = Size {
+            width: 150,
+            height: 100,
+        };
+        let size = grid
+            .measure(BoxConstraints::loose(max), &mut (), backend)
+            .unwrap();
+        assert_eq!(
+            size,
+            Size {
+                width: 150,
+                height: 40
+            }
+        );
+
+        let region = Rectangle {
+            x: 0,
+            y: 0,
+            width: size.width,
+            height: size.height,
+        };
+        grid.arrange(region, &mut (), backend).unwrap();
+        assert_eq!(grid.column_widths, [40, 100]);
+        assert_eq!(grid.row_heights, [20, 15]);
+
+        let regions: Vec<_> = grid.items.iter().map(|item| item.region).collect();
+        assert_eq!(
+            regions,
+            [
+                Rectangle {
+                    x: 0,
+                    y: 0,
+                    width: 40,
+                    height: 20
+                },
+                Rectangle {
+                    x: 95,
+                    y: 5,
+                    width: 10,
+                    height: 10
+                },
+                Rectangle {
+                    x: 0,
+                    y: 25,
+                    width: 150,
+                    height: 15
+                },
+            ]
+        );
+    }
+}
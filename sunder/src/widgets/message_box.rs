@@ -0,0 +1,443 @@
+/*
+
+`sunder` is free software: you can redistribute it and/or modify it under the terms of one of
+the following licenses:
+
+- The GNU Affero General Public License as published by the Free Software Foundation, either version
+  3 of the License, or (at your option) any later version.
+- The Patron License at https://github.com/notgull/sunder/blob/main/LICENSE-PATRON.md, for
+  sponsors and contributors, who can ignore the copyleft provisions of the GNU AGPL for this project.
+
+`sunder` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+Public License and the Patron License for more details.
+
+You should have received a copy of the GNU Affero General Public License and the corresponding Patron
+License along with `sunder`. If not, see <https://www.gnu.org/licenses/>.
+
+*/
+
+//! A message with a row of buttons underneath it, for use in dialogs.
+
+use super::button::{ImmediateState as ButtonState, PushButton};
+use super::text_view::TextView;
+use crate::tree::{self, Container, Router};
+use crate::{Damage, Event, Key, KeyEvent, Rectangle, Widget};
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+
+cfg_piet! {
+    use super::button::PietCache as ButtonPietCache;
+    use super::text_view::PietCache as TextPietCache;
+    use crate::piet::PietBackend;
+    use crate::{Backend, BoxConstraints, RenderedWidget, Size};
+    use piet::kurbo::RoundedRect;
+    use piet::{Color, RenderContext};
+
+    /// The space between the edge of the box and its contents.
+    const PADDING: u32 = 12;
+
+    /// The space between the message and the buttons, and between each button.
+    const SPACING: u32 = 8;
+}
+
+/// The width that the message is wrapped at.
+const MAX_MESSAGE_WIDTH: f64 = 320.0;
+
+/// A message with a row of buttons underneath it.
+///
+/// The buttons are lined up on the right, in the order they were given in. Tab, Shift+Tab and the
+/// arrow keys move focus between the buttons, and escape dismisses the box.
+pub struct MessageBox<'a> {
+    /// The message.
+    message: TextView<'a>,
+
+    /// The buttons underneath the message.
+    buttons: Vec<PushButton<'a>>,
+
+    /// The size of the message, as it was last measured.
+    #[cfg(feature = "piet")]
+    message_size: Size,
+
+    /// The region of each button, relative to the box.
+    button_regions: Vec<Rectangle>,
+
+    /// The size that the box was last arranged at.
+    #[cfg(feature = "piet")]
+    size: Size,
+
+    /// Routes pointer events to the buttons.
+    router: Router,
+}
+
+/// The immediate state of a [`MessageBox`].
+#[derive(Default)]
+pub struct MessageBoxState {
+    /// The state of each button.
+    buttons: Vec<ButtonState>,
+
+    /// The index of the button with keyboard focus.
+    focused: Option<usize>,
+
+    /// The index of the button that was clicked since this was last checked.
+    chosen: Option<usize>,
+
+    /// Whether the box was dismissed since this was last checked.
+    dismissed: bool,
+}
+
+impl MessageBoxState {
+    /// Get the index of the button with keyboard focus.
+    pub fn focused(&self) -> Option<usize> {
+        self.focused
+    }
+
+    /// Check which button has been clicked, resetting it.
+    pub fn take_chosen(&mut self) -> Option<usize> {
+        self.chosen.take()
+    }
+
+    /// Check whether the box has been dismissed without clicking a button, resetting the flag.
+    pub fn take_dismissed(&mut self) -> bool {
+        core::mem::take(&mut self.dismissed)
+    }
+}
+
+cfg_piet! {
+    pub struct PietCache<R: RenderContext + ?Sized> {
+        message: TextPietCache<R>,
+        buttons: Vec<ButtonPietCache<R>>,
+    }
+
+    impl<R: RenderContext + ?Sized> Default for PietCache<R> {
+        fn default() -> Self {
+            Self {
+                message: Default::default(),
+                buttons: Vec::new(),
+            }
+        }
+    }
+}
+
+impl<'a> MessageBox<'a> {
+    /// Create a new message box with the given message and button labels.
+    pub fn new<L: Into<Cow<'a, str>>>(
+        message: impl Into<Cow<'a, str>>,
+        buttons: impl IntoIterator<Item = L>,
+    ) -> Self {
+        let buttons: Vec<_> = buttons.into_iter().map(PushButton::new).collect();
+
+        Self {
+            message: TextView::new(message).with_max_width(MAX_MESSAGE_WIDTH),
+            button_regions: alloc::vec![Rectangle::default(); buttons.len()],
+            buttons,
+            #[cfg(feature = "piet")]
+            message_size: Size::default(),
+            #[cfg(feature = "piet")]
+            size: Size::default(),
+            router: Router::new(),
+        }
+    }
+
+    /// Get the message.
+    pub fn message(&self) -> &str {
+        self.message.text()
+    }
+
+    /// Get the label of a button.
+    pub fn button(&self, index: usize) -> Option<&str> {
+        self.buttons.get(index).map(PushButton::text)
+    }
+
+    /// Get the number of buttons.
+    pub fn len(&self) -> usize {
+        self.buttons.len()
+    }
+
+    /// Whether the box has no buttons.
+    pub fn is_empty(&self) -> bool {
+        self.buttons.is_empty()
+    }
+
+    /// Get the region of the message, relative to the box.
+    #[cfg(feature = "piet")]
+    fn message_region(&self) -> Rectangle {
+        Rectangle {
+            x: PADDING as i32,
+            y: PADDING as i32,
+            width: self.message_size.width,
+            height: self.message_size.height,
+        }
+    }
+
+    /// Deliver an event to a button in its own coordinates, noting whether it was clicked.
+    fn deliver(&mut self, immediate: &mut MessageBoxState, index: usize, event: Event) -> Damage {
+        let state = &mut immediate.buttons[index];
+        let damage = self.buttons[index].handle_event(state, event);
+
+        if state.take_clicked() {
+            immediate.chosen = Some(index);
+        }
+
+        damage
+    }
+
+    /// Deliver a focus or keyboard event to a button.
+    fn send(&mut self, immediate: &mut MessageBoxState, index: usize, event: Event) -> Damage {
+        let region = self.button_regions[index];
+        self.deliver(immediate, index, event).within(region)
+    }
+
+    /// Move keyboard focus to another button.
+    fn move_focus(&mut self, immediate: &mut MessageBoxState, index: usize) -> Damage {
+        match immediate.focused.replace(index) {
+            Some(old) if old == index => Damage::None,
+            Some(old) => {
+                let damage = self.send(immediate, old, Event::FocusOut);
+                damage.union(self.send(immediate, index, Event::FocusIn))
+            }
+            None => self.send(immediate, index, Event::FocusIn),
+        }
+    }
+
+    /// Move keyboard focus one button forwards or backwards, wrapping around at the ends.
+    fn step_focus(&mut self, immediate: &mut MessageBoxState, forwards: bool) -> Damage {
+        let len = self.buttons.len();
+        let index = match (immediate.focused, forwards) {
+            (Some(i), true) => (i + 1) % len,
+            (Some(i), false) => (i + len - 1) % len,
+            (None, true) => 0,
+            (None, false) => len - 1,
+        };
+
+        self.move_focus(immediate, index)
+    }
+}
+
+/// A message box along with its state, so that events can be routed through it.
+struct Routed<'s, 'a> {
+    message_box: &'s mut MessageBox<'a>,
+    state: &'s mut MessageBoxState,
+}
+
+impl Container for Routed<'_, '_> {
+    fn child_count(&self) -> usize {
+        self.message_box.buttons.len()
+    }
+
+    fn child_region(&self, index: usize) -> Rectangle {
+        self.message_box.button_regions[index]
+    }
+
+    fn child_event(&mut self, index: usize, event: Event) -> Damage {
+        self.message_box.deliver(self.state, index, event)
+    }
+
+    fn child_captures_pointer(&self, index: usize) -> bool {
+        self.message_box.buttons[index].captures_pointer()
+    }
+
+    fn router(&mut self) -> &mut Router {
+        &mut self.message_box.router
+    }
+
+    // Pressing a button moves focus to it.
+    fn bubble(&mut self, event: &Event, target: Option<usize>) -> Damage {
+        match (event, target) {
+            (Event::PointerDown(_), Some(index)) => self.message_box.move_focus(self.state, index),
+            _ => Damage::None,
+        }
+    }
+}
+
+impl Widget for MessageBox<'_> {
+    type Immediate<'a> = MessageBoxState;
+
+    fn handle_event(&mut self, immediate: &mut Self::Immediate<'_>, event: Event) -> Damage {
+        immediate
+            .buttons
+            .resize_with(self.buttons.len(), ButtonState::default);
+
+        if self.buttons.is_empty() {
+            if let Event::KeyDown(KeyEvent {
+                key: Key::Escape, ..
+            }) = event
+            {
+                immediate.dismissed = true;
+            }
+
+            return Damage::None;
+        }
+
+        match event {
+            // Focus goes back to the button that had it last.
+            Event::FocusIn => {
+                let index = immediate.focused.take().unwrap_or(0);
+                self.move_focus(immediate, index)
+            }
+
+            Event::FocusOut => match immediate.focused {
+                Some(index) => self.send(immediate, index, Event::FocusOut),
+                None => Damage::None,
+            },
+
+            Event::KeyDown(KeyEvent {
+                key: Key::Tab,
+                modifiers,
+                ..
+            }) => self.step_focus(immediate, !modifiers.shift),
+            Event::KeyDown(KeyEvent {
+                key: Key::Right, ..
+            }) => self.step_focus(immediate, true),
+            Event::KeyDown(KeyEvent { key: Key::Left, .. }) => self.step_focus(immediate, false),
+
+            Event::KeyDown(KeyEvent {
+                key: Key::Escape, ..
+            }) => {
+                immediate.dismissed = true;
+                Damage::None
+            }
+
+            event if event.is_keyboard() => match immediate.focused {
+                Some(index) => self.send(immediate, index, event),
+                None => Damage::None,
+            },
+
+            event => tree::dispatch(
+                &mut Routed {
+                    message_box: self,
+                    state: immediate,
+                },
+                event,
+            ),
+        }
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn captures_pointer(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(feature = "piet")]
+impl<'a, R: RenderContext> RenderedWidget<PietBackend<R>> for MessageBox<'a> {
+    type Cache = PietCache<R>;
+
+    fn measure(
+        &mut self,
+        constraints: BoxConstraints,
+        cache: &mut Self::Cache,
+        backend: &mut PietBackend<R>,
+    ) -> Result<Size, <PietBackend<R> as Backend>::Error> {
+        let inner = constraints.loosen().deflate(PADDING * 2, PADDING * 2);
+        self.message_size = self.message.measure(inner, &mut cache.message, backend)?;
+
+        // Line the buttons up in a row, all as tall as the tallest one.
+        cache
+            .buttons
+            .resize_with(self.buttons.len(), Default::default);
+        let mut row = Size::default();
+        for ((button, cache), region) in self
+            .buttons
+            .iter_mut()
+            .zip(&mut cache.buttons)
+            .zip(&mut self.button_regions)
+        {
+            let size = button.measure(inner, cache, backend)?;
+            region.width = size.width;
+            region.height = size.height;
+
+            row.width += size.width;
+            row.height = row.height.max(size.height);
+        }
+
+        row.width += SPACING * (self.buttons.len() as u32).saturating_sub(1);
+        for region in &mut self.button_regions {
+            region.height = row.height;
+        }
+
+        let spacing = if self.buttons.is_empty() { 0 } else { SPACING };
+        Ok(constraints.constrain(Size {
+            width: self.message_size.width.max(row.width) + PADDING * 2,
+            height: self.message_size.height + spacing + row.height + PADDING * 2,
+        }))
+    }
+
+    fn arrange(
+        &mut self,
+        region: Rectangle,
+        cache: &mut Self::Cache,
+        backend: &mut PietBackend<R>,
+    ) -> Result<(), piet::Error> {
+        self.size = Size {
+            width: region.width,
+            height: region.height,
+        };
+
+        let message = self.message_region();
+        self.message.arrange(message, &mut cache.message, backend)?;
+
+        // The buttons sit in the bottom right corner.
+        let mut x = region.width as i32 - PADDING as i32;
+        for ((button, cache), button_region) in self
+            .buttons
+            .iter_mut()
+            .zip(&mut cache.buttons)
+            .zip(&mut self.button_regions)
+            .rev()
+        {
+            x -= button_region.width as i32;
+            button_region.x = x;
+            button_region.y = region.height as i32 - PADDING as i32 - button_region.height as i32;
+            button.arrange(*button_region, cache, backend)?;
+
+            x -= SPACING as i32;
+        }
+
+        Ok(())
+    }
+
+    fn render(
+        &self,
+        immediate: &Self::Immediate<'_>,
+        cache: &mut Self::Cache,
+        backend: &mut PietBackend<R>,
+    ) -> Result<(), piet::Error> {
+        // The panel is opaque, so that it hides whatever is underneath it.
+        let panel = RoundedRect::new(
+            0.5,
+            0.5,
+            self.size.width as f64 - 0.5,
+            self.size.height as f64 - 0.5,
+            PADDING as f64 / 2.0,
+        );
+
+        let rc = backend.context();
+        let fill = rc.solid_brush(Color::grey(0.95));
+        rc.fill(panel, &fill);
+        let border = rc.solid_brush(Color::grey(0.4));
+        rc.stroke(panel, &border, 1.0);
+
+        backend.with_region(self.message_region(), |backend| {
+            self.message.render(&(), &mut cache.message, backend)
+        })?;
+
+        let default_state = ButtonState::default();
+        for (index, ((button, cache), region)) in self
+            .buttons
+            .iter()
+            .zip(&mut cache.buttons)
+            .zip(&self.button_regions)
+            .enumerate()
+        {
+            // The state of the buttons is only filled in once an event comes in.
+            let state = immediate.buttons.get(index).unwrap_or(&default_state);
+            backend.with_region(*region, |backend| button.render(state, cache, backend))?;
+        }
+
+        Ok(())
+    }
+}
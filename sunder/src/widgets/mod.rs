@@ -19,8 +19,26 @@ License along with `sunder`. If not, see <https://www.gnu.org/licenses/>.
 
 //! A collection of predefined widgets.
 
+pub mod boxed;
 pub mod button;
+mod editing;
+pub mod flex;
+pub mod grid;
+pub mod message_box;
+pub mod scroll_view;
+pub mod text_area;
+pub mod text_input;
 pub mod text_view;
 
+#[cfg(test)]
+mod testing;
+
+pub use boxed::BoxedWidget;
 pub use button::PushButton;
+pub use flex::{Axis, CrossAlignment, Flex, FlexItem, MainAlignment};
+pub use grid::{Grid, GridItem, Track};
+pub use message_box::{MessageBox, MessageBoxState};
+pub use scroll_view::{ScrollAxes, ScrollState, ScrollView, Scrollbars};
+pub use text_area::{TextArea, TextAreaState, TextPosition};
+pub use text_input::{TextInput, TextInputState};
 pub use text_view::TextView;
@@ -0,0 +1,772 @@
+/*
+
+`sunder` is free software: you can redistribute it and/or modify it under the terms of one of
+the following licenses:
+
+- The GNU Affero General Public License as published by the Free Software Foundation, either version
+  3 of the License, or (at your option) any later version.
+- The Patron License at https://github.com/notgull/sunder/blob/main/LICENSE-PATRON.md, for
+  sponsors and contributors, who can ignore the copyleft provisions of the GNU AGPL for this project.
+
+`sunder` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+Public License and the Patron License for more details.
+
+You should have received a copy of the GNU Affero General Public License and the corresponding Patron
+License along with `sunder`. If not, see <https://www.gnu.org/licenses/>.
+
+*/
+
+//! A viewport onto content that is larger than it, with scrollbars.
+
+use super::boxed::BoxedWidget;
+use crate::tree::{self, Container, Router};
+use crate::{BoxConstraints, Damage, Event, Rectangle, RenderedWidget, Size, Widget};
+
+cfg_piet! {
+    use crate::piet::{draw_box, BoxStyle, PietBackend};
+    use crate::Backend;
+    use piet::RenderContext;
+}
+
+/// The thickness of a scrollbar.
+pub const SCROLLBAR_WIDTH: u32 = 8;
+
+/// The shortest that a scrollbar thumb can be.
+const MIN_THUMB_LENGTH: f64 = 16.0;
+
+/// The axes that a viewport can scroll along.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScrollAxes {
+    /// The content can only scroll up and down.
+    #[default]
+    Vertical,
+
+    /// The content can only scroll left and right.
+    Horizontal,
+
+    /// The content can scroll in any direction.
+    Both,
+}
+
+impl ScrollAxes {
+    /// Whether the content can scroll left and right.
+    pub fn horizontal(self) -> bool {
+        matches!(self, ScrollAxes::Horizontal | ScrollAxes::Both)
+    }
+
+    /// Whether the content can scroll up and down.
+    pub fn vertical(self) -> bool {
+        matches!(self, ScrollAxes::Vertical | ScrollAxes::Both)
+    }
+
+    /// Get the constraints for the content, given the constraints for the viewport.
+    ///
+    /// The content can be as long as it wants along the axes that scroll.
+    pub fn content_constraints(self, constraints: BoxConstraints) -> BoxConstraints {
+        BoxConstraints::loose(Size {
+            width: if self.horizontal() {
+                u32::MAX
+            } else {
+                constraints.max.width
+            },
+            height: if self.vertical() {
+                u32::MAX
+            } else {
+                constraints.max.height
+            },
+        })
+    }
+}
+
+/// The scroll position of a viewport, along with the scrollbar being dragged.
+///
+/// All positions are relative to the top left corner of the viewport.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ScrollState {
+    /// How far the content is scrolled to the right.
+    offset_x: f64,
+
+    /// How far the content is scrolled down.
+    offset_y: f64,
+
+    /// The size of the viewport.
+    viewport: Size,
+
+    /// The size of the content.
+    content: Size,
+
+    /// The scrollbar thumb being dragged.
+    drag: Option<Drag>,
+
+    /// Whether the pointer is over the thumb of the vertical scrollbar, or of the horizontal one.
+    hover: Option<bool>,
+}
+
+/// A scrollbar thumb being dragged.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Drag {
+    /// Whether the vertical scrollbar is being dragged.
+    vertical: bool,
+
+    /// The distance from the start of the thumb to the cursor.
+    grab: f64,
+}
+
+impl ScrollState {
+    /// Get how far the content is scrolled, to the right and down.
+    pub fn offset(&self) -> (f64, f64) {
+        (self.offset_x, self.offset_y)
+    }
+
+    /// Get the size of the viewport.
+    pub fn viewport(&self) -> Size {
+        self.viewport
+    }
+
+    /// Get the size of the content.
+    pub fn content(&self) -> Size {
+        self.content
+    }
+
+    /// Whether a scrollbar thumb is being dragged.
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    /// Whether the pointer is over the thumb of a scrollbar, or is dragging it.
+    pub fn is_hovered(&self, vertical: bool) -> bool {
+        self.hover == Some(vertical) || self.drag.map(|drag| drag.vertical) == Some(vertical)
+    }
+
+    /// Update the sizes of the viewport and the content, keeping the offset in range.
+    pub fn set_sizes(&mut self, viewport: Size, content: Size) {
+        self.viewport = viewport;
+        self.content = content;
+        self.set_offset(self.offset_x, self.offset_y);
+    }
+
+    /// Scroll to the given offset, limited to the scrollable range.
+    ///
+    /// Returns `true` if the offset changed.
+    pub fn set_offset(&mut self, x: f64, y: f64) -> bool {
+        let (max_x, max_y) = self.max_offset();
+        let x = x.max(0.0).min(max_x);
+        let y = y.max(0.0).min(max_y);
+
+        let changed = x != self.offset_x || y != self.offset_y;
+        self.offset_x = x;
+        self.offset_y = y;
+        changed
+    }
+
+    /// Scroll by the given distance.
+    ///
+    /// Returns `true` if the offset changed.
+    pub fn scroll_by(&mut self, dx: f64, dy: f64) -> bool {
+        self.set_offset(self.offset_x + dx, self.offset_y + dy)
+    }
+
+    /// Scroll as little as possible to bring an area of the content into view.
+    ///
+    /// `target` is relative to the top left corner of the content. If it is larger than the
+    /// viewport, its top left corner is brought into view. Returns `true` if the offset changed.
+    pub fn scroll_to(&mut self, target: Rectangle) -> bool {
+        let fit = |offset: f64, start: i32, length: u32, viewport: u32| {
+            let start = start as f64;
+            let end = start + length as f64;
+            let viewport = viewport as f64;
+
+            if start < offset || end - start > viewport {
+                start
+            } else if end > offset + viewport {
+                end - viewport
+            } else {
+                offset
+            }
+        };
+
+        let x = fit(self.offset_x, target.x, target.width, self.viewport.width);
+        let y = fit(self.offset_y, target.y, target.height, self.viewport.height);
+        self.set_offset(x, y)
+    }
+
+    /// Respond to scrolling and scrollbar input.
+    ///
+    /// The event should be relative to the top left corner of the viewport. Returns `true` if the
+    /// viewport needs to be redrawn.
+    pub fn handle_event(&mut self, event: &Event) -> bool {
+        let bounds = Rectangle {
+            x: 0,
+            y: 0,
+            width: self.viewport.width,
+            height: self.viewport.height,
+        };
+
+        match *event {
+            Event::Scroll {
+                x,
+                y,
+                delta_x,
+                delta_y,
+                ..
+            } if bounds.contains(x, y) => self.scroll_by(delta_x, delta_y),
+
+            Event::PointerDown(pointer) if pointer.is_primary() => {
+                let (x, y) = (pointer.x, pointer.y);
+                for vertical in [true, false] {
+                    let (track, thumb) = match self.scrollbar(vertical) {
+                        Some(scrollbar) => scrollbar,
+                        None => continue,
+                    };
+                    if !track.contains(x, y) {
+                        continue;
+                    }
+
+                    let (position, thumb_start) = if vertical {
+                        (y, thumb.y as f64)
+                    } else {
+                        (x, thumb.x as f64)
+                    };
+
+                    if thumb.contains(x, y) {
+                        self.drag = Some(Drag {
+                            vertical,
+                            grab: position - thumb_start,
+                        });
+                        return true;
+                    }
+
+                    // Clicking on the track moves by a page towards the cursor.
+                    let page = if vertical {
+                        self.viewport.height
+                    } else {
+                        self.viewport.width
+                    } as f64;
+                    let page = if position < thumb_start { -page } else { page };
+
+                    return if vertical {
+                        self.scroll_by(0.0, page)
+                    } else {
+                        self.scroll_by(page, 0.0)
+                    };
+                }
+
+                false
+            }
+
+            Event::PointerMove(pointer) => {
+                let (x, y) = (pointer.x, pointer.y);
+                let drag = match self.drag {
+                    Some(drag) => drag,
+                    None => return self.hover_at(x, y),
+                };
+                let (track, thumb) = match self.scrollbar(drag.vertical) {
+                    Some(scrollbar) => scrollbar,
+                    None => return false,
+                };
+
+                // Move the thumb so that it stays under the cursor.
+                let (position, track_start, track_length, thumb_length, max_offset) =
+                    if drag.vertical {
+                        (y, track.y, track.height, thumb.height, self.max_offset().1)
+                    } else {
+                        (x, track.x, track.width, thumb.width, self.max_offset().0)
+                    };
+                let travel = (track_length as f64 - thumb_length as f64).max(1.0);
+                let offset = (position - drag.grab - track_start as f64) / travel * max_offset;
+
+                if drag.vertical {
+                    self.set_offset(self.offset_x, offset)
+                } else {
+                    self.set_offset(offset, self.offset_y)
+                }
+            }
+
+            Event::PointerUp(pointer) if pointer.is_primary() => {
+                self.hover_at(pointer.x, pointer.y);
+                self.drag.take().is_some()
+            }
+            Event::PointerCancel(_) => self.drag.take().is_some(),
+            Event::PointerLeave(_) => self.hover.take().is_some(),
+
+            _ => false,
+        }
+    }
+
+    /// Note which scrollbar thumb the pointer is over, returning whether that changed.
+    fn hover_at(&mut self, x: f64, y: f64) -> bool {
+        let hover = [true, false].into_iter().find(|&vertical| {
+            self.scrollbar(vertical)
+                .is_some_and(|(_, thumb)| thumb.contains(x, y))
+        });
+        core::mem::replace(&mut self.hover, hover) != hover
+    }
+
+    /// Get the furthest that the content can be scrolled, to the right and down.
+    fn max_offset(&self) -> (f64, f64) {
+        (
+            self.content.width.saturating_sub(self.viewport.width) as f64,
+            self.content.height.saturating_sub(self.viewport.height) as f64,
+        )
+    }
+
+    /// Get the track and thumb of a scrollbar, if it is shown.
+    ///
+    /// Scrollbars are only shown when there's content to scroll to.
+    pub fn scrollbar(&self, vertical: bool) -> Option<(Rectangle, Rectangle)> {
+        let (max_x, max_y) = self.max_offset();
+        let (shown, other_shown) = if vertical {
+            (max_y > 0.0, max_x > 0.0)
+        } else {
+            (max_x > 0.0, max_y > 0.0)
+        };
+        if !shown {
+            return None;
+        }
+
+        // Leave room in the corner for the other scrollbar.
+        let corner = if other_shown { SCROLLBAR_WIDTH } else { 0 };
+        let track = if vertical {
+            Rectangle {
+                x: self.viewport.width.saturating_sub(SCROLLBAR_WIDTH) as i32,
+                y: 0,
+                width: SCROLLBAR_WIDTH,
+                height: self.viewport.height.saturating_sub(corner),
+            }
+        } else {
+            Rectangle {
+                x: 0,
+                y: self.viewport.height.saturating_sub(SCROLLBAR_WIDTH) as i32,
+                width: self.viewport.width.saturating_sub(corner),
+                height: SCROLLBAR_WIDTH,
+            }
+        };
+
+        let (track_length, viewport, content, offset, max_offset) = if vertical {
+            (
+                track.height as f64,
+                self.viewport.height as f64,
+                self.content.height as f64,
+                self.offset_y,
+                max_y,
+            )
+        } else {
+            (
+                track.width as f64,
+                self.viewport.width as f64,
+                self.content.width as f64,
+                self.offset_x,
+                max_x,
+            )
+        };
+
+        let thumb_length = (track_length * viewport / content)
+            .max(MIN_THUMB_LENGTH)
+            .min(track_length);
+        let thumb_start = (track_length - thumb_length) * offset / max_offset;
+
+        let thumb = if vertical {
+            Rectangle {
+                y: thumb_start as i32,
+                height: thumb_length as u32,
+                ..track
+            }
+        } else {
+            Rectangle {
+                x: thumb_start as i32,
+                width: thumb_length as u32,
+                ..track
+            }
+        };
+
+        Some((track, thumb))
+    }
+}
+
+/// Draws the scrollbars for a [`ScrollState`].
+///
+/// This is drawn over the top of the viewport, after the content. The track is styled by the
+/// `scrollbar-track` theme class, and the thumb by `scrollbar`, or `scrollbar:hover` while the
+/// pointer is over it or dragging it.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Scrollbars;
+
+impl Widget for Scrollbars {
+    type Immediate<'a> = ScrollState;
+
+    fn handle_event(&mut self, immediate: &mut Self::Immediate<'_>, event: Event) -> Damage {
+        immediate.handle_event(&event).into()
+    }
+}
+
+#[cfg(feature = "piet")]
+impl<R: RenderContext> RenderedWidget<PietBackend<R>> for Scrollbars {
+    type Cache = ();
+
+    fn measure(
+        &mut self,
+        constraints: BoxConstraints,
+        _cache: &mut Self::Cache,
+        _backend: &mut PietBackend<R>,
+    ) -> Result<Size, piet::Error> {
+        // Scrollbars fill whatever viewport they are drawn over.
+        Ok(constraints.max)
+    }
+
+    fn render(
+        &self,
+        immediate: &Self::Immediate<'_>,
+        _cache: &mut Self::Cache,
+        backend: &mut PietBackend<R>,
+    ) -> Result<(), piet::Error> {
+        let theme = backend.theme();
+        let track = BoxStyle::from_classes(theme, ["scrollbar-track"]);
+        let thumb = BoxStyle::from_classes(theme, ["scrollbar"]);
+        let hovered = BoxStyle::from_classes(theme, ["scrollbar:hover", "scrollbar"]);
+
+        for vertical in [true, false] {
+            if let Some((track_region, thumb_region)) = immediate.scrollbar(vertical) {
+                let thumb = if immediate.is_hovered(vertical) {
+                    &hovered
+                } else {
+                    &thumb
+                };
+                draw_box(backend.context(), &track, track_region)?;
+                draw_box(backend.context(), thumb, thumb_region)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A viewport onto a child that is larger than it.
+///
+/// The child is clipped to the viewport, and can be scrolled with the mouse wheel or by dragging
+/// the scrollbars.
+pub struct ScrollView<'a, B: crate::Backend> {
+    /// The content of the viewport.
+    child: BoxedWidget<'a, B>,
+
+    /// The axes that the content can scroll along.
+    axes: ScrollAxes,
+
+    /// The size of the content, from the last measurement.
+    content: Size,
+
+    /// The size of the viewport, from the last arrangement.
+    viewport: Size,
+
+    /// Routes events to the content.
+    router: Router,
+}
+
+impl<'a, B: crate::Backend + 'a> ScrollView<'a, B> {
+    /// Create a new viewport onto a child that scrolls vertically.
+    pub fn new(child: impl RenderedWidget<B> + 'a) -> Self {
+        Self {
+            child: BoxedWidget::new(child),
+            axes: ScrollAxes::default(),
+            content: Size::default(),
+            viewport: Size::default(),
+            router: Router::new(),
+        }
+    }
+
+    /// Set the axes that the content can scroll along.
+    pub fn with_axes(mut self, axes: ScrollAxes) -> Self {
+        self.axes = axes;
+        self
+    }
+
+    /// Scroll as little as possible to bring an area of the content into view.
+    ///
+    /// Returns `true` if the viewport needs to be redrawn.
+    pub fn scroll_to(&self, immediate: &mut ScrollState, target: Rectangle) -> bool {
+        immediate.set_sizes(self.viewport, self.content);
+        immediate.scroll_to(target)
+    }
+}
+
+/// A scroll view along with its scroll position, so that events can be routed through it.
+struct Routed<'s, 'a, B: crate::Backend> {
+    view: &'s mut ScrollView<'a, B>,
+    state: &'s mut ScrollState,
+}
+
+impl<B: crate::Backend> Container for Routed<'_, '_, B> {
+    fn child_count(&self) -> usize {
+        1
+    }
+
+    fn child_region(&self, _index: usize) -> Rectangle {
+        let (x, y) = self.state.offset();
+        Rectangle {
+            x: -x as i32,
+            y: -y as i32,
+            width: self.view.content.width,
+            height: self.view.content.height,
+        }
+    }
+
+    // Only the part of the child inside of the viewport is shown.
+    fn child_clip(&self, _index: usize) -> Rectangle {
+        Rectangle {
+            x: 0,
+            y: 0,
+            width: self.view.viewport.width,
+            height: self.view.viewport.height,
+        }
+    }
+
+    fn child_event(&mut self, _index: usize, event: Event) -> Damage {
+        self.view.child.handle_event(event)
+    }
+
+    fn child_captures_pointer(&self, _index: usize) -> bool {
+        self.view.child.captures_pointer()
+    }
+
+    fn child_is_focusable(&self, _index: usize) -> bool {
+        self.view.child.is_focusable()
+    }
+
+    fn child_steps_focus(&self, _index: usize, forwards: bool) -> bool {
+        self.view.child.steps_focus(forwards)
+    }
+
+    fn router(&mut self) -> &mut Router {
+        &mut self.view.router
+    }
+
+    // The scrollbars are drawn over the child, so they get the first look at the pointer.
+    fn capture(&mut self, event: &Event) -> Option<Damage> {
+        if self.state.handle_event(event) {
+            Some(Damage::Full)
+        } else {
+            None
+        }
+    }
+}
+
+impl<B: crate::Backend> Widget for ScrollView<'_, B> {
+    type Immediate<'a> = ScrollState;
+
+    fn handle_event(&mut self, immediate: &mut Self::Immediate<'_>, event: Event) -> Damage {
+        immediate.set_sizes(self.viewport, self.content);
+        tree::dispatch(
+            &mut Routed {
+                view: self,
+                state: immediate,
+            },
+            event,
+        )
+    }
+
+    fn is_focusable(&self) -> bool {
+        self.child.is_focusable()
+    }
+
+    fn steps_focus(&self, forwards: bool) -> bool {
+        self.child.steps_focus(forwards)
+    }
+
+    // Dragging the scrollbars has to keep working outside of the viewport.
+    fn captures_pointer(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(feature = "piet")]
+impl<'a, R: RenderContext> RenderedWidget<PietBackend<R>> for ScrollView<'a, PietBackend<R>> {
+    type Cache = ();
+
+    fn measure(
+        &mut self,
+        constraints: BoxConstraints,
+        _cache: &mut Self::Cache,
+        backend: &mut PietBackend<R>,
+    ) -> Result<Size, piet::Error> {
+        self.content = self
+            .child
+            .measure(self.axes.content_constraints(constraints), backend)?;
+        Ok(constraints.constrain(self.content))
+    }
+
+    fn arrange(
+        &mut self,
+        region: Rectangle,
+        _cache: &mut Self::Cache,
+        backend: &mut PietBackend<R>,
+    ) -> Result<(), piet::Error> {
+        self.viewport = Size {
+            width: region.width,
+            height: region.height,
+        };
+
+        self.child.arrange(
+            Rectangle {
+                x: 0,
+                y: 0,
+                width: self.content.width,
+                height: self.content.height,
+            },
+            backend,
+        )
+    }
+
+    fn render(
+        &self,
+        immediate: &Self::Immediate<'_>,
+        _cache: &mut Self::Cache,
+        backend: &mut PietBackend<R>,
+    ) -> Result<(), piet::Error> {
+        let mut state = immediate.clone();
+        state.set_sizes(self.viewport, self.content);
+        let (x, y) = state.offset();
+
+        let viewport = Rectangle {
+            x: 0,
+            y: 0,
+            width: self.viewport.width,
+            height: self.viewport.height,
+        };
+        let content = Rectangle {
+            x: -x as i32,
+            y: -y as i32,
+            width: self.content.width,
+            height: self.content.height,
+        };
+
+        backend.with_region(viewport, |backend| {
+            backend.with_region(content, |backend| self.child.render(backend))?;
+            Scrollbars.render(&state, &mut (), backend)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PointerButton, PointerEvent};
+
+    fn size(width: u32, height: u32) -> Size {
+        Size { width, height }
+    }
+
+    fn rect(x: i32, y: i32, width: u32, height: u32) -> Rectangle {
+        Rectangle {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn keeps_offset_in_range() {
+        let mut state = ScrollState::default();
+        state.set_sizes(size(100, 50), size(300, 200));
+
+        assert!(state.set_offset(500.0, -10.0));
+        assert_eq!(state.offset(), (200.0, 0.0));
+        assert!(!state.set_offset(250.0, 0.0));
+
+        assert!(state.scroll_by(-50.0, 400.0));
+        assert_eq!(state.offset(), (150.0, 150.0));
+
+        // Shrinking the content pulls the offset back with it.
+        state.set_sizes(size(100, 50), size(120, 40));
+        assert_eq!(state.offset(), (20.0, 0.0));
+    }
+
+    #[test]
+    fn scrolls_as_little_as_possible() {
+        let mut state = ScrollState::default();
+        state.set_sizes(size(100, 50), size(100, 500));
+
+        // Below the viewport, the bottom of the target lines up with the bottom of the viewport.
+        assert!(state.scroll_to(rect(0, 100, 20, 20)));
+        assert_eq!(state.offset(), (0.0, 70.0));
+
+        // Targets that can already be seen don't scroll.
+        assert!(!state.scroll_to(rect(0, 80, 20, 20)));
+
+        // Above the viewport, the tops line up.
+        assert!(state.scroll_to(rect(0, 10, 20, 20)));
+        assert_eq!(state.offset(), (0.0, 10.0));
+
+        // Targets taller than the viewport show their top.
+        assert!(state.scroll_to(rect(0, 200, 20, 80)));
+        assert_eq!(state.offset(), (0.0, 200.0));
+
+        // The offset still can't go past the end of the content.
+        state.scroll_to(rect(0, 490, 20, 40));
+        assert_eq!(state.offset(), (0.0, 450.0));
+    }
+
+    #[test]
+    fn sizes_scrollbars() {
+        let mut state = ScrollState::default();
+        state.set_sizes(size(100, 100), size(100, 400));
+        assert_eq!(state.scrollbar(false), None);
+
+        // The thumb is as much of the track as the viewport is of the content.
+        state.set_offset(0.0, 150.0);
+        let (track, thumb) = state.scrollbar(true).unwrap();
+        assert_eq!(track, rect(92, 0, 8, 100));
+        assert_eq!(thumb, rect(92, 37, 8, 25));
+
+        // With both scrollbars, they leave the corner free, and thumbs don't get too short.
+        state.set_sizes(size(100, 100), size(200, 10_000));
+        let (track, thumb) = state.scrollbar(true).unwrap();
+        assert_eq!(track, rect(92, 0, 8, 92));
+        assert_eq!(thumb.height, MIN_THUMB_LENGTH as u32);
+        let (track, _) = state.scrollbar(false).unwrap();
+        assert_eq!(track, rect(0, 92, 92, 8));
+    }
+
+    #[test]
+    fn hovers_thumbs() {
+        let mut state = ScrollState::default();
+        state.set_sizes(size(100, 100), size(100, 400));
+        let pointer = PointerEvent::new;
+
+        // The thumb starts out at rect(92, 0, 8, 25).
+        assert!(!state.handle_event(&Event::PointerMove(pointer(50.0, 10.0))));
+        assert!(state.handle_event(&Event::PointerMove(pointer(95.0, 10.0))));
+        assert!(state.is_hovered(true));
+        assert!(!state.is_hovered(false));
+        assert!(!state.handle_event(&Event::PointerMove(pointer(96.0, 12.0))));
+
+        // Dragging keeps the thumb hovered, even once the pointer strays off of it.
+        let mut press = pointer(95.0, 10.0);
+        press.button = Some(PointerButton::Primary);
+        assert!(state.handle_event(&Event::PointerDown(press)));
+        state.handle_event(&Event::PointerMove(pointer(50.0, 90.0)));
+        assert!(state.is_hovered(true));
+
+        // Letting go off of the thumb stops hovering it.
+        let mut release = pointer(50.0, 90.0);
+        release.button = Some(PointerButton::Primary);
+        assert!(state.handle_event(&Event::PointerUp(release)));
+        assert!(!state.is_hovered(true));
+
+        state.handle_event(&Event::PointerMove(pointer(95.0, 90.0)));
+        assert!(state.is_hovered(true));
+        assert!(state.handle_event(&Event::PointerLeave(pointer(101.0, 90.0))));
+        assert!(!state.is_hovered(true));
+    }
+
+    #[test]
+    fn lets_content_grow_along_scrolling_axes() {
+        let constraints = BoxConstraints::loose(size(100, 50));
+
+        let vertical = ScrollAxes::Vertical.content_constraints(constraints);
+        assert_eq!(vertical.max, size(100, u32::MAX));
+
+        let both = ScrollAxes::Both.content_constraints(constraints);
+        assert_eq!(both.max, size(u32::MAX, u32::MAX));
+    }
+}
@@ -0,0 +1,96 @@
+/*
+
+`sunder` is free software: you can redistribute it and/or modify it under the terms of one of
+the following licenses:
+
+- The GNU Affero General Public License as published by the Free Software Foundation, either version
+  3 of the License, or (at your option) any later version.
+- The Patron License at https://github.com/notgull/sunder/blob/main/LICENSE-PATRON.md, for
+  sponsors and contributors, who can ignore the copyleft provisions of the GNU AGPL for this project.
+
+`sunder` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+Public License and the Patron License for more details.
+
+You should have received a copy of the GNU Affero General Public License and the corresponding Patron
+License along with `sunder`. If not, see <https://www.gnu.org/licenses/>.
+
+*/
+
+//! Widgets and a backend for testing layout.
+
+use crate::{Backend, BoxConstraints, Damage, Event, Rectangle, RenderedWidget, Size, Widget};
+use core::convert::Infallible;
+
+/// A backend that doesn't draw anything.
+pub(crate) struct NullBackend;
+
+impl Backend for NullBackend {
+    type Error = Infallible;
+    type Output = ();
+
+    fn with_region<R>(
+        &mut self,
+        _region: Rectangle,
+        f: impl FnOnce(&mut Self) -> Result<R, Self::Error>,
+    ) -> Result<R, Self::Error> {
+        f(self)
+    }
+}
+
+/// A widget that wants to be a certain size, but takes whatever it is given.
+pub(crate) struct Fixed {
+    /// The size that the widget wants.
+    size: Size,
+
+    /// The distance from the top of the widget to its baseline.
+    baseline: Option<u32>,
+}
+
+impl Fixed {
+    pub(crate) fn new(width: u32, height: u32) -> Self {
+        Self {
+            size: Size { width, height },
+            baseline: None,
+        }
+    }
+
+    pub(crate) fn with_baseline(mut self, baseline: u32) -> Self {
+        self.baseline = Some(baseline);
+        self
+    }
+}
+
+impl Widget for Fixed {
+    type Immediate<'a> = ();
+
+    fn handle_event(&mut self, _immediate: &mut Self::Immediate<'_>, _event: Event) -> Damage {
+        Damage::None
+    }
+}
+
+impl RenderedWidget<NullBackend> for Fixed {
+    type Cache = ();
+
+    fn measure(
+        &mut self,
+        constraints: BoxConstraints,
+        _cache: &mut Self::Cache,
+        _backend: &mut NullBackend,
+    ) -> Result<Size, Infallible> {
+        Ok(constraints.constrain(self.size))
+    }
+
+    fn baseline(&self, _cache: &Self::Cache) -> Option<u32> {
+        self.baseline
+    }
+
+    fn render(
+        &self,
+        _immediate: &Self::Immediate<'_>,
+        _cache: &mut Self::Cache,
+        _backend: &mut NullBackend,
+    ) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
@@ -0,0 +1,1278 @@
+/*
+
+`sunder` is free software: you can redistribute it and/or modify it under the terms of one of
+the following licenses:
+
+- The GNU Affero General Public License as published by the Free Software Foundation, either version
+  3 of the License, or (at your option) any later version.
+- The Patron License at https://github.com/notgull/sunder/blob/main/LICENSE-PATRON.md, for
+  sponsors and contributors, who can ignore the copyleft provisions of the GNU AGPL for this project.
+
+`sunder` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+Public License and the Patron License for more details.
+
+You should have received a copy of the GNU Affero General Public License and the corresponding Patron
+License along with `sunder`. If not, see <https://www.gnu.org/licenses/>.
+
+*/
+
+//! Multiple lines of editable text, with soft wrapping and scrolling.
+
+use super::editing::{floor_boundary, next_char, next_word, previous_char, previous_word};
+use super::scroll_view::ScrollState;
+use crate::{Damage, Event, Key, KeyEvent, Modifiers, PointerKind, Size, Widget};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+cfg_piet! {
+    use super::scroll_view::{Scrollbars, SCROLLBAR_WIDTH};
+    use super::text_input::TextStyles;
+    use crate::piet::{draw_box, BoxStyle, PietBackend, Sides};
+    use crate::{Backend, BoxConstraints, Rectangle, RenderedWidget};
+    use alloc::vec;
+    use piet::kurbo::{Point, Rect};
+    use piet::{LineMetric, RenderContext, Text, TextLayout, TextLayoutBuilder};
+
+    /// The space between the edge of the area and its text.
+    const PADDING: u32 = 4;
+
+    /// The size of an area that isn't given a size by its parent.
+    const DEFAULT_SIZE: Size = Size {
+        width: 300,
+        height: 200,
+    };
+}
+
+/// A position in the text of a [`TextArea`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TextPosition {
+    /// The index of the line, counting from zero.
+    pub line: usize,
+
+    /// The byte offset into the line.
+    pub offset: usize,
+}
+
+/// Multiple lines of editable text.
+///
+/// Lines are wrapped to the width of the area, and the text scrolls up and down when it is too
+/// tall to fit. Editing works like [`TextInput`], except that enter starts a new line, and the up
+/// and down arrow keys move between lines. Control+Home and Control+End move to the start and end
+/// of the text.
+///
+/// Each line of the text is laid out separately, so that editing a line only lays out that line
+/// again, no matter how long the rest of the text is.
+///
+/// The box around the text is styled by the `text_area` theme class, or `text_area:focus` while
+/// the area has focus. Its border is drawn over the text and the scrollbar. The selection and the
+/// caret are styled like those of a [`TextInput`].
+///
+/// [`TextInput`]: super::TextInput
+pub struct TextArea {
+    /// The lines of the text, without their line breaks.
+    lines: Vec<Line>,
+
+    /// The revision to give the next line that is edited.
+    #[cfg(feature = "piet")]
+    next_revision: u64,
+
+    /// Incremented every time the lines change.
+    edits: u64,
+
+    /// The position of the caret.
+    caret: TextPosition,
+
+    /// The end of the selection that doesn't move with the caret.
+    ///
+    /// Nothing is selected if this is the same as the caret.
+    anchor: TextPosition,
+
+    /// The horizontal position to keep the caret at while moving up and down.
+    goal_x: Option<f64>,
+
+    /// Caret movements that can't be worked out until the text is laid out, in the order they
+    /// happened.
+    ///
+    /// Input that moves the caret or edits the text waits behind them, so that it applies to
+    /// wherever the caret ends up.
+    #[cfg(feature = "piet")]
+    pending: Vec<Pending>,
+
+    /// Whether the caret should be scrolled into view the next time the area is arranged.
+    reveal: bool,
+
+    /// The pointer that is selecting text, if any.
+    dragging: Option<PointerKind>,
+
+    /// The scroll position and scrollbar state.
+    scroll: ScrollState,
+
+    /// The size that the area was last arranged at.
+    size: Size,
+}
+
+/// A line of a [`TextArea`].
+struct Line {
+    /// The text of the line.
+    text: String,
+
+    /// Changes every time the line is edited, so that its layout can be cached.
+    #[cfg(feature = "piet")]
+    revision: u64,
+}
+
+/// Input waiting for the text to be laid out.
+#[cfg(feature = "piet")]
+#[derive(Debug, Clone)]
+enum Pending {
+    /// Move the caret to a point, relative to the area.
+    Pointer { x: f64, y: f64, extend: bool },
+
+    /// Move the caret up or down by a number of wrapped lines.
+    Rows { rows: isize, extend: bool },
+
+    /// A key that was pressed after an earlier movement.
+    Key { key: Key, modifiers: Modifiers },
+
+    /// Text that was typed after an earlier movement.
+    Text(String),
+}
+
+/// The immediate state of a [`TextArea`].
+#[derive(Default)]
+pub struct TextAreaState {
+    /// Whether the area has keyboard focus.
+    focused: bool,
+
+    /// Whether the text has been edited since this was last checked.
+    changed: bool,
+}
+
+impl TextAreaState {
+    /// Whether the area has keyboard focus.
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    /// Check whether the text has been edited, resetting the flag.
+    pub fn take_changed(&mut self) -> bool {
+        core::mem::take(&mut self.changed)
+    }
+}
+
+cfg_piet! {
+    pub struct PietCache<R: RenderContext + ?Sized> {
+        /// The layout of each line, in order.
+        layouts: Vec<R::TextLayout>,
+
+        /// The revision of each line when it was laid out.
+        revisions: Vec<u64>,
+
+        /// The top of each line relative to the text, followed by the bottom of the last line.
+        tops: Vec<f64>,
+
+        /// The edit count of the area when the layouts were last brought up to date.
+        edits: Option<u64>,
+
+        /// The width that lines are wrapped at.
+        width: f64,
+    }
+
+    impl<R: RenderContext + ?Sized> Default for PietCache<R> {
+        fn default() -> Self {
+            Self {
+                layouts: Vec::new(),
+                revisions: Vec::new(),
+                tops: vec![0.0],
+                edits: None,
+                width: 0.0,
+            }
+        }
+    }
+
+    impl<R: RenderContext + ?Sized> PietCache<R> {
+        /// Lay out any lines that have changed since the last time this was called.
+        fn populate(
+            &mut self,
+            area: &TextArea,
+            ctx: &mut R,
+            width: f64,
+        ) -> Result<(), piet::Error> {
+            use alloc::string::ToString;
+
+            // Everything has to be wrapped again if the width changes.
+            if width != self.width {
+                *self = Self {
+                    width,
+                    ..Self::default()
+                };
+            }
+
+            if self.edits == Some(area.edits) {
+                return Ok(());
+            }
+
+            // Edits replace a run of lines, so the lines on either side of it keep their
+            // revisions. Only the lines in between have to be laid out.
+            let unchanged = |(revision, line): (&u64, &Line)| *revision == line.revision;
+            let prefix = self
+                .revisions
+                .iter()
+                .zip(&area.lines)
+                .take_while(|&pair| unchanged(pair))
+                .count();
+            let suffix = self.revisions[prefix..]
+                .iter()
+                .rev()
+                .zip(area.lines[prefix..].iter().rev())
+                .take_while(|&pair| unchanged(pair))
+                .count();
+            let old = prefix..self.revisions.len() - suffix;
+            let new = prefix..area.lines.len() - suffix;
+
+            let mut layouts = Vec::with_capacity(new.len());
+            let mut tops = Vec::with_capacity(new.len());
+            let mut top = self.tops[prefix];
+            for line in &area.lines[new.clone()] {
+                let layout = ctx
+                    .text()
+                    .new_text_layout(line.text.to_string())
+                    .max_width(width)
+                    .build()?;
+
+                tops.push(top);
+                top += layout.size().height;
+                layouts.push(layout);
+            }
+
+            // Move the lines after the edit to make room for it.
+            let shift = top - self.tops[old.end];
+            self.layouts.splice(old.clone(), layouts);
+            self.revisions.splice(
+                old.clone(),
+                area.lines[new.clone()].iter().map(|line| line.revision),
+            );
+            self.tops.splice(old, tops);
+            for top in &mut self.tops[new.end..] {
+                *top += shift;
+            }
+
+            self.edits = Some(area.edits);
+            Ok(())
+        }
+
+        /// Get the layout of a line.
+        fn layout(&self, line: usize) -> &R::TextLayout {
+            &self.layouts[line]
+        }
+
+        /// Get the height of all of the text.
+        fn height(&self) -> f64 {
+            self.tops.last().copied().unwrap_or(0.0)
+        }
+
+        /// Get the line at a height, relative to the text.
+        fn line_at(&self, y: f64) -> usize {
+            let lines = self.revisions.len();
+            self.tops[..lines]
+                .partition_point(|&top| top <= y)
+                .saturating_sub(1)
+        }
+
+        /// Get the wrapped line that a position is on, along with its index in its line's layout.
+        fn row(&self, position: TextPosition) -> (usize, LineMetric) {
+            let layout = self.layout(position.line);
+            let row = layout.hit_test_text_position(position.offset).line;
+            (row, layout.line_metric(row).unwrap_or_default())
+        }
+    }
+}
+
+impl TextArea {
+    /// Create a new text area.
+    ///
+    /// The caret starts at the end of the text.
+    pub fn new(text: impl AsRef<str>) -> Self {
+        let mut area = Self {
+            lines: Vec::new(),
+            #[cfg(feature = "piet")]
+            next_revision: 0,
+            edits: 0,
+            caret: TextPosition::default(),
+            anchor: TextPosition::default(),
+            goal_x: None,
+            #[cfg(feature = "piet")]
+            pending: Vec::new(),
+            reveal: false,
+            dragging: None,
+            scroll: ScrollState::default(),
+            size: Size::default(),
+        };
+
+        area.set_text(text);
+        area
+    }
+
+    /// Get the text, with its lines joined by line breaks.
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        for (i, line) in self.lines.iter().enumerate() {
+            if i > 0 {
+                text.push('\n');
+            }
+            text.push_str(&line.text);
+        }
+        text
+    }
+
+    /// Replace the text, moving the caret to the end of it.
+    pub fn set_text(&mut self, text: impl AsRef<str>) {
+        self.lines.clear();
+        self.caret = TextPosition::default();
+        self.anchor = self.caret;
+        let line = self.new_line(String::new());
+        self.lines.push(line);
+
+        self.replace(self.caret..self.caret, text.as_ref());
+    }
+
+    /// Get the number of lines in the text.
+    ///
+    /// This counts line breaks, not lines that have been wrapped.
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Get a line of the text.
+    pub fn line(&self, line: usize) -> Option<&str> {
+        self.lines.get(line).map(|line| line.text.as_str())
+    }
+
+    /// Get the position of the caret.
+    pub fn caret(&self) -> TextPosition {
+        self.caret
+    }
+
+    /// Get the line and column of the caret, counting characters from zero.
+    pub fn line_column(&self) -> (usize, usize) {
+        let line = &self.lines[self.caret.line].text;
+        (self.caret.line, line[..self.caret.offset].chars().count())
+    }
+
+    /// Get the selected range of the text.
+    ///
+    /// The range is empty if nothing is selected.
+    pub fn selection(&self) -> Range<TextPosition> {
+        self.caret.min(self.anchor)..self.caret.max(self.anchor)
+    }
+
+    /// Get the selected text.
+    pub fn selected_text(&self) -> String {
+        let Range { start, end } = self.selection();
+        if start.line == end.line {
+            return self.lines[start.line].text[start.offset..end.offset].into();
+        }
+
+        let mut text = String::from(&self.lines[start.line].text[start.offset..]);
+        for line in &self.lines[start.line + 1..end.line] {
+            text.push('\n');
+            text.push_str(&line.text);
+        }
+        text.push('\n');
+        text.push_str(&self.lines[end.line].text[..end.offset]);
+        text
+    }
+
+    /// Select a range of the text, placing the caret at its end.
+    ///
+    /// The range is clamped to the text, and moved back onto character boundaries.
+    pub fn set_selection(&mut self, range: Range<TextPosition>) {
+        self.anchor = self.clamp(range.start);
+        self.caret = self.clamp(range.end);
+        self.goal_x = None;
+        self.reveal = true;
+    }
+
+    /// Select all of the text.
+    pub fn select_all(&mut self) {
+        self.set_selection(TextPosition::default()..self.end());
+    }
+
+    /// Get the position at the end of the text.
+    fn end(&self) -> TextPosition {
+        let line = self.lines.len() - 1;
+        TextPosition {
+            line,
+            offset: self.lines[line].text.len(),
+        }
+    }
+
+    /// Move a position onto the nearest character boundary in the text.
+    fn clamp(&self, position: TextPosition) -> TextPosition {
+        let line = position.line.min(self.lines.len() - 1);
+        TextPosition {
+            line,
+            offset: floor_boundary(&self.lines[line].text, position.offset),
+        }
+    }
+
+    /// Create a line with a new revision.
+    fn new_line(&mut self, text: String) -> Line {
+        #[cfg(feature = "piet")]
+        {
+            let revision = self.next_revision;
+            self.next_revision += 1;
+            Line { text, revision }
+        }
+
+        #[cfg(not(feature = "piet"))]
+        Line { text }
+    }
+
+    /// Move the caret to a point relative to the area, once the text has been laid out.
+    ///
+    /// Without a backend that lays out text, the caret can only be moved with the keyboard.
+    fn point_at(&mut self, x: f64, y: f64, extend: bool) {
+        #[cfg(feature = "piet")]
+        {
+            // Only the latest position of a drag matters.
+            if let (true, Some(Pending::Pointer { extend: true, .. })) =
+                (extend, self.pending.last())
+            {
+                self.pending.pop();
+            }
+
+            self.pending.push(Pending::Pointer { x, y, extend });
+        }
+
+        #[cfg(not(feature = "piet"))]
+        let _ = (x, y, extend);
+    }
+
+    /// Move the caret up or down by a number of wrapped lines, once the text has been laid out.
+    fn move_rows(&mut self, rows: isize, extend: bool) {
+        #[cfg(feature = "piet")]
+        {
+            self.pending.push(Pending::Rows { rows, extend });
+        }
+
+        #[cfg(not(feature = "piet"))]
+        let _ = (rows, extend);
+    }
+
+    /// Whether the caret is waiting for the text to be laid out.
+    fn is_pending(&self) -> bool {
+        #[cfg(feature = "piet")]
+        return !self.pending.is_empty();
+
+        #[cfg(not(feature = "piet"))]
+        false
+    }
+
+    /// Hold a key press back until the caret has finished moving, if it is still moving.
+    ///
+    /// Returns `true` if the key was held back.
+    fn defer_key(&mut self, key: Key, modifiers: Modifiers) -> bool {
+        #[cfg(feature = "piet")]
+        if self.is_pending() {
+            self.pending.push(Pending::Key { key, modifiers });
+            return true;
+        }
+
+        let _ = (key, modifiers);
+        false
+    }
+
+    /// Hold typed text back until the caret has finished moving, if it is still moving.
+    ///
+    /// Returns the text if it wasn't held back.
+    fn defer_text(&mut self, text: String) -> Option<String> {
+        #[cfg(feature = "piet")]
+        if self.is_pending() {
+            self.pending.push(Pending::Text(text));
+            return None;
+        }
+
+        Some(text)
+    }
+
+    /// Replace the selection with typed text.
+    fn type_text(&mut self, text: &str) {
+        let selection = self.selection();
+        self.replace(selection, text);
+    }
+
+    /// Replace a range of the text, leaving the caret after the new text.
+    fn replace(&mut self, range: Range<TextPosition>, text: &str) {
+        let Range { start, end } = range;
+        let head = &self.lines[start.line].text[..start.offset];
+        let tail = &self.lines[end.line].text[end.offset..];
+
+        // Build the new lines, and note where the caret goes.
+        let mut pieces = text
+            .split('\n')
+            .map(|piece| piece.strip_suffix('\r').unwrap_or(piece));
+        let mut texts = Vec::new();
+        let mut current = String::from(head);
+        current.push_str(pieces.next().unwrap_or(""));
+        for piece in pieces {
+            texts.push(core::mem::replace(&mut current, String::from(piece)));
+        }
+
+        self.caret = TextPosition {
+            line: start.line + texts.len(),
+            offset: current.len(),
+        };
+        current.push_str(tail);
+        texts.push(current);
+
+        let lines: Vec<_> = texts.into_iter().map(|text| self.new_line(text)).collect();
+        self.lines.splice(start.line..=end.line, lines);
+
+        self.anchor = self.caret;
+        self.goal_x = None;
+        self.reveal = true;
+        self.edits += 1;
+    }
+
+    /// Move the caret, extending the selection or collapsing it.
+    fn move_caret(&mut self, position: TextPosition, extend: bool) {
+        self.caret = position;
+        if !extend {
+            self.anchor = position;
+        }
+
+        self.goal_x = None;
+        self.reveal = true;
+    }
+
+    /// Get the position after the character after a position.
+    fn next_position(&self, position: TextPosition, by_word: bool) -> TextPosition {
+        let text = &self.lines[position.line].text;
+
+        // Moving forwards from the end of a line goes to the start of the next one.
+        if position.offset == text.len() {
+            return TextPosition {
+                line: (position.line + 1).min(self.lines.len() - 1),
+                offset: if position.line + 1 < self.lines.len() {
+                    0
+                } else {
+                    position.offset
+                },
+            };
+        }
+
+        let offset = if by_word {
+            next_word(text, position.offset)
+        } else {
+            next_char(text, position.offset)
+        };
+
+        TextPosition { offset, ..position }
+    }
+
+    /// Get the position before the character before a position.
+    fn previous_position(&self, position: TextPosition, by_word: bool) -> TextPosition {
+        // Moving backwards from the start of a line goes to the end of the previous one.
+        if position.offset == 0 {
+            return match position.line.checked_sub(1) {
+                Some(line) => TextPosition {
+                    line,
+                    offset: self.lines[line].text.len(),
+                },
+                None => position,
+            };
+        }
+
+        let text = &self.lines[position.line].text;
+        let offset = if by_word {
+            previous_word(text, position.offset)
+        } else {
+            previous_char(text, position.offset)
+        };
+
+        TextPosition { offset, ..position }
+    }
+
+    /// Delete the selection, or the text between the caret and another position if nothing is
+    /// selected.
+    ///
+    /// Returns `true` if anything was deleted.
+    fn delete_towards(&mut self, position: TextPosition) -> bool {
+        if self.caret == self.anchor {
+            self.anchor = position;
+        }
+
+        let selection = self.selection();
+        if selection.is_empty() {
+            return false;
+        }
+
+        self.replace(selection, "");
+        true
+    }
+
+    /// Reply to a key being pressed.
+    ///
+    /// Returns `true` if the text was edited.
+    fn key_down(&mut self, key: Key, modifiers: Modifiers) -> bool {
+        let extend = modifiers.shift;
+        let by_word = modifiers.control || modifiers.alt;
+        let shortcut = modifiers.control || modifiers.logo;
+        let selection = self.selection();
+
+        // Moving without shift collapses the selection to the side being moved towards.
+        let collapse = !extend && !selection.is_empty();
+
+        match key {
+            Key::Left if collapse => self.move_caret(selection.start, false),
+            Key::Right if collapse => self.move_caret(selection.end, false),
+            Key::Left => self.move_caret(self.previous_position(self.caret, by_word), extend),
+            Key::Right => self.move_caret(self.next_position(self.caret, by_word), extend),
+
+            // Moving between wrapped lines needs the layout.
+            Key::Up => self.move_rows(-1, extend),
+            Key::Down => self.move_rows(1, extend),
+
+            Key::Home if shortcut => self.move_caret(TextPosition::default(), extend),
+            Key::End if shortcut => self.move_caret(self.end(), extend),
+            Key::Home => self.move_caret(
+                TextPosition {
+                    offset: 0,
+                    ..self.caret
+                },
+                extend,
+            ),
+            Key::End => {
+                let offset = self.lines[self.caret.line].text.len();
+                self.move_caret(
+                    TextPosition {
+                        offset,
+                        ..self.caret
+                    },
+                    extend,
+                )
+            }
+
+            Key::Character('a' | 'A') if shortcut => self.select_all(),
+
+            Key::Backspace => {
+                let target = self.previous_position(self.caret, by_word);
+                return self.delete_towards(target);
+            }
+
+            Key::Delete => {
+                let target = self.next_position(self.caret, by_word);
+                return self.delete_towards(target);
+            }
+
+            Key::Enter => {
+                self.replace(selection, "\n");
+                return true;
+            }
+
+            _ => {}
+        }
+
+        false
+    }
+
+    /// Move the caret now that the text has been laid out.
+    #[cfg(feature = "piet")]
+    fn resolve<R: RenderContext + ?Sized>(&mut self, pending: Pending, cache: &PietCache<R>) {
+        match pending {
+            Pending::Pointer { x, y, extend } => {
+                let (_, offset_y) = self.scroll.offset();
+                let y = y + offset_y - PADDING as f64;
+                let line = cache.line_at(y);
+                let point = Point::new(x - PADDING as f64, y - cache.tops[line]);
+                let offset = cache.layout(line).hit_test_point(point).idx;
+
+                let position = self.clamp(TextPosition { line, offset });
+                self.move_caret(position, extend);
+            }
+
+            Pending::Rows { rows, extend } => {
+                let layout = cache.layout(self.caret.line);
+                let x = self
+                    .goal_x
+                    .unwrap_or_else(|| layout.hit_test_text_position(self.caret.offset).point.x);
+
+                // Step through the wrapped lines, crossing into other lines at the ends.
+                let (mut line, (mut row, _)) = (self.caret.line, cache.row(self.caret));
+                let mut past_end = false;
+                for _ in 0..rows.unsigned_abs() {
+                    if rows < 0 {
+                        if row > 0 {
+                            row -= 1;
+                        } else if line > 0 {
+                            line -= 1;
+                            row = cache.layout(line).line_count().saturating_sub(1);
+                        } else {
+                            past_end = true;
+                        }
+                    } else if row + 1 < cache.layout(line).line_count() {
+                        row += 1;
+                    } else if line + 1 < self.lines.len() {
+                        line += 1;
+                        row = 0;
+                    } else {
+                        past_end = true;
+                    }
+                }
+
+                // Moving past the first or last line goes to the start or end of the text.
+                let position = if past_end && rows < 0 {
+                    TextPosition::default()
+                } else if past_end {
+                    self.end()
+                } else {
+                    let layout = cache.layout(line);
+                    let metric = layout.line_metric(row).unwrap_or_default();
+                    let point = Point::new(x, metric.y_offset + metric.height / 2.0);
+                    let offset = layout.hit_test_point(point).idx;
+                    self.clamp(TextPosition { line, offset })
+                };
+
+                self.move_caret(position, extend);
+                self.goal_x = Some(x);
+            }
+
+            Pending::Key { key, modifiers } => {
+                self.key_down(key, modifiers);
+            }
+
+            Pending::Text(text) => self.type_text(&text),
+        }
+    }
+
+    /// Whether a point is inside of the area.
+    fn contains(&self, x: f64, y: f64) -> bool {
+        x >= 0.0 && y >= 0.0 && x < self.size.width as f64 && y < self.size.height as f64
+    }
+}
+
+impl Widget for TextArea {
+    type Immediate<'a> = TextAreaState;
+
+    fn handle_event(&mut self, immediate: &mut Self::Immediate<'_>, event: Event) -> Damage {
+        match event {
+            Event::PointerDown(pointer)
+                if pointer.is_primary() && self.contains(pointer.x, pointer.y) =>
+            {
+                // Pressing on a scrollbar doesn't move the caret.
+                if !self.scroll.handle_event(&event) {
+                    self.point_at(pointer.x, pointer.y, pointer.modifiers.shift);
+                    self.dragging = Some(pointer.kind);
+                }
+
+                Damage::Full
+            }
+
+            Event::PointerMove(pointer) if self.dragging == Some(pointer.kind) => {
+                self.point_at(pointer.x, pointer.y, true);
+                Damage::Full
+            }
+
+            Event::PointerUp(pointer) | Event::PointerCancel(pointer) => {
+                // Only letting go of the pointer that is selecting stops the selection.
+                let released = pointer.is_primary() || matches!(event, Event::PointerCancel(_));
+                if released && self.dragging == Some(pointer.kind) {
+                    self.dragging = None;
+                }
+
+                self.scroll.handle_event(&event).into()
+            }
+
+            Event::PointerMove(_) | Event::Scroll { .. } => self.scroll.handle_event(&event).into(),
+
+            Event::KeyDown(KeyEvent { key, modifiers, .. }) => {
+                // Moving up or down is queued by itself, like any other movement that needs the
+                // layout.
+                if !matches!(key, Key::Up | Key::Down) && self.defer_key(key, modifiers) {
+                    immediate.changed |= matches!(key, Key::Backspace | Key::Delete | Key::Enter);
+                    return Damage::Full;
+                }
+
+                let before = (self.caret, self.anchor);
+                let edited = self.key_down(key, modifiers);
+                immediate.changed |= edited;
+
+                let moved = self.is_pending() || (self.caret, self.anchor) != before;
+                Damage::from(edited || moved)
+            }
+
+            Event::Text(text) => {
+                let text = text.replace('\r', "");
+                if text.is_empty() {
+                    return Damage::None;
+                }
+
+                if let Some(text) = self.defer_text(text) {
+                    self.type_text(&text);
+                }
+
+                immediate.changed = true;
+                Damage::Full
+            }
+
+            Event::FocusIn => {
+                immediate.focused = true;
+                Damage::Full
+            }
+
+            Event::FocusOut => {
+                immediate.focused = false;
+                self.dragging = None;
+                Damage::Full
+            }
+
+            _ => Damage::None,
+        }
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn captures_pointer(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(feature = "piet")]
+impl<R: RenderContext> RenderedWidget<PietBackend<R>> for TextArea {
+    type Cache = PietCache<R>;
+
+    fn measure(
+        &mut self,
+        constraints: BoxConstraints,
+        _cache: &mut Self::Cache,
+        _backend: &mut PietBackend<R>,
+    ) -> Result<Size, <PietBackend<R> as Backend>::Error> {
+        // Take up all of the space we are offered, since the text scrolls.
+        let width = if constraints.has_bounded_width() {
+            constraints.max.width
+        } else {
+            DEFAULT_SIZE.width
+        };
+        let height = if constraints.max.height != u32::MAX {
+            constraints.max.height
+        } else {
+            DEFAULT_SIZE.height
+        };
+
+        Ok(constraints.constrain(Size { width, height }))
+    }
+
+    fn arrange(
+        &mut self,
+        region: Rectangle,
+        cache: &mut Self::Cache,
+        backend: &mut PietBackend<R>,
+    ) -> Result<(), piet::Error> {
+        self.size = Size {
+            width: region.width,
+            height: region.height,
+        };
+
+        // Always leave room for the scrollbar, so that the text doesn't have to be wrapped again
+        // when it appears.
+        let width = region.width.saturating_sub(PADDING * 2 + SCROLLBAR_WIDTH) as f64;
+        cache.populate(self, backend.context(), width)?;
+
+        // Now that we have the layout, work out where the caret has moved to.
+        for pending in core::mem::take(&mut self.pending) {
+            self.resolve(pending, cache);
+
+            // Edited text has to be laid out again before the caret can move through it.
+            cache.populate(self, backend.context(), width)?;
+        }
+
+        let content = Size {
+            width: region.width,
+            height: cache.height().ceil() as u32 + PADDING * 2,
+        };
+        self.scroll.set_sizes(self.size, content);
+
+        // Scroll as little as possible to keep the caret in view.
+        if core::mem::take(&mut self.reveal) {
+            let (_, metric) = cache.row(self.caret);
+            let x = cache
+                .layout(self.caret.line)
+                .hit_test_text_position(self.caret.offset)
+                .point
+                .x;
+            let y = cache.tops[self.caret.line] + metric.y_offset;
+
+            self.scroll.scroll_to(Rectangle {
+                x: x as i32,
+                y: y as i32,
+                width: PADDING * 2,
+                height: metric.height.ceil() as u32 + PADDING * 2,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn render(
+        &self,
+        immediate: &Self::Immediate<'_>,
+        cache: &mut Self::Cache,
+        backend: &mut PietBackend<R>,
+    ) -> Result<(), piet::Error> {
+        let styles = TextStyles::new(backend.theme(), "text_area", immediate.focused);
+
+        // Draw the inside of the box around the text, leaving its border until last.
+        let frame = Rectangle {
+            x: 0,
+            y: 0,
+            width: self.size.width,
+            height: self.size.height,
+        };
+        let inside = BoxStyle {
+            border: Sides::default(),
+            ..styles.frame.clone()
+        };
+        draw_box(backend.context(), &inside, frame)?;
+
+        // Only draw the lines that can be seen.
+        let rc = backend.context();
+        let (width, height) = (self.size.width as f64, self.size.height as f64);
+        rc.save()?;
+        rc.clip(Rect::new(0.0, 0.0, width, height));
+        let (_, offset_y) = self.scroll.offset();
+        let origin = Point::new(PADDING as f64, PADDING as f64 - offset_y);
+        let first = cache.line_at(offset_y - PADDING as f64);
+        let selection = self.selection();
+
+        for line in first..self.lines.len() {
+            let top = origin.y + cache.tops[line];
+            if top > height {
+                break;
+            }
+
+            let layout = cache.layout(line);
+            let line_origin = Point::new(origin.x, top);
+
+            // Highlight the selection behind the text.
+            let selected =
+                !selection.is_empty() && selection.start.line <= line && line <= selection.end.line;
+            if let Some(highlight) = styles.selection.as_ref().filter(|_| selected) {
+                let start = if line == selection.start.line {
+                    selection.start.offset
+                } else {
+                    0
+                };
+                let end = if line == selection.end.line {
+                    selection.end.offset
+                } else {
+                    layout.text().len()
+                };
+
+                for rect in layout.rects_for_range(start..end) {
+                    rc.fill(rect + line_origin.to_vec2(), highlight);
+                }
+
+                // Show that the line break is selected too.
+                if line < selection.end.line {
+                    let (_, metric) = cache.row(TextPosition { line, offset: end });
+                    let x = layout.hit_test_text_position(end).point.x;
+                    let rect = Rect::new(
+                        x,
+                        metric.y_offset,
+                        x + PADDING as f64,
+                        metric.y_offset + metric.height,
+                    );
+                    rc.fill(rect + line_origin.to_vec2(), highlight);
+                }
+            }
+
+            rc.draw_text(layout, line_origin);
+        }
+
+        match &styles.caret {
+            Some(caret) if immediate.focused => {
+                let (_, metric) = cache.row(self.caret);
+                let x = origin.x
+                    + cache
+                        .layout(self.caret.line)
+                        .hit_test_text_position(self.caret.offset)
+                        .point
+                        .x;
+                let top = origin.y + cache.tops[self.caret.line] + metric.y_offset;
+                rc.fill(Rect::new(x - 0.5, top, x + 0.5, top + metric.height), caret);
+            }
+            _ => {}
+        }
+
+        rc.restore()?;
+
+        // Draw the scrollbar and the border over the text.
+        Scrollbars.render(&self.scroll, &mut (), backend)?;
+
+        let border = BoxStyle {
+            background: None,
+            shadow: None,
+            ..styles.frame
+        };
+        draw_box(backend.context(), &border, frame)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[cfg(feature = "piet")]
+    use crate::piet::testing::RecordingContext;
+    #[cfg(feature = "piet")]
+    use crate::{PointerButton, PointerEvent};
+    #[cfg(feature = "piet")]
+    use alloc::rc::Rc;
+
+    const NONE: Modifiers = Modifiers {
+        shift: false,
+        control: false,
+        alt: false,
+        logo: false,
+    };
+
+    const SHIFT: Modifiers = Modifiers {
+        shift: true,
+        ..NONE
+    };
+
+    const CONTROL: Modifiers = Modifiers {
+        control: true,
+        ..NONE
+    };
+
+    fn at(line: usize, offset: usize) -> TextPosition {
+        TextPosition { line, offset }
+    }
+
+    fn key(area: &mut TextArea, state: &mut TextAreaState, key: Key, modifiers: Modifiers) {
+        area.handle_event(state, Event::KeyDown(KeyEvent::new(key, modifiers)));
+    }
+
+    fn type_text(area: &mut TextArea, state: &mut TextAreaState, text: &str) {
+        area.handle_event(state, Event::Text(text.to_string()));
+    }
+
+    /// Lays out text areas with room for ten characters on each line.
+    #[cfg(feature = "piet")]
+    struct Layout {
+        cache: PietCache<RecordingContext>,
+        backend: PietBackend<RecordingContext>,
+    }
+
+    #[cfg(feature = "piet")]
+    impl Layout {
+        fn new() -> Self {
+            Self {
+                cache: PietCache::default(),
+                backend: PietBackend::new(RecordingContext::new(), Rc::default()),
+            }
+        }
+
+        fn arrange(&mut self, area: &mut TextArea) {
+            let region = Rectangle {
+                x: 0,
+                y: 0,
+                width: 100 + PADDING * 2 + SCROLLBAR_WIDTH,
+                height: 100,
+            };
+
+            area.arrange(region, &mut self.cache, &mut self.backend)
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn inserts_lines() {
+        let mut area = TextArea::new("first\r\nlast");
+        let mut state = TextAreaState::default();
+        assert_eq!(area.line_count(), 2);
+        assert_eq!(area.line(1), Some("last"));
+
+        area.set_selection(at(0, 2)..at(1, 2));
+        type_text(&mut area, &mut state, "1\n2\n3");
+        assert_eq!(area.text(), "fi1\n2\n3st");
+        assert_eq!(area.caret(), at(2, 1));
+        assert!(state.take_changed());
+
+        key(&mut area, &mut state, Key::Enter, NONE);
+        assert_eq!(area.text(), "fi1\n2\n3\nst");
+        assert_eq!(area.caret(), at(3, 0));
+    }
+
+    #[test]
+    fn deletes_across_lines() {
+        let mut area = TextArea::new("one\ntwo words");
+        let mut state = TextAreaState::default();
+
+        key(&mut area, &mut state, Key::Backspace, CONTROL);
+        assert_eq!(area.text(), "one\ntwo ");
+
+        // Deleting at the start of a line joins it to the line before.
+        key(&mut area, &mut state, Key::Home, NONE);
+        key(&mut area, &mut state, Key::Backspace, NONE);
+        assert_eq!(area.text(), "onetwo ");
+        assert_eq!(area.caret(), at(0, 3));
+
+        key(&mut area, &mut state, Key::Delete, CONTROL);
+        assert_eq!(area.text(), "one ");
+        assert!(state.take_changed());
+    }
+
+    #[test]
+    fn moves_by_word_across_lines() {
+        let mut area = TextArea::new("alpha beta\ngamma");
+        let mut state = TextAreaState::default();
+        key(&mut area, &mut state, Key::Home, CONTROL);
+
+        key(&mut area, &mut state, Key::Right, CONTROL);
+        assert_eq!(area.caret(), at(0, 5));
+        key(&mut area, &mut state, Key::Right, CONTROL);
+        assert_eq!(area.caret(), at(0, 10));
+        key(&mut area, &mut state, Key::Right, CONTROL);
+        assert_eq!(area.caret(), at(1, 0));
+        key(&mut area, &mut state, Key::Left, CONTROL);
+        assert_eq!(area.caret(), at(0, 10));
+        key(&mut area, &mut state, Key::Left, CONTROL);
+        assert_eq!(area.caret(), at(0, 6));
+    }
+
+    #[test]
+    fn selects_across_lines() {
+        let mut area = TextArea::new("one\ntwo\nthree");
+        let mut state = TextAreaState::default();
+
+        key(&mut area, &mut state, Key::Home, NONE);
+        key(&mut area, &mut state, Key::Left, SHIFT);
+        key(&mut area, &mut state, Key::Left, SHIFT);
+        assert_eq!(area.selected_text(), "o\n");
+
+        let modifiers = Modifiers {
+            shift: true,
+            ..CONTROL
+        };
+        key(&mut area, &mut state, Key::Home, modifiers);
+        assert_eq!(area.selected_text(), "one\ntwo\n");
+
+        // Moving without shift collapses the selection towards that side.
+        key(&mut area, &mut state, Key::Right, NONE);
+        assert_eq!(area.selection(), at(2, 0)..at(2, 0));
+
+        key(&mut area, &mut state, Key::Character('a'), CONTROL);
+        assert_eq!(area.selected_text(), "one\ntwo\nthree");
+        type_text(&mut area, &mut state, "x");
+        assert_eq!(area.text(), "x");
+    }
+
+    #[test]
+    fn counts_columns_in_characters() {
+        let mut area = TextArea::new("line\nnaïve café");
+        assert_eq!(area.line_column(), (1, 10));
+
+        area.set_selection(at(1, 0)..at(1, 4));
+        assert_eq!(area.line_column(), (1, 3));
+
+        // Positions are clamped and moved onto character boundaries.
+        area.set_selection(at(0, 0)..at(7, 3));
+        assert_eq!(area.caret(), at(1, 2));
+        assert_eq!(area.line_column(), (1, 2));
+    }
+
+    #[cfg(feature = "piet")]
+    #[test]
+    fn moves_in_order_before_a_frame() {
+        let mut layout = Layout::new();
+        let mut area = TextArea::new("one\ntwo\nthree");
+        let mut state = TextAreaState::default();
+        layout.arrange(&mut area);
+
+        // The selection starts where moving up left the caret.
+        key(&mut area, &mut state, Key::Up, NONE);
+        key(&mut area, &mut state, Key::Down, SHIFT);
+        layout.arrange(&mut area);
+        assert_eq!(area.selection(), at(1, 3)..at(2, 5));
+
+        // Moving right from the end of the line goes on to the next line.
+        key(&mut area, &mut state, Key::Up, NONE);
+        key(&mut area, &mut state, Key::Right, NONE);
+        type_text(&mut area, &mut state, "x");
+        assert_eq!(area.text(), "one\ntwo\nthree");
+        assert!(state.take_changed());
+
+        layout.arrange(&mut area);
+        assert_eq!(area.text(), "one\ntwo\nxthree");
+        assert_eq!(area.caret(), at(2, 1));
+    }
+
+    #[cfg(feature = "piet")]
+    #[test]
+    fn lays_out_edited_lines() {
+        let mut layout = Layout::new();
+        let mut area = TextArea::new("one\ntwo\nthree four five");
+        let mut state = TextAreaState::default();
+        layout.arrange(&mut area);
+        assert_eq!(layout.backend.context().layouts_made(), 3);
+        assert_eq!(layout.cache.tops, [0.0, 20.0, 40.0, 80.0]);
+
+        // Typing on one line only lays out that line.
+        area.set_selection(at(0, 3)..at(0, 3));
+        type_text(&mut area, &mut state, " more words");
+        layout.arrange(&mut area);
+        assert_eq!(layout.backend.context().layouts_made(), 4);
+        assert_eq!(layout.cache.tops, [0.0, 40.0, 60.0, 100.0]);
+
+        // Splitting a line lays out both halves, and moves the lines after them down.
+        area.set_selection(at(1, 1)..at(1, 1));
+        key(&mut area, &mut state, Key::Enter, NONE);
+        layout.arrange(&mut area);
+        assert_eq!(layout.backend.context().layouts_made(), 6);
+        assert_eq!(layout.cache.tops, [0.0, 40.0, 60.0, 80.0, 120.0]);
+
+        // Joining them again lays out the joined line.
+        key(&mut area, &mut state, Key::Backspace, NONE);
+        layout.arrange(&mut area);
+        assert_eq!(layout.backend.context().layouts_made(), 7);
+        assert_eq!(layout.cache.tops, [0.0, 40.0, 60.0, 100.0]);
+        assert_eq!(layout.cache.layout(1).text(), "two");
+    }
+
+    #[cfg(feature = "piet")]
+    #[test]
+    fn drag_before_a_frame() {
+        let mut layout = Layout::new();
+        let mut area = TextArea::new("hello world");
+        let mut state = TextAreaState::default();
+        layout.arrange(&mut area);
+
+        let padding = PADDING as f64;
+        let mut press = PointerEvent::new(padding + 20.0, padding + 5.0);
+        press.button = Some(PointerButton::Primary);
+        area.handle_event(&mut state, Event::PointerDown(press));
+        for x in [40.0, 50.0] {
+            let drag = PointerEvent::new(padding + x, padding + 5.0);
+            area.handle_event(&mut state, Event::PointerMove(drag));
+        }
+
+        layout.arrange(&mut area);
+        assert_eq!(area.selected_text(), "llo");
+
+        // The second row of the wrapped line starts after ten characters.
+        let drag = PointerEvent::new(padding + 10.0, padding + 25.0);
+        area.handle_event(&mut state, Event::PointerMove(drag));
+        layout.arrange(&mut area);
+        assert_eq!(area.selected_text(), "llo world");
+    }
+}
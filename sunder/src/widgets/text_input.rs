@@ -0,0 +1,811 @@
+/*
+
+`sunder` is free software: you can redistribute it and/or modify it under the terms of one of
+the following licenses:
+
+- The GNU Affero General Public License as published by the Free Software Foundation, either version
+  3 of the License, or (at your option) any later version.
+- The Patron License at https://github.com/notgull/sunder/blob/main/LICENSE-PATRON.md, for
+  sponsors and contributors, who can ignore the copyleft provisions of the GNU AGPL for this project.
+
+`sunder` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+Public License and the Patron License for more details.
+
+You should have received a copy of the GNU Affero General Public License and the corresponding Patron
+License along with `sunder`. If not, see <https://www.gnu.org/licenses/>.
+
+*/
+
+//! A single line of editable text.
+
+use super::editing::{floor_boundary, next_char, next_word, previous_char, previous_word};
+use crate::{Damage, Event, Key, KeyEvent, Modifiers, PointerKind, Size, Widget};
+use alloc::string::String;
+use core::ops::Range;
+
+cfg_piet! {
+    use crate::piet::ui_theme::Theme;
+    use crate::piet::{draw_box, BoxStyle, PietBackend};
+    use crate::{Backend, BoxConstraints, Rectangle, RenderedWidget};
+    use alloc::format;
+    use piet::kurbo::{Point, Rect};
+    use piet::{PaintBrush, RenderContext, Text, TextLayout, TextLayoutBuilder};
+
+    /// The space between the edge of the input and its text.
+    const PADDING: u32 = 4;
+
+    /// The width of an input that isn't given a width by its parent.
+    const DEFAULT_WIDTH: u32 = 200;
+}
+
+/// A single line of editable text.
+///
+/// The text can be edited with the keyboard while the input has focus. The arrow keys move the
+/// caret by character, or by word while control is held. Home and end move it to the start and
+/// end of the line. Holding shift while moving the caret selects text, as does dragging the mouse
+/// across it. Control+A selects all of the text.
+///
+/// The box around the text is styled by the `text_input` theme class, or `text_input:focus`
+/// while the input has focus. The selection is filled like the `selection` class, or
+/// `selection:focus`, and the caret like the `caret` class.
+///
+/// Positions in the text are byte offsets, and always lie on character boundaries.
+pub struct TextInput {
+    /// The text being edited.
+    text: String,
+
+    /// The position of the caret.
+    caret: usize,
+
+    /// The end of the selection that doesn't move with the caret.
+    ///
+    /// Nothing is selected if this is the same as the caret.
+    anchor: usize,
+
+    /// A pointer position that hasn't been turned into a text position yet.
+    ///
+    /// Events come in without the text layout, so pointer positions are looked up the next time
+    /// the input is arranged.
+    #[cfg(feature = "piet")]
+    pending: Option<PendingPointer>,
+
+    /// The pointer that is selecting text, if any.
+    dragging: Option<PointerKind>,
+
+    /// How far the text is scrolled to the left, to keep the caret in view.
+    #[cfg(feature = "piet")]
+    scroll: f64,
+
+    /// The size that the input was last arranged at.
+    size: Size,
+}
+
+/// Pointer positions waiting to be looked up in the text layout.
+///
+/// A press and a drag can both arrive before the next frame, so the press that starts the
+/// selection is kept apart from where the pointer has moved to since.
+#[cfg(feature = "piet")]
+#[derive(Debug, Clone, Copy)]
+struct PendingPointer {
+    /// Where a new selection starts, relative to the input.
+    ///
+    /// This is `None` when the current selection is being extended.
+    anchor: Option<f64>,
+
+    /// Where the caret moves to, relative to the input.
+    caret: f64,
+}
+
+/// The immediate state of a [`TextInput`].
+#[derive(Default)]
+pub struct TextInputState {
+    /// Whether the input has keyboard focus.
+    focused: bool,
+
+    /// Whether the text has been edited since this was last checked.
+    changed: bool,
+
+    /// Whether enter has been pressed since this was last checked.
+    submitted: bool,
+}
+
+impl TextInputState {
+    /// Whether the input has keyboard focus.
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    /// Check whether the text has been edited, resetting the flag.
+    pub fn take_changed(&mut self) -> bool {
+        core::mem::take(&mut self.changed)
+    }
+
+    /// Check whether enter has been pressed, resetting the flag.
+    pub fn take_submitted(&mut self) -> bool {
+        core::mem::take(&mut self.submitted)
+    }
+}
+
+cfg_piet! {
+    pub struct PietCache<R: RenderContext + ?Sized> {
+        /// Text layout.
+        layout: Option<R::TextLayout>,
+    }
+
+    impl<R: RenderContext + ?Sized> Default for PietCache<R> {
+        fn default() -> Self {
+            Self { layout: None }
+        }
+    }
+
+    impl<R: RenderContext + ?Sized> PietCache<R> {
+        fn populate(&mut self, text: &str, ctx: &mut R) -> Result<&R::TextLayout, piet::Error> {
+            use alloc::string::ToString;
+
+            let stale = !matches!(&self.layout, Some(layout) if layout.text() == text);
+            if stale {
+                self.layout = Some(ctx.text().new_text_layout(text.to_string()).build()?);
+            }
+
+            Ok(self.layout.as_ref().unwrap())
+        }
+    }
+
+    /// How the theme paints a widget that edits text.
+    pub(super) struct TextStyles {
+        /// The box around the text.
+        pub(super) frame: BoxStyle,
+
+        /// What fills the selection, from the `selection` class, or `selection:focus` while the
+        /// widget has focus.
+        pub(super) selection: Option<PaintBrush>,
+
+        /// What fills the caret, from the `caret` class.
+        pub(super) caret: Option<PaintBrush>,
+    }
+
+    impl TextStyles {
+        /// Look up the styles of a widget whose frame is styled by `class`, or by `class:focus`
+        /// while the widget has focus.
+        pub(super) fn new(theme: &Theme, class: &str, focused: bool) -> Self {
+            let focus = format!("{class}:focus");
+            let frame = focused.then_some(focus.as_str()).into_iter().chain(Some(class));
+            let selection = focused
+                .then_some("selection:focus")
+                .into_iter()
+                .chain(Some("selection"));
+
+            Self {
+                frame: BoxStyle::from_classes(theme, frame),
+                selection: BoxStyle::from_classes(theme, selection).background,
+                caret: BoxStyle::from_classes(theme, ["caret"]).background,
+            }
+        }
+    }
+}
+
+impl TextInput {
+    /// Create a new text input.
+    ///
+    /// The caret starts at the end of the text.
+    pub fn new(text: impl Into<String>) -> Self {
+        let text = text.into();
+
+        Self {
+            caret: text.len(),
+            anchor: text.len(),
+            text,
+            #[cfg(feature = "piet")]
+            pending: None,
+            dragging: None,
+            #[cfg(feature = "piet")]
+            scroll: 0.0,
+            size: Size::default(),
+        }
+    }
+
+    /// Get the text.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Replace the text, moving the caret to the end of it.
+    pub fn set_text(&mut self, text: impl Into<String>) {
+        self.text = text.into();
+        self.caret = self.text.len();
+        self.anchor = self.caret;
+    }
+
+    /// Get the position of the caret.
+    pub fn caret(&self) -> usize {
+        self.caret
+    }
+
+    /// Get the selected range of the text.
+    ///
+    /// The range is empty if nothing is selected.
+    pub fn selection(&self) -> Range<usize> {
+        self.caret.min(self.anchor)..self.caret.max(self.anchor)
+    }
+
+    /// Get the selected text.
+    pub fn selected_text(&self) -> &str {
+        &self.text[self.selection()]
+    }
+
+    /// Select a range of the text, placing the caret at its end.
+    ///
+    /// The range is clamped to the text, and moved back onto character boundaries.
+    pub fn set_selection(&mut self, range: Range<usize>) {
+        self.anchor = floor_boundary(&self.text, range.start);
+        self.caret = floor_boundary(&self.text, range.end);
+    }
+
+    /// Select all of the text.
+    pub fn select_all(&mut self) {
+        self.anchor = 0;
+        self.caret = self.text.len();
+    }
+
+    /// Move the caret, extending the selection or collapsing it.
+    fn move_caret(&mut self, position: usize, extend: bool) {
+        self.caret = position;
+        if !extend {
+            self.anchor = position;
+        }
+    }
+
+    /// Replace the selection with some text, leaving the caret after it.
+    fn insert(&mut self, text: &str) {
+        let selection = self.selection();
+        self.text.replace_range(selection.clone(), text);
+        self.move_caret(selection.start + text.len(), false);
+    }
+
+    /// Delete the selection, or the text between the caret and another position if nothing is
+    /// selected.
+    ///
+    /// Returns `true` if anything was deleted.
+    fn delete_towards(&mut self, position: usize) -> bool {
+        if self.caret == self.anchor {
+            self.anchor = position;
+        }
+
+        let selection = self.selection();
+        if selection.is_empty() {
+            return false;
+        }
+
+        self.insert("");
+        true
+    }
+
+    /// Reply to a key being pressed.
+    ///
+    /// Returns `true` if the text was edited.
+    fn key_down(&mut self, key: Key, modifiers: Modifiers) -> bool {
+        let extend = modifiers.shift;
+        let by_word = modifiers.control || modifiers.alt;
+        let shortcut = modifiers.control || modifiers.logo;
+        let selection = self.selection();
+
+        // Moving without shift collapses the selection to the side being moved towards.
+        let collapse = !extend && !selection.is_empty();
+
+        match key {
+            Key::Left if collapse => self.move_caret(selection.start, false),
+            Key::Right if collapse => self.move_caret(selection.end, false),
+
+            Key::Left if by_word => self.move_caret(previous_word(&self.text, self.caret), extend),
+            Key::Right if by_word => self.move_caret(next_word(&self.text, self.caret), extend),
+            Key::Left => self.move_caret(previous_char(&self.text, self.caret), extend),
+            Key::Right => self.move_caret(next_char(&self.text, self.caret), extend),
+
+            // There's only one line, so moving up or down goes to its ends.
+            Key::Home | Key::Up => self.move_caret(0, extend),
+            Key::End | Key::Down => self.move_caret(self.text.len(), extend),
+
+            Key::Character('a' | 'A') if shortcut => self.select_all(),
+
+            Key::Backspace => {
+                let target = if by_word {
+                    previous_word(&self.text, self.caret)
+                } else {
+                    previous_char(&self.text, self.caret)
+                };
+
+                return self.delete_towards(target);
+            }
+
+            Key::Delete => {
+                let target = if by_word {
+                    next_word(&self.text, self.caret)
+                } else {
+                    next_char(&self.text, self.caret)
+                };
+
+                return self.delete_towards(target);
+            }
+
+            _ => {}
+        }
+
+        false
+    }
+
+    /// Whether a point is inside of the input.
+    fn contains(&self, x: f64, y: f64) -> bool {
+        x >= 0.0 && y >= 0.0 && x < self.size.width as f64 && y < self.size.height as f64
+    }
+
+    /// Move the caret to a pointer position, once the text has been laid out.
+    ///
+    /// Without a backend that lays out text, the caret can only be moved with the keyboard.
+    fn point_at(&mut self, x: f64, extend: bool) {
+        #[cfg(feature = "piet")]
+        {
+            // Extending keeps the start of a selection that hasn't been looked up yet.
+            let anchor = if extend {
+                self.pending.and_then(|pointer| pointer.anchor)
+            } else {
+                Some(x)
+            };
+
+            self.pending = Some(PendingPointer { anchor, caret: x });
+        }
+
+        #[cfg(not(feature = "piet"))]
+        let _ = (x, extend);
+    }
+
+    /// Move the caret to the pending pointer positions, given a way to find the text position
+    /// under a pointer position.
+    #[cfg(feature = "piet")]
+    fn resolve_pointer(&mut self, mut position: impl FnMut(f64) -> usize) {
+        if let Some(pointer) = self.pending.take() {
+            if let Some(anchor) = pointer.anchor {
+                let anchor = floor_boundary(&self.text, position(anchor));
+                self.move_caret(anchor, false);
+            }
+
+            let caret = floor_boundary(&self.text, position(pointer.caret));
+            self.move_caret(caret, true);
+        }
+    }
+}
+
+impl Widget for TextInput {
+    type Immediate<'a> = TextInputState;
+
+    fn handle_event(&mut self, immediate: &mut Self::Immediate<'_>, event: Event) -> Damage {
+        match event {
+            Event::PointerDown(pointer)
+                if pointer.is_primary() && self.contains(pointer.x, pointer.y) =>
+            {
+                self.point_at(pointer.x, pointer.modifiers.shift);
+                self.dragging = Some(pointer.kind);
+                Damage::Full
+            }
+
+            Event::PointerMove(pointer) if self.dragging == Some(pointer.kind) => {
+                self.point_at(pointer.x, true);
+                Damage::Full
+            }
+
+            Event::PointerUp(pointer) | Event::PointerCancel(pointer) => {
+                // Only letting go of the pointer that is selecting stops the selection.
+                let released = pointer.is_primary() || matches!(event, Event::PointerCancel(_));
+                if released && self.dragging == Some(pointer.kind) {
+                    self.dragging = None;
+                }
+
+                Damage::None
+            }
+
+            Event::KeyDown(KeyEvent {
+                key: Key::Enter, ..
+            }) => {
+                immediate.submitted = true;
+                Damage::None
+            }
+
+            Event::KeyDown(KeyEvent { key, modifiers, .. }) => {
+                let before = (self.caret, self.anchor);
+                let edited = self.key_down(key, modifiers);
+                immediate.changed |= edited;
+
+                Damage::from(edited || (self.caret, self.anchor) != before)
+            }
+
+            // There's only one line, so line breaks can't be typed.
+            Event::Text(text) => {
+                let text = text.replace(['\n', '\r'], "");
+                if text.is_empty() {
+                    return Damage::None;
+                }
+
+                self.insert(&text);
+                immediate.changed = true;
+                Damage::Full
+            }
+
+            Event::FocusIn => {
+                immediate.focused = true;
+                Damage::Full
+            }
+
+            Event::FocusOut => {
+                immediate.focused = false;
+                self.dragging = None;
+                Damage::Full
+            }
+
+            _ => Damage::None,
+        }
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn captures_pointer(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(feature = "piet")]
+impl<R: RenderContext> RenderedWidget<PietBackend<R>> for TextInput {
+    type Cache = PietCache<R>;
+
+    fn measure(
+        &mut self,
+        constraints: BoxConstraints,
+        cache: &mut Self::Cache,
+        backend: &mut PietBackend<R>,
+    ) -> Result<Size, <PietBackend<R> as Backend>::Error> {
+        let layout = cache.populate(&self.text, backend.context())?;
+
+        // Take up all of the width we are offered, since the text can grow.
+        let width = if constraints.has_bounded_width() {
+            constraints.max.width
+        } else {
+            DEFAULT_WIDTH
+        };
+
+        Ok(constraints.constrain(Size {
+            width,
+            height: layout.size().height.ceil() as u32 + PADDING * 2,
+        }))
+    }
+
+    fn arrange(
+        &mut self,
+        region: Rectangle,
+        cache: &mut Self::Cache,
+        backend: &mut PietBackend<R>,
+    ) -> Result<(), piet::Error> {
+        self.size = Size {
+            width: region.width,
+            height: region.height,
+        };
+
+        let layout = cache.populate(&self.text, backend.context())?;
+
+        // Now that we have the layout, find out where the pointer is in the text.
+        let (scroll, middle) = (self.scroll, layout.size().height / 2.0);
+        self.resolve_pointer(|x| {
+            let point = Point::new(x - PADDING as f64 + scroll, middle);
+            layout.hit_test_point(point).idx
+        });
+
+        // Scroll as little as possible to keep the caret in view.
+        let visible = region.width.saturating_sub(PADDING * 2) as f64;
+        let caret = layout.hit_test_text_position(self.caret).point.x;
+        let overflow = (layout.size().width - visible).max(0.0);
+
+        if caret - self.scroll > visible {
+            self.scroll = caret - visible;
+        } else if caret < self.scroll {
+            self.scroll = caret;
+        }
+        self.scroll = self.scroll.clamp(0.0, overflow);
+
+        Ok(())
+    }
+
+    fn baseline(&self, cache: &Self::Cache) -> Option<u32> {
+        let line = cache.layout.as_ref()?.line_metric(0)?;
+        Some((line.y_offset + line.baseline).round() as u32 + PADDING)
+    }
+
+    fn render(
+        &self,
+        immediate: &Self::Immediate<'_>,
+        cache: &mut Self::Cache,
+        backend: &mut PietBackend<R>,
+    ) -> Result<(), piet::Error> {
+        let styles = TextStyles::new(backend.theme(), "text_input", immediate.focused);
+
+        // Draw the box around the text.
+        let frame = Rectangle {
+            x: 0,
+            y: 0,
+            width: self.size.width,
+            height: self.size.height,
+        };
+        draw_box(backend.context(), &styles.frame, frame)?;
+
+        let rc = backend.context();
+        let layout = cache.populate(&self.text, rc)?;
+
+        // Keep the text inside of the padding.
+        let (width, height) = (self.size.width as f64, self.size.height as f64);
+        rc.save()?;
+        rc.clip(Rect::new(
+            PADDING as f64,
+            0.0,
+            width - PADDING as f64,
+            height,
+        ));
+
+        let origin = Point::new(PADDING as f64 - self.scroll, PADDING as f64);
+        let line_height = layout.size().height;
+
+        // Highlight the selection behind the text.
+        let selection = self.selection();
+        match &styles.selection {
+            Some(highlight) if !selection.is_empty() => {
+                let start = layout.hit_test_text_position(selection.start).point.x;
+                let end = layout.hit_test_text_position(selection.end).point.x;
+                rc.fill(
+                    Rect::new(start, 0.0, end, line_height) + origin.to_vec2(),
+                    highlight,
+                );
+            }
+            _ => {}
+        }
+
+        rc.draw_text(layout, origin);
+
+        match &styles.caret {
+            Some(caret) if immediate.focused => {
+                let x = origin.x + layout.hit_test_text_position(self.caret).point.x;
+                rc.fill(
+                    Rect::new(x - 0.5, origin.y, x + 0.5, origin.y + line_height),
+                    caret,
+                );
+            }
+            _ => {}
+        }
+
+        rc.restore()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[cfg(feature = "piet")]
+    use crate::piet::testing::{Brush, Op, RecordingContext};
+    #[cfg(feature = "piet")]
+    use crate::piet::ui_theme::{self, Background, WidgetProperties};
+    #[cfg(feature = "piet")]
+    use crate::{PointerButton, PointerEvent};
+    #[cfg(feature = "piet")]
+    use alloc::rc::Rc;
+    #[cfg(feature = "piet")]
+    use alloc::vec::Vec;
+    #[cfg(feature = "piet")]
+    use piet::Color;
+
+    const NONE: Modifiers = Modifiers {
+        shift: false,
+        control: false,
+        alt: false,
+        logo: false,
+    };
+
+    const SHIFT: Modifiers = Modifiers {
+        shift: true,
+        ..NONE
+    };
+
+    const CONTROL: Modifiers = Modifiers {
+        control: true,
+        ..NONE
+    };
+
+    fn key(input: &mut TextInput, state: &mut TextInputState, key: Key, modifiers: Modifiers) {
+        input.handle_event(state, Event::KeyDown(KeyEvent::new(key, modifiers)));
+    }
+
+    /// Press the primary button at a position.
+    #[cfg(feature = "piet")]
+    fn press(x: f64, shift: bool) -> Event {
+        let mut pointer = PointerEvent::new(x, 5.0);
+        pointer.button = Some(PointerButton::Primary);
+        pointer.modifiers.shift = shift;
+        Event::PointerDown(pointer)
+    }
+
+    fn input(text: &str) -> (TextInput, TextInputState) {
+        let mut input = TextInput::new(text);
+        input.size = Size {
+            width: 200,
+            height: 20,
+        };
+
+        (input, TextInputState::default())
+    }
+
+    #[test]
+    fn inserts_text() {
+        let (mut input, mut state) = input("hello");
+
+        input.handle_event(&mut state, Event::Text(" world".to_string()));
+        assert_eq!(input.text, "hello world");
+        assert_eq!(input.caret, 11);
+        assert!(state.take_changed());
+
+        // Line breaks are dropped, and typing replaces the selection.
+        input.set_selection(0..5);
+        input.handle_event(&mut state, Event::Text("bye\n".to_string()));
+        assert_eq!(input.text, "bye world");
+        assert_eq!(input.selection(), 3..3);
+    }
+
+    #[test]
+    fn deletes_text() {
+        let (mut input, mut state) = input("one two three");
+
+        key(&mut input, &mut state, Key::Backspace, NONE);
+        assert_eq!(input.text, "one two thre");
+
+        key(&mut input, &mut state, Key::Backspace, CONTROL);
+        assert_eq!(input.text, "one two ");
+
+        key(&mut input, &mut state, Key::Home, NONE);
+        key(&mut input, &mut state, Key::Delete, NONE);
+        assert_eq!(input.text, "ne two ");
+
+        key(&mut input, &mut state, Key::Delete, CONTROL);
+        assert_eq!(input.text, " two ");
+        assert!(state.take_changed());
+
+        // There's nothing before the caret to delete.
+        key(&mut input, &mut state, Key::Backspace, NONE);
+        assert_eq!(input.text, " two ");
+        assert!(!state.take_changed());
+    }
+
+    #[test]
+    fn moves_by_word() {
+        let (mut input, mut state) = input("let x_1 = 42;");
+
+        key(&mut input, &mut state, Key::Left, CONTROL);
+        assert_eq!(input.caret, 10);
+        key(&mut input, &mut state, Key::Left, CONTROL);
+        assert_eq!(input.caret, 4);
+        key(&mut input, &mut state, Key::Right, CONTROL);
+        assert_eq!(input.caret, 7);
+    }
+
+    #[test]
+    fn selects_with_shift() {
+        let (mut input, mut state) = input("héllo");
+
+        key(&mut input, &mut state, Key::Left, SHIFT);
+        key(&mut input, &mut state, Key::Left, SHIFT);
+        assert_eq!(input.selected_text(), "lo");
+
+        // Moving without shift collapses the selection towards that side.
+        key(&mut input, &mut state, Key::Left, NONE);
+        assert_eq!(input.selection(), 4..4);
+
+        // Characters that take up more than one byte are moved over whole.
+        key(&mut input, &mut state, Key::Left, NONE);
+        key(&mut input, &mut state, Key::Left, SHIFT);
+        assert_eq!(input.selected_text(), "é");
+    }
+
+    #[test]
+    fn selects_all() {
+        let (mut input, mut state) = input("hello");
+        key(&mut input, &mut state, Key::Home, NONE);
+
+        key(&mut input, &mut state, Key::Character('a'), CONTROL);
+        assert_eq!(input.selected_text(), "hello");
+
+        key(&mut input, &mut state, Key::Backspace, NONE);
+        assert_eq!(input.text, "");
+    }
+
+    #[cfg(feature = "piet")]
+    #[test]
+    fn drag_before_a_frame() {
+        let (mut input, mut state) = input("hello world");
+
+        input.handle_event(&mut state, press(20.0, false));
+        input.handle_event(&mut state, Event::PointerMove(PointerEvent::new(50.0, 5.0)));
+
+        // Every character is ten units wide.
+        input.resolve_pointer(|x| (x / 10.0) as usize);
+        assert_eq!(input.selection(), 2..5);
+        assert_eq!(input.caret, 5);
+
+        // Dragging on from there keeps the start of the selection.
+        input.handle_event(&mut state, Event::PointerMove(PointerEvent::new(90.0, 5.0)));
+        input.resolve_pointer(|x| (x / 10.0) as usize);
+        assert_eq!(input.selected_text(), "llo wor");
+    }
+
+    #[cfg(feature = "piet")]
+    #[test]
+    fn shift_press_extends_the_selection() {
+        let (mut input, mut state) = input("hello world");
+        input.move_caret(1, false);
+
+        input.handle_event(&mut state, press(40.0, true));
+        input.handle_event(&mut state, Event::PointerMove(PointerEvent::new(70.0, 5.0)));
+        input.resolve_pointer(|x| (x / 10.0) as usize);
+        assert_eq!(input.selection(), 1..7);
+    }
+
+    #[cfg(feature = "piet")]
+    #[test]
+    fn paints_from_the_theme() {
+        let mut theme = ui_theme::Theme::new();
+        for (class, [red, green, blue]) in [
+            ("text_input", [0xff, 0xff, 0xff]),
+            ("text_input:focus", [0xee, 0xee, 0xff]),
+            ("selection", [0xcc, 0xcc, 0xcc]),
+            ("selection:focus", [0xaa, 0xcc, 0xff]),
+            ("caret", [0, 0, 0]),
+        ] {
+            let properties = WidgetProperties {
+                background: Some(Background::Color(ui_theme::Color::rgb(red, green, blue))),
+                ..Default::default()
+            };
+            theme.insert_class(class, properties);
+        }
+
+        let (mut input, mut state) = input("hello");
+        input.move_caret(1, false);
+        input.move_caret(3, true);
+
+        // The colors of everything that was filled, in order.
+        let mut backend = PietBackend::new(RecordingContext::new(), Rc::new(theme));
+        let mut fills = |input: &TextInput, state: &TextInputState| {
+            input
+                .render(state, &mut PietCache::default(), &mut backend)
+                .unwrap();
+            backend
+                .context()
+                .take_ops()
+                .into_iter()
+                .filter_map(|op| match op {
+                    Op::Fill {
+                        brush: Brush::Solid(color),
+                        ..
+                    } => Some(color),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(fills(&input, &state), [Color::WHITE, Color::grey8(0xcc)]);
+
+        state.focused = true;
+        assert_eq!(
+            fills(&input, &state),
+            [
+                Color::rgb8(0xee, 0xee, 0xff),
+                Color::rgb8(0xaa, 0xcc, 0xff),
+                Color::BLACK,
+            ]
+        );
+    }
+}
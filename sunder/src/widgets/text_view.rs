@@ -19,10 +19,12 @@ License along with `sunder`. If not, see <https://www.gnu.org/licenses/>.
 
 //! A text label.
 
-use crate::{RenderedWidget, Widget};
+use crate::{Damage, Widget};
+use alloc::borrow::Cow;
 
 cfg_piet! {
     use crate::piet::PietBackend;
+    use crate::{BoxConstraints, RenderedWidget};
     use piet::{RenderContext, TextLayout, Text, TextLayoutBuilder};
 }
 
@@ -31,31 +33,67 @@ cfg_piet! {
 /// This widget is the atomic unit used to render text.
 pub struct TextView<'a> {
     /// The text to display.
-    text: &'a str,
+    text: Cow<'a, str>,
 
     /// The maximum width of the label.
     max_width: Option<f64>,
     // TODO: Other text properties
 }
 
+impl<'a> TextView<'a> {
+    /// Create a new text view.
+    pub fn new(text: impl Into<Cow<'a, str>>) -> Self {
+        Self {
+            text: text.into(),
+            max_width: None,
+        }
+    }
+
+    /// Set the maximum width of the label.
+    ///
+    /// Text that is wider than this is wrapped onto the next line.
+    pub fn with_max_width(mut self, max_width: f64) -> Self {
+        self.max_width = Some(max_width);
+        self
+    }
+
+    /// Get the text of this label.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Replace the text of this label.
+    pub fn set_text(&mut self, text: impl Into<Cow<'a, str>>) {
+        self.text = text.into();
+    }
+}
+
 cfg_piet! {
     pub struct PietCache<R: RenderContext + ?Sized> {
         /// Text layout.
         layout: Option<R::TextLayout>,
+
+        /// The width that the text layout was wrapped to.
+        width: Option<f64>,
     }
 
     impl<R: RenderContext + ?Sized> Default for PietCache<R> {
         fn default() -> Self {
-            Self { layout: None }
+            Self { layout: None, width: None }
         }
     }
 
     impl<R: RenderContext + ?Sized> PietCache<R> {
-        fn populate(&mut self, label: &TextView<'_>, ctx: &mut R) -> Result<(), piet::Error> {
+        fn populate(
+            &mut self,
+            label: &TextView<'_>,
+            ctx: &mut R,
+            width: Option<f64>,
+        ) -> Result<(), piet::Error> {
             use alloc::string::ToString;
 
             if let Some(layout) = &self.layout {
-                if layout.text() == label.text {
+                if layout.text() == label.text() && self.width == width {
                     // No need to change anything.
                     return Ok(());
                 }
@@ -65,11 +103,12 @@ cfg_piet! {
             let mut layout = ctx
                 .text()
                 .new_text_layout(label.text.to_string());
-            if let Some(max_width) = label.max_width {
-                layout = layout.max_width(max_width);
+            if let Some(width) = width {
+                layout = layout.max_width(width);
             }
 
             self.layout = Some(layout.build()?);
+            self.width = width;
             Ok(())
         }
     }
@@ -78,38 +117,61 @@ cfg_piet! {
 impl Widget for TextView<'_> {
     type Immediate<'a> = ();
 
-    fn handle_event(&mut self, _immediate: &mut Self::Immediate<'_>, _event: crate::Event) -> bool {
+    fn handle_event(
+        &mut self,
+        _immediate: &mut Self::Immediate<'_>,
+        _event: crate::Event,
+    ) -> Damage {
         // We don't care about events.
         // TODO: Highlighting text?
-        false
+        Damage::None
     }
 }
 
 #[cfg(feature = "piet")]
-impl<R: RenderContext + ?Sized> RenderedWidget<PietBackend<'_, R>> for TextView<'_> {
+impl<R: RenderContext> RenderedWidget<PietBackend<R>> for TextView<'_> {
     type Cache = PietCache<R>;
 
-    fn rectangle(
+    fn measure(
         &mut self,
+        constraints: BoxConstraints,
         cache: &mut Self::Cache,
-        backend: &mut PietBackend<'_, R>,
+        backend: &mut PietBackend<R>,
     ) -> Result<crate::Size, piet::Error> {
-        cache.populate(self, backend.context())?;
+        // Wrap the text to the narrower of our maximum width and the space we are offered.
+        let offered = if constraints.has_bounded_width() {
+            Some(constraints.max.width as f64)
+        } else {
+            None
+        };
+        let width = match (self.max_width, offered) {
+            (Some(max_width), Some(offered)) => Some(max_width.min(offered)),
+            (max_width, offered) => max_width.or(offered),
+        };
+
+        cache.populate(self, backend.context(), width)?;
 
         let size = cache.layout.as_ref().unwrap().size();
-        Ok(crate::Size {
-            width: size.width as u32,
-            height: size.height as u32,
-        })
+        Ok(constraints.constrain(crate::Size {
+            width: size.width.ceil() as u32,
+            height: size.height.ceil() as u32,
+        }))
+    }
+
+    fn baseline(&self, cache: &Self::Cache) -> Option<u32> {
+        let line = cache.layout.as_ref()?.line_metric(0)?;
+        Some((line.y_offset + line.baseline).round() as u32)
     }
 
     fn render(
         &self,
         _: &(),
         cache: &mut Self::Cache,
-        backend: &mut PietBackend<'_, R>,
+        backend: &mut PietBackend<R>,
     ) -> Result<(), piet::Error> {
-        cache.populate(self, backend.context())?;
+        // Keep the layout from the last measurement, unless the text has changed since.
+        let width = cache.width;
+        cache.populate(self, backend.context(), width)?;
         backend
             .context()
             .draw_text(cache.layout.as_ref().unwrap(), (0.0, 0.0));
@@ -119,11 +181,12 @@ impl<R: RenderContext + ?Sized> RenderedWidget<PietBackend<'_, R>> for TextView<
 }
 
 cfg_web! {
-    impl RenderedWidget<crate::web::HtmlBackend> for TextView<'_> {
+    impl crate::RenderedWidget<crate::web::HtmlBackend> for TextView<'_> {
         type Cache = ();
 
-        fn rectangle(
+        fn measure(
             &mut self,
+            _constraints: crate::BoxConstraints,
             _: &mut Self::Cache,
             _backend: &mut crate::web::HtmlBackend,
         ) -> Result<crate::Size, crate::web::Error> {
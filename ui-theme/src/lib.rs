@@ -0,0 +1,187 @@
+/*
+
+`ui-theme` is free software: you can redistribute it and/or modify it under the terms of one of
+the following licenses:
+
+- The GNU Affero General Public License as published by the Free Software Foundation, either version
+  3 of the License, or (at your option) any later version.
+- The Patron License at https://github.com/notgull/sunder/blob/main/LICENSE-PATRON.md, for
+  sponsors and contributors, who can ignore the copyleft provisions of the GNU AGPL for this project.
+
+`ui-theme` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+Public License and the Patron License for more details.
+
+You should have received a copy of the GNU Affero General Public License and the corresponding Patron
+License along with `ui-theme`. If not, see <https://www.gnu.org/licenses/>.
+
+*/
+
+//! Theme classes that describe how widgets are painted.
+//!
+//! A [`Theme`] maps class names, such as `"button"` or `"button:hover"`, to the
+//! [`WidgetProperties`] that a widget in that state is painted with. The properties only describe
+//! the box around a widget: its background, border, corners, shadow and padding. How the box is
+//! painted is up to the backend.
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// An 8-bit RGBA color.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Color {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+    pub alpha: u8,
+}
+
+impl Color {
+    /// Create an opaque color.
+    pub const fn rgb(red: u8, green: u8, blue: u8) -> Self {
+        Self::rgba(red, green, blue, 0xFF)
+    }
+
+    /// Create a color with the given opacity.
+    pub const fn rgba(red: u8, green: u8, blue: u8, alpha: u8) -> Self {
+        Self {
+            red,
+            green,
+            blue,
+            alpha,
+        }
+    }
+}
+
+/// What fills the inside of a box.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Background {
+    /// A single color.
+    Color(Color),
+
+    /// A gradient along a line.
+    ///
+    /// `start` and `end` are relative to the box, where `(0.0, 0.0)` is its top left corner and
+    /// `(1.0, 1.0)` is its bottom right corner. Each stop is a position along the line, from `0.0`
+    /// to `1.0`, and the color at that position.
+    LinearGradient {
+        start: (f64, f64),
+        end: (f64, f64),
+        stops: Vec<(f32, Color)>,
+    },
+}
+
+/// A value for each edge of a box.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Edges<T> {
+    pub top: T,
+    pub right: T,
+    pub bottom: T,
+    pub left: T,
+}
+
+impl<T: Clone> Edges<T> {
+    /// Use the same value for every edge.
+    pub fn all(value: T) -> Self {
+        Self {
+            top: value.clone(),
+            right: value.clone(),
+            bottom: value.clone(),
+            left: value,
+        }
+    }
+}
+
+/// One side of the border around a box.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BorderSide {
+    /// The width of the border, in pixels. A width of zero draws nothing.
+    pub width: f64,
+
+    /// The color of the border.
+    pub color: Color,
+}
+
+/// The radius of each corner of a box, in pixels.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Corners {
+    pub top_left: f64,
+    pub top_right: f64,
+    pub bottom_right: f64,
+    pub bottom_left: f64,
+}
+
+impl Corners {
+    /// Use the same radius for every corner.
+    pub fn all(radius: f64) -> Self {
+        Self {
+            top_left: radius,
+            top_right: radius,
+            bottom_right: radius,
+            bottom_left: radius,
+        }
+    }
+}
+
+/// A shadow cast by a box.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BoxShadow {
+    /// How far the shadow is moved from the box, in pixels.
+    pub offset: (f64, f64),
+
+    /// The radius of the blur applied to the shadow.
+    pub blur: f64,
+
+    /// How far the shadow grows past the edges of the box before it is blurred.
+    pub spread: f64,
+
+    /// The color of the shadow.
+    pub color: Color,
+}
+
+/// How the box around a widget is painted.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WidgetProperties {
+    /// What fills the box, if anything.
+    pub background: Option<Background>,
+
+    /// The border on each side of the box.
+    pub border: Edges<BorderSide>,
+
+    /// The radius of each corner of the box.
+    pub radius: Corners,
+
+    /// The shadow that the box casts, if any.
+    pub shadow: Option<BoxShadow>,
+
+    /// The space between the border and the contents of the box, in pixels.
+    pub padding: Edges<f64>,
+}
+
+/// A set of classes that widgets look up to decide how they are painted.
+#[derive(Debug, Clone, Default)]
+pub struct Theme {
+    classes: BTreeMap<String, WidgetProperties>,
+}
+
+impl Theme {
+    /// Create a theme without any classes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the properties of a class, if the theme has it.
+    pub fn class(&self, name: &str) -> Option<&WidgetProperties> {
+        self.classes.get(name)
+    }
+
+    /// Add a class to the theme, replacing any class with the same name.
+    pub fn insert_class(&mut self, name: impl Into<String>, properties: WidgetProperties) {
+        self.classes.insert(name.into(), properties);
+    }
+}